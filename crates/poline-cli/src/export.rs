@@ -0,0 +1,87 @@
+//! `poline export` writes a palette in any format of the exporter registry.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use poline_core::{registry, Poline};
+
+use crate::CliError;
+
+pub const USAGE: &str = "poline export <format> [--from <share string>] [--out <file>]";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    pub format: String,
+    pub from: Option<String>,
+    /// Standard output when `None`
+    pub out: Option<PathBuf>,
+}
+
+impl ExportOptions {
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut args = args.iter().map(String::as_str);
+        let format = args.next().filter(|format| !format.starts_with("--"));
+        let mut options = ExportOptions {
+            format: format.ok_or(CliError::Usage(USAGE))?.to_string(),
+            from: None,
+            out: None,
+        };
+        while let Some(arg) = args.next() {
+            match arg {
+                "--from" => options.from = Some(args.next().ok_or(CliError::Usage(USAGE))?.into()),
+                "--out" => options.out = Some(args.next().ok_or(CliError::Usage(USAGE))?.into()),
+                _ => return Err(CliError::Usage(USAGE)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let options = ExportOptions::parse(args)?;
+    let Some(exporter) = registry().get(&options.format) else {
+        return Err(CliError::UnknownFormat {
+            format: options.format,
+            known: registry().names().join(", "),
+        });
+    };
+    let poline = match &options.from {
+        Some(share) => Poline::from_share_string(share)?,
+        None => Poline::default(),
+    };
+    let bytes = exporter.export(&poline);
+    match &options.out {
+        Some(out) => fs::write(out, bytes)?,
+        None => io::stdout().lock().write_all(&bytes)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_format_and_flags() {
+        let options = ExportOptions::parse(&args("gpl --out palette.gpl")).unwrap();
+        assert_eq!(options.format, "gpl");
+        assert_eq!(options.out, Some(PathBuf::from("palette.gpl")));
+        assert!(ExportOptions::parse(&args("--out palette.gpl")).is_err());
+        assert!(ExportOptions::parse(&args("gpl --from")).is_err());
+    }
+
+    #[test]
+    fn lists_the_formats_of_unknown_ones() {
+        let Err(CliError::UnknownFormat { known, .. }) = run(&args("ase")) else {
+            panic!("ase is not built in");
+        };
+        assert!(known.split(", ").any(|name| name == "gpl"));
+    }
+}
@@ -0,0 +1,159 @@
+//! `poline gallery` writes a contact sheet of palettes as one HTML page.
+//!
+//! Every anchor preset is swept over each pair of x and y position
+//! functions. Each swatch row is captioned with the easings and the share
+//! string that rebuilds it, and the page only depends on the inputs, so
+//! two releases can be diffed for visual changes.
+
+use std::{fmt::Write, fs, path::PathBuf};
+
+use poline_core::{Poline, PolineOptions, PositionScale, Vector3};
+
+use crate::CliError;
+
+pub const USAGE: &str = "poline gallery [--points <n>] [--out <file>]";
+
+/// Anchor presets, as HSL colors
+const PRESETS: [(&str, &[Vector3]); 4] = [
+    ("dusk", &[Vector3(260.0, 0.6, 0.2), Vector3(20.0, 0.8, 0.7)]),
+    (
+        "forest",
+        &[
+            Vector3(150.0, 0.5, 0.15),
+            Vector3(90.0, 0.6, 0.55),
+            Vector3(50.0, 0.7, 0.9),
+        ],
+    ),
+    ("ocean", &[Vector3(220.0, 0.9, 0.1), Vector3(190.0, 0.7, 0.85)]),
+    (
+        "ember",
+        &[
+            Vector3(0.0, 0.0, 0.05),
+            Vector3(10.0, 0.9, 0.45),
+            Vector3(50.0, 1.0, 0.85),
+        ],
+    ),
+];
+
+const SWATCH_WIDTH: usize = 24;
+const SWATCH_HEIGHT: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GalleryOptions {
+    pub points: usize,
+    pub out: PathBuf,
+}
+
+impl GalleryOptions {
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = GalleryOptions {
+            points: 6,
+            out: PathBuf::from("gallery.html"),
+        };
+        let mut args = args.iter().map(String::as_str);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--points" => {
+                    let points = args.next().and_then(|n| n.parse().ok());
+                    options.points = points.ok_or(CliError::Usage(USAGE))?;
+                }
+                "--out" => options.out = args.next().ok_or(CliError::Usage(USAGE))?.into(),
+                _ => return Err(CliError::Usage(USAGE)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Every preset with every pair of x and y position functions
+pub fn palettes(points: usize) -> Result<Vec<(String, Poline)>, CliError> {
+    let mut palettes = Vec::new();
+    for (name, anchors) in PRESETS {
+        for &x in PositionScale::ALL {
+            for &y in PositionScale::ALL {
+                let poline = Poline::try_from_options(PolineOptions {
+                    anchor_colors: Some(anchors.to_vec()),
+                    num_points: points,
+                    position_function: x,
+                    position_function_y: Some(y),
+                    inverted_lightness: true,
+                    ..Default::default()
+                })?;
+                palettes.push((format!("{name} x={x} y={y}"), poline));
+            }
+        }
+    }
+    Ok(palettes)
+}
+
+/// The contact sheet as a standalone HTML page
+pub fn render(palettes: &[(String, Poline)]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>poline gallery</title>\n\
+         <style>body{font:12px sans-serif;display:flex;flex-wrap:wrap;gap:16px}\
+         figure{margin:0}code{font-size:10px}</style>\n</head>\n<body>\n",
+    );
+    for (label, poline) in palettes {
+        let colors = poline.css_strings();
+        let width = colors.len() * SWATCH_WIDTH;
+        let _ = writeln!(html, "<figure>");
+        let _ = writeln!(
+            html,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{SWATCH_HEIGHT}\">"
+        );
+        for (i, color) in colors.iter().enumerate() {
+            let _ = writeln!(
+                html,
+                "<rect x=\"{}\" width=\"{SWATCH_WIDTH}\" height=\"{SWATCH_HEIGHT}\" fill=\"{color}\"/>",
+                i * SWATCH_WIDTH
+            );
+        }
+        let _ = writeln!(html, "</svg>");
+        let _ = writeln!(
+            html,
+            "<figcaption>{label}<br><code>{}</code></figcaption>\n</figure>",
+            poline.to_share_string()
+        );
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let options = GalleryOptions::parse(args)?;
+    let palettes = palettes(options.points)?;
+    fs::write(&options.out, render(&palettes))?;
+    println!("wrote {} palettes to {}", palettes.len(), options.out.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_flags() {
+        let options = GalleryOptions::parse(&args("--points 3 --out sheet.html")).unwrap();
+        assert_eq!(options.points, 3);
+        assert_eq!(options.out, PathBuf::from("sheet.html"));
+        assert!(GalleryOptions::parse(&args("--points many")).is_err());
+        assert!(GalleryOptions::parse(&args("--out")).is_err());
+    }
+
+    #[test]
+    fn sweeps_every_preset_and_easing_pair() {
+        let palettes = palettes(2).unwrap();
+        assert_eq!(palettes.len(), PRESETS.len() * 81);
+        assert_eq!(palettes[1].0, "dusk x=linear y=exponential");
+
+        let html = render(&palettes);
+        assert_eq!(html.matches("<figure>").count(), palettes.len());
+        let share = palettes[0].1.to_share_string();
+        assert!(html.contains(&share));
+        assert!(Poline::from_share_string(&share).is_ok());
+    }
+}
@@ -0,0 +1,61 @@
+use std::{io, process::ExitCode};
+
+use poline_core::{ParseError, Poline, PolineErrors};
+
+mod export;
+mod gallery;
+mod repl;
+mod theme;
+
+const USAGE: &str = "Usage: poline <command>
+
+Commands:
+  export   Write a palette in one of the registered formats
+  gallery  Write an HTML contact sheet of presets swept over the easings
+  repl     Edit a palette interactively, `help` lists the commands
+  theme    Write a palette and its variants as Rust and CSS tokens";
+
+#[derive(thiserror::Error, Debug)]
+pub enum CliError {
+    #[error("unknown command `{0}`, try `help`")]
+    UnknownCommand(String),
+    #[error("usage: {0}")]
+    Usage(&'static str),
+    #[error("unknown format `{format}`, one of {known}")]
+    UnknownFormat { format: String, known: String },
+    #[error("`{0}` is a binary format, write it with `poline export {0} --out <file>`")]
+    BinaryFormat(String),
+    #[error("`{palette}` only reaches a contrast of {ratio:.2}:1")]
+    Contrast { palette: String, ratio: f32 },
+    #[error("{0}")]
+    Poline(#[from] PolineErrors),
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("repl") => repl::run(Poline::default(), io::stdin().lock(), io::stdout().lock()),
+        Some("export") => export::run(&args[1..]),
+        Some("gallery") => gallery::run(&args[1..]),
+        Some("theme") => theme::run(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("poline: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
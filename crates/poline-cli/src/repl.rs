@@ -0,0 +1,264 @@
+//! A line based editor for a live palette. Every command that changes the
+//! palette prints its new ANSI preview.
+
+use std::io::{BufRead, Write};
+
+use poline_core::{
+    parse_css_color_detailed, registry, ColorPointCollection, Poline, PolineErrors, PositionScale,
+};
+
+use crate::CliError;
+
+/// Where the descriptions in the help text start
+const HELP_INDENT: usize = 28;
+
+/// `words` joined by `, ` and wrapped to lines that fit after the help
+/// text's indent
+fn help_list<'a>(words: impl IntoIterator<Item = &'a str>) -> String {
+    let mut lines = vec![String::new()];
+    for word in words {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && HELP_INDENT + line.len() + word.len() + 2 > 78 {
+            line.push(',');
+            lines.push(String::new());
+        } else if !line.is_empty() {
+            line.push_str(", ");
+        }
+        lines.last_mut().unwrap().push_str(word);
+    }
+    lines.join(&format!("\n{:HELP_INDENT$}", ""))
+}
+
+/// The commands, listing the easings and the formats in the exporter
+/// registry. The binary formats are left to `poline export`.
+fn help() -> String {
+    let easings = help_list(PositionScale::ALL.iter().map(|scale| scale.as_str()));
+    let poline = Poline::default();
+    let names: Vec<String> = registry()
+        .names()
+        .into_iter()
+        .filter(|name| poline.export(name).is_ok_and(|bytes| String::from_utf8(bytes).is_ok()))
+        .collect();
+    let formats = help_list(names.iter().map(String::as_str));
+    format!(
+        "Commands:
+  show                      Print the palette
+  shift-hue <degrees>       Rotate every anchor's hue
+  add-anchor <color> [i]    Add a CSS color as an anchor, at the end or index i
+  remove-anchor <i>         Remove the anchor at index i
+  easing <x|y|z|all> <fn>   Set the position function of an axis, one of
+                            {easings}
+  export <format>           Print the palette in one of the text formats
+                            {formats}
+  help                      Print this list
+  quit                      Leave the repl"
+    )
+}
+
+pub enum Outcome {
+    /// Show this text
+    Print(String),
+    /// The palette changed, show its preview
+    Changed,
+    Quit,
+}
+
+pub struct Repl {
+    poline: Poline,
+}
+
+impl Repl {
+    pub fn new(poline: Poline) -> Self {
+        Self { poline }
+    }
+
+    pub fn poline(&self) -> &Poline {
+        &self.poline
+    }
+
+    /// Runs one line of input against the palette
+    pub fn execute(&mut self, line: &str) -> Result<Outcome, CliError> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Outcome::Print(String::new()));
+        };
+        let args: Vec<&str> = words.collect();
+        match (command, args.as_slice()) {
+            ("show", []) => Ok(Outcome::Print(self.poline.to_unicode_preview())),
+            ("shift-hue", [degrees]) => {
+                let degrees = degrees
+                    .parse()
+                    .map_err(|_| CliError::Usage("shift-hue <degrees>"))?;
+                self.poline.shift_hue(degrees);
+                Ok(Outcome::Changed)
+            }
+            ("add-anchor", [color, rest @ ..]) if rest.len() <= 1 => {
+                let index = match rest.first() {
+                    Some(index) => Some(
+                        index
+                            .parse()
+                            .ok()
+                            .filter(|&index| index <= self.poline.anchors().len())
+                            .ok_or(CliError::Usage("add-anchor <color> [index]"))?,
+                    ),
+                    None => None,
+                };
+                let anchor = ColorPointCollection {
+                    xyz: None,
+                    color: Some(parse_css_color_detailed(color)?),
+                    inverted_lightness: self.poline.inverted_lightness(),
+                    model: self.poline.color_model(),
+                };
+                self.poline.add_anchor_point(anchor, index)?;
+                Ok(Outcome::Changed)
+            }
+            ("remove-anchor", [index]) => {
+                let index = index
+                    .parse()
+                    .ok()
+                    .filter(|&index| index < self.poline.anchors().len())
+                    .ok_or(CliError::Usage("remove-anchor <index>"))?;
+                if self.poline.anchors().len() <= 2 {
+                    return Err(CliError::Usage("a palette keeps at least two anchors"));
+                }
+                self.poline.remove_anchor_point_at_index(index)?;
+                Ok(Outcome::Changed)
+            }
+            ("easing", [axis, name]) => {
+                let usage = CliError::Usage("easing <x|y|z|all> <function>");
+                let Ok(scale) = name.parse::<PositionScale>() else {
+                    return Err(usage);
+                };
+                let scale = scale.id();
+                match *axis {
+                    "x" => self.poline.set_position_fn_x(scale),
+                    "y" => self.poline.set_position_fn_y(scale),
+                    "z" => self.poline.set_position_fn_z(scale),
+                    "all" => self.poline.set_position_fn(scale),
+                    _ => return Err(usage),
+                }
+                Ok(Outcome::Changed)
+            }
+            ("export", [format]) => Ok(Outcome::Print(self.export(format)?)),
+            ("help", []) => Ok(Outcome::Print(help())),
+            ("quit" | "exit", []) => Ok(Outcome::Quit),
+            _ => Err(CliError::UnknownCommand(line.trim().to_string())),
+        }
+    }
+
+    /// The palette in the registered text format `format`, as
+    /// `poline export` writes it
+    fn export(&self, format: &str) -> Result<String, CliError> {
+        let bytes = self.poline.export(format).map_err(|err| match err {
+            PolineErrors::UnknownFormat => CliError::UnknownFormat {
+                format: format.to_string(),
+                known: registry().names().join(", "),
+            },
+            err => CliError::Poline(err),
+        })?;
+        let exported =
+            String::from_utf8(bytes).map_err(|_| CliError::BinaryFormat(format.to_string()))?;
+        Ok(exported.trim_end().to_string())
+    }
+}
+
+/// Reads commands from `input` until it ends or `quit`, errors are printed
+/// and the session goes on
+pub fn run(poline: Poline, input: impl BufRead, mut output: impl Write) -> Result<(), CliError> {
+    let mut repl = Repl::new(poline);
+    writeln!(output, "{}", repl.poline().to_unicode_preview())?;
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        match repl.execute(&line?) {
+            Ok(Outcome::Print(text)) if text.is_empty() => {}
+            Ok(Outcome::Print(text)) => writeln!(output, "{text}")?,
+            Ok(Outcome::Changed) => writeln!(output, "{}", repl.poline().to_unicode_preview())?,
+            Ok(Outcome::Quit) => return Ok(()),
+            Err(err) => writeln!(output, "error: {err}")?,
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use poline_core::{PolineOptions, PositionScale, Vector3};
+
+    use super::*;
+
+    fn grays() -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            inverted_lightness: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn commands_edit_the_palette() {
+        let mut repl = Repl::new(grays());
+        assert!(matches!(
+            repl.execute("add-anchor #ff8800"),
+            Ok(Outcome::Changed)
+        ));
+        assert_eq!(repl.poline().anchors().len(), 3);
+        assert!(matches!(repl.execute("easing z arc"), Ok(Outcome::Changed)));
+        assert_eq!(repl.poline().position_functions().2, PositionScale::Arc);
+        assert!(matches!(
+            repl.execute("remove-anchor 1"),
+            Ok(Outcome::Changed)
+        ));
+        assert!(matches!(
+            repl.execute("remove-anchor 0"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            repl.execute("frobnicate"),
+            Err(CliError::UnknownCommand(_))
+        ));
+        for format in ["css", "css-gradient", "gpl"] {
+            let expected = repl.poline().export(format).unwrap();
+            match repl.execute(&format!("export {format}")) {
+                Ok(Outcome::Print(text)) => {
+                    assert_eq!(text, String::from_utf8(expected).unwrap().trim_end())
+                }
+                _ => panic!("expected {format}"),
+            }
+        }
+        assert!(matches!(
+            repl.execute("export png"),
+            Err(CliError::BinaryFormat(_))
+        ));
+        assert!(matches!(
+            repl.execute("export ase"),
+            Err(CliError::UnknownFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn help_lists_every_easing_and_format() {
+        let help = help();
+        for scale in PositionScale::ALL {
+            assert!(help.contains(scale.as_str()), "{scale}");
+        }
+        for name in registry().names() {
+            let text = Poline::default().export(&name).map(String::from_utf8).unwrap();
+            assert_eq!(help.contains(&name), text.is_ok(), "{name}");
+        }
+        assert!(help.lines().all(|line| line.len() <= 80), "{help}");
+    }
+
+    #[test]
+    fn session_runs_until_quit() {
+        let input = "shift-hue 20\nadd-anchor nope\nquit\nshow\n";
+        let mut output = Vec::new();
+        run(grays(), input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("\x1b[0m").count(), 2);
+        assert!(output.contains("error: "));
+    }
+}
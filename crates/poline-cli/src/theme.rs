@@ -0,0 +1,188 @@
+//! `poline theme` writes a palette and its variants as design tokens.
+//!
+//! The palette's first and last anchors are taken as background and text.
+//! Palettes are built with inverted lightness, so the anchors' lightness
+//! carries through to the tokens rather than every anchor sitting on the
+//! rim of the color wheel.
+//! `--dark` adds a variant with the perceptual lightness flipped,
+//! `--contrast` moves the text, and the background when that is not
+//! enough, until they meet a WCAG level and `--roles` names the colors
+//! a UI needs on top of the numbered tokens.
+
+use std::{fs, path::PathBuf};
+
+use poline_core::{Poline, PolineSet, WcagLevel};
+
+use crate::CliError;
+
+pub const USAGE: &str = "poline theme [--from <share string>] [--roles] [--dark] \
+[--contrast <AA-large|AA|AAA>] [--out <dir>]";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeOptions {
+    pub from: Option<String>,
+    pub roles: bool,
+    pub dark: bool,
+    pub contrast: Option<WcagLevel>,
+    pub out: PathBuf,
+}
+
+impl ThemeOptions {
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = ThemeOptions {
+            from: None,
+            roles: false,
+            dark: false,
+            contrast: None,
+            out: PathBuf::from("tokens"),
+        };
+        let mut args = args.iter().map(String::as_str);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--roles" => options.roles = true,
+                "--dark" => options.dark = true,
+                "--from" => options.from = Some(args.next().ok_or(CliError::Usage(USAGE))?.into()),
+                "--out" => options.out = args.next().ok_or(CliError::Usage(USAGE))?.into(),
+                "--contrast" => {
+                    let level = match args.next() {
+                        Some("AA-large") => WcagLevel::AALarge,
+                        Some("AA") => WcagLevel::AA,
+                        Some("AAA") => WcagLevel::AAA,
+                        _ => return Err(CliError::Usage(USAGE)),
+                    };
+                    options.contrast = Some(level);
+                }
+                _ => return Err(CliError::Usage(USAGE)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// CSS custom properties naming the roles of every palette, pointing at
+/// the numbered tokens, `--light-primary: var(--light-4)`
+pub fn role_variables(set: &PolineSet) -> String {
+    let mut css = String::from("/* Generated by poline theme, do not edit */\n:root {\n");
+    for (name, poline) in set.iter() {
+        for (role, index) in poline.assign_roles() {
+            css.push_str(&format!("  --{name}-{role}: var(--{name}-{index});\n"));
+        }
+    }
+    css.push_str("}\n");
+    css
+}
+
+/// The `light` palette and, with `--dark`, its `dark` variant, each
+/// adjusted to the `--contrast` level
+pub fn build_theme(poline: Poline, options: &ThemeOptions) -> Result<PolineSet, CliError> {
+    let poline = if poline.inverted_lightness() {
+        poline
+    } else {
+        let colors = poline.anchors().iter().map(|anchor| anchor.color).collect();
+        poline.with_anchor_colors(colors)
+    };
+    let dark = options.dark.then(|| poline.dark_variant());
+    let mut set = PolineSet::new();
+    for (name, poline) in [("light", Some(poline)), ("dark", dark)] {
+        let Some(mut poline) = poline else {
+            continue;
+        };
+        if let Some(level) = options.contrast {
+            poline = poline.meet_contrast(level).map_err(|ratio| CliError::Contrast {
+                palette: name.to_string(),
+                ratio,
+            })?;
+        }
+        set.insert(name, poline);
+    }
+    Ok(set)
+}
+
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let options = ThemeOptions::parse(args)?;
+    let poline = match &options.from {
+        Some(share) => Poline::from_share_string(share)?,
+        None => Poline::default(),
+    };
+    let set = build_theme(poline, &options)?;
+    fs::create_dir_all(&options.out)?;
+    poline_codegen::write_theme(&set, &options.out, "theme")?;
+    if options.roles {
+        fs::write(options.out.join("theme-roles.css"), role_variables(&set))?;
+    }
+    for (name, poline) in set.iter() {
+        println!("{name:<6}{}", poline.to_unicode_preview());
+        if options.roles {
+            let roles: Vec<String> = poline
+                .assign_roles()
+                .into_iter()
+                .map(|(role, index)| format!("{role} {index}"))
+                .collect();
+            println!("      {}", roles.join(", "));
+        }
+    }
+    println!("wrote {}", options.out.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use poline_core::{contrast_ratio, PolineOptions, Vector3};
+
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn grays(high: f32) -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, high)]),
+            inverted_lightness: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn parses_flags() {
+        let options =
+            ThemeOptions::parse(&args("--roles --dark --contrast AA --out theme")).unwrap();
+        assert!(options.roles && options.dark);
+        assert_eq!(options.contrast, Some(WcagLevel::AA));
+        assert_eq!(options.out, PathBuf::from("theme"));
+        assert!(ThemeOptions::parse(&args("--contrast B")).is_err());
+        assert!(ThemeOptions::parse(&args("--out")).is_err());
+    }
+
+    #[test]
+    fn adjusts_every_palette_to_the_contrast_level() {
+        let options = ThemeOptions::parse(&args("--dark --contrast AAA")).unwrap();
+        let set = build_theme(grays(0.3), &options).unwrap();
+        assert_eq!(set.names().collect::<Vec<_>>(), ["light", "dark"]);
+        for (name, poline) in set.iter() {
+            let anchors = poline.anchors();
+            let ratio = contrast_ratio(anchors[0].color, anchors[anchors.len() - 1].color);
+            assert!(ratio >= 7.0, "{name}: {ratio}");
+        }
+        // Enough contrast already, nothing moves
+        let light = build_theme(grays(1.0), &options).unwrap();
+        assert_eq!(light.get("light").unwrap().anchors(), grays(1.0).anchors());
+
+        let rim = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(30.0, 0.5, 0.9), Vector3(250.0, 0.6, 0.3)]),
+            ..Default::default()
+        });
+        let set = build_theme(rim, &options).unwrap();
+        let background = |name| set.get(name).unwrap().colors_vec()[0].2;
+        assert!(background("light") > 0.8 && background("dark") < 0.2);
+    }
+
+    #[test]
+    fn writes_role_variables() {
+        let mut set = PolineSet::new();
+        set.insert("light", grays(1.0));
+        let css = role_variables(&set);
+        assert!(css.contains("  --light-background: var(--light-0);\n"));
+        assert!(css.contains("  --light-text: var(--light-"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Build-time generation of theme constants from poline palettes.
+//!
+//! Call it from a build script with a [`PolineSet`] and include the result,
+//! the application then refers to `theme::ACCENT_3` without depending on
+//! poline at runtime. The same colors are written as CSS custom properties
+//! for the web side of the app.
+//!
+//! ```ignore
+//! // build.rs
+//! let mut set = PolineSet::new();
+//! set.insert("accent", Poline::from(options));
+//! poline_codegen::write_theme(&set, std::env::var("OUT_DIR").unwrap(), "theme").unwrap();
+//!
+//! // main.rs
+//! include!(concat!(env!("OUT_DIR"), "/theme.rs"));
+//! let accent = theme::ACCENT_3;
+//! ```
+
+use std::{collections::HashSet, fs, io, iter, path::Path};
+
+pub use poline_core::{Poline, PolineOptions, PolineSet};
+
+/// `accent color` -> `ACCENT_COLOR`
+fn constant_name(name: &str) -> String {
+    let mut constant = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            constant.push(c.to_ascii_uppercase());
+        } else if !constant.ends_with('_') {
+            constant.push('_');
+        }
+    }
+    let constant = constant.trim_matches('_').to_string();
+    if constant.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{constant}")
+    } else {
+        constant
+    }
+}
+
+/// `ACCENT_COLOR` -> `accent-color`
+fn css_name(constant: &str) -> String {
+    constant
+        .trim_start_matches('_')
+        .to_ascii_lowercase()
+        .replace('_', "-")
+}
+
+/// The constant of every palette in `set`, in order. A name without any
+/// letters or digits becomes `PALETTE`, and a constant whose array or
+/// color constants clash with an earlier palette's gets `_V2`, `_V3`, ...
+/// appended.
+fn constant_names(set: &PolineSet) -> Vec<String> {
+    let mut taken = HashSet::new();
+    set.iter()
+        .map(|(name, poline)| {
+            let colors = rgb8(poline).len();
+            let base = Some(constant_name(name))
+                .filter(|constant| !constant.is_empty())
+                .unwrap_or_else(|| "PALETTE".to_string());
+            let identifiers = |constant: &str| {
+                iter::once(constant.to_string())
+                    .chain((0..colors).map(move |i| format!("{constant}_{i}")))
+                    .collect::<Vec<_>>()
+            };
+            let constant = iter::once(base.clone())
+                .chain((2..).map(|version| format!("{base}_V{version}")))
+                .find(|constant| identifiers(constant).iter().all(|id| !taken.contains(id)))
+                .expect("some version is free");
+            taken.extend(identifiers(&constant));
+            constant
+        })
+        .collect()
+}
+
+fn rgb8(poline: &Poline) -> Vec<[u8; 3]> {
+    poline
+        .colors_rgba8()
+        .chunks_exact(4)
+        .map(|rgba| [rgba[0], rgba[1], rgba[2]])
+        .collect()
+}
+
+/// A Rust module `module` with a `Color` type, one constant per palette
+/// color (`ACCENT_0`, `ACCENT_1`, ...) and an array per palette (`ACCENT`).
+/// A palette named without letters or digits is `PALETTE`, and one whose
+/// constants would clash with an earlier palette's gets `_V2` appended.
+pub fn rust_module(set: &PolineSet, module: &str) -> String {
+    let mut source = format!(
+        "// Generated by poline-codegen, do not edit
+#[allow(dead_code)]
+pub mod {module} {{
+    /// An opaque sRGB color
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Color {{
+        pub r: u8,
+        pub g: u8,
+        pub b: u8,
+    }}
+
+    impl Color {{
+        /// Channels in 0..1
+        pub fn to_f32(self) -> [f32; 3] {{
+            [self.r, self.g, self.b].map(|c| c as f32 / 255.0)
+        }}
+    }}
+"
+    );
+    for ((_, poline), constant) in set.iter().zip(constant_names(set)) {
+        let colors = rgb8(poline);
+        source.push('\n');
+        for (i, [r, g, b]) in colors.iter().enumerate() {
+            source.push_str(&format!(
+                "    pub const {constant}_{i}: Color = Color {{ r: {r}, g: {g}, b: {b} }};\n"
+            ));
+        }
+        let items: Vec<String> = (0..colors.len())
+            .map(|i| format!("{constant}_{i}"))
+            .collect();
+        source.push_str(&format!(
+            "    pub const {constant}: [Color; {n}] = [{items}];\n",
+            n = colors.len(),
+            items = items.join(", ")
+        ));
+    }
+    source.push_str("}\n");
+    source
+}
+
+/// The same colors as CSS custom properties on `:root`, `--accent-0` etc.
+pub fn css_variables(set: &PolineSet) -> String {
+    let mut css = String::from("/* Generated by poline-codegen, do not edit */\n:root {\n");
+    for ((_, poline), constant) in set.iter().zip(constant_names(set)) {
+        let prefix = css_name(&constant);
+        for (i, [r, g, b]) in rgb8(poline).into_iter().enumerate() {
+            css.push_str(&format!("  --{prefix}-{i}: #{r:02x}{g:02x}{b:02x};\n"));
+        }
+    }
+    css.push_str("}\n");
+    css
+}
+
+/// Writes `{module}.rs` and `{module}.css` to `dir`, only touching files
+/// whose contents changed so cargo does not rebuild needlessly
+pub fn write_theme(set: &PolineSet, dir: impl AsRef<Path>, module: &str) -> io::Result<()> {
+    let dir = dir.as_ref();
+    write_if_changed(&dir.join(format!("{module}.rs")), &rust_module(set, module))?;
+    write_if_changed(&dir.join(format!("{module}.css")), &css_variables(set))
+}
+
+fn write_if_changed(path: &Path, contents: &str) -> io::Result<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poline_core::Vector3;
+
+    fn set() -> PolineSet {
+        let mut set = PolineSet::new();
+        set.insert(
+            "brand accent",
+            Poline::from(PolineOptions {
+                anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+                inverted_lightness: true,
+                ..Default::default()
+            }),
+        );
+        set
+    }
+
+    #[test]
+    fn names_are_valid_identifiers() {
+        assert_eq!(constant_name("brand accent"), "BRAND_ACCENT");
+        assert_eq!(constant_name("2 dark--mode"), "_2_DARK_MODE");
+        assert_eq!(css_name(&constant_name("Brand Accent")), "brand-accent");
+        assert_eq!(constant_name("!!!"), "");
+    }
+
+    #[test]
+    fn names_are_unique_and_never_empty() {
+        let mut set = set();
+        let poline = set.get("brand accent").unwrap().clone();
+        for name in ["Brand-Accent", "", "!!!", "brand accent 0"] {
+            set.insert(name, poline.clone());
+        }
+        let constants = constant_names(&set);
+        assert_eq!(
+            constants,
+            ["BRAND_ACCENT", "BRAND_ACCENT_V2", "PALETTE", "PALETTE_V2", "BRAND_ACCENT_0_V2"]
+        );
+        let source = rust_module(&set, "theme");
+        let mut declared: Vec<&str> = source
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("pub const "))
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        let count = declared.len();
+        declared.sort();
+        declared.dedup();
+        assert_eq!(declared.len(), count);
+
+        let css = css_variables(&set);
+        assert!(css.contains("  --palette-v2-0: #000000;\n"));
+        assert!(!css.contains("  ---"));
+    }
+
+    #[test]
+    fn generates_constants_and_css() {
+        let set = set();
+        let n = set.get("brand accent").unwrap().colors_rgba8().len() / 4;
+        let source = rust_module(&set, "theme");
+        assert!(source.contains("pub mod theme {"));
+        assert!(source.contains("pub const BRAND_ACCENT_0: Color = Color { r: 0, g: 0, b: 0 };"));
+        assert!(source.contains(&format!("pub const BRAND_ACCENT: [Color; {n}]")));
+
+        let css = css_variables(&set);
+        assert!(css.contains("  --brand-accent-0: #000000;\n"));
+        assert_eq!(css.matches("--brand-accent-").count(), n);
+    }
+}
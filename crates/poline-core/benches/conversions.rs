@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use poline_core::{ColorModel, LutFormat, Poline, PolineOptions, Vector3};
+
+fn conversions(c: &mut Criterion) {
+    let positions: Vec<Vector3> = (0..1024)
+        .map(|i| {
+            let t = i as f32 / 1023.0;
+            Vector3(t, 1.0 - t, (t * 7.0).fract())
+        })
+        .collect();
+
+    for model in [
+        ColorModel::Hsl,
+        ColorModel::LightnessOnZ,
+        ColorModel::OklchCylinder,
+    ] {
+        let colors = model.points_to_hsl(&positions, false);
+        c.bench_function(&format!("{model:?} points_to_hsl x1024"), |b| {
+            b.iter(|| model.points_to_hsl(black_box(&positions), false))
+        });
+        c.bench_function(&format!("{model:?} hsl_to_points x1024"), |b| {
+            b.iter(|| model.hsl_to_points(black_box(&colors), false))
+        });
+    }
+}
+
+fn palettes(c: &mut Criterion) {
+    let options = PolineOptions {
+        anchor_colors: Some(vec![
+            Vector3(0.0, 0.9, 0.3),
+            Vector3(120.0, 0.9, 0.5),
+            Vector3(240.0, 0.9, 0.7),
+        ]),
+        num_points: 64,
+        ..Default::default()
+    };
+    c.bench_function("build 3 anchors x 64 points", |b| {
+        b.iter(|| Poline::from(black_box(options.clone())))
+    });
+    let poline = Poline::from(options);
+    c.bench_function("rgba8 lut x4096", |b| {
+        b.iter(|| poline.to_lut_bytes(black_box(4096), LutFormat::Rgba8))
+    });
+}
+
+criterion_group!(benches, conversions, palettes);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color_space::{
+        clamp_rgb, linear_rgb_to_xyz, linear_to_srgb, srgb_to_linear, xyz_to_linear_rgb,
+    },
+    types::Vector3,
+};
+
+/// CIE standard illuminants usable as source or target white points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WhitePoint {
+    /// Incandescent / tungsten
+    A,
+    /// Horizon light, used for print proofing
+    D50,
+    /// Mid-morning daylight
+    D55,
+    /// Noon daylight, the sRGB white point
+    D65,
+    /// North sky daylight
+    D75,
+}
+
+impl WhitePoint {
+    /// The XYZ tristimulus values of the white point, normalized to Y = 1
+    pub fn xyz(self) -> Vector3 {
+        match self {
+            WhitePoint::A => Vector3(1.098_47, 1.0, 0.355_82),
+            WhitePoint::D50 => Vector3(0.964_22, 1.0, 0.825_21),
+            WhitePoint::D55 => Vector3(0.956_82, 1.0, 0.921_49),
+            WhitePoint::D65 => Vector3(0.950_47, 1.0, 1.088_83),
+            WhitePoint::D75 => Vector3(0.949_72, 1.0, 1.226_38),
+        }
+    }
+}
+
+fn bradford(xyz: Vector3) -> Vector3 {
+    let Vector3(x, y, z) = xyz;
+    Vector3(
+        0.8951 * x + 0.2664 * y - 0.1614 * z,
+        -0.7502 * x + 1.7135 * y + 0.0367 * z,
+        0.0389 * x - 0.0685 * y + 1.0296 * z,
+    )
+}
+
+fn bradford_inverse(lms: Vector3) -> Vector3 {
+    let Vector3(l, m, s) = lms;
+    Vector3(
+        0.986_992_9 * l - 0.147_054_26 * m + 0.159_962_65 * s,
+        0.432_305_27 * l + 0.518_360_27 * m + 0.049_291_23 * s,
+        -0.008_528_665 * l + 0.040_042_82 * m + 0.968_486_7 * s,
+    )
+}
+
+/// Adapts an XYZ color seen under `from` to how it appears under `to`
+/// using the Bradford cone response transform
+pub fn adapt_xyz(xyz: Vector3, from: WhitePoint, to: WhitePoint) -> Vector3 {
+    if from == to {
+        return xyz;
+    }
+    let source = bradford(from.xyz());
+    let target = bradford(to.xyz());
+    let Vector3(l, m, s) = bradford(xyz);
+    bradford_inverse(Vector3(
+        l * target.0 / source.0,
+        m * target.1 / source.1,
+        s * target.2 / source.2,
+    ))
+}
+
+/// Adapts an sRGB color designed under `from` to the `to` white point,
+/// clamping the result to the sRGB gamut
+pub fn adapt_rgb(rgb: Vector3, from: WhitePoint, to: WhitePoint) -> Vector3 {
+    let xyz = linear_rgb_to_xyz(srgb_to_linear(rgb));
+    clamp_rgb(linear_to_srgb(xyz_to_linear_rgb(adapt_xyz(xyz, from, to))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adapts_white_to_target_white() {
+        let Vector3(x, y, z) = adapt_xyz(WhitePoint::D65.xyz(), WhitePoint::D65, WhitePoint::D50);
+        let Vector3(tx, ty, tz) = WhitePoint::D50.xyz();
+        assert!((x - tx).abs() < 1e-3 && (y - ty).abs() < 1e-3 && (z - tz).abs() < 1e-3);
+    }
+}
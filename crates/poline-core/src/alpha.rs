@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color_space::{hsl_to_rgb, rgba_to_hex},
+    types::Vector3,
+};
+
+/// One entry of a transparency series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlphaStep {
+    pub alpha: f32,
+    /// `#rrggbbaa` with the color channels left untouched
+    pub straight_hex: String,
+    /// `#rrggbbaa` with the color channels multiplied by alpha
+    pub premultiplied_hex: String,
+}
+
+///
+/// Builds `steps` increasingly opaque versions of an HSL color, evenly spaced
+/// from 1 / steps up to fully opaque
+///
+/// ```
+/// # use poline_core::{alpha_ramp, Vector3};
+/// let ramp = alpha_ramp(Vector3(0.0, 1.0, 0.5), 2);
+/// assert_eq!(ramp.iter().map(|step| step.alpha).collect::<Vec<_>>(), [0.5, 1.0]);
+/// ```
+pub fn alpha_ramp(color: Vector3, steps: usize) -> Vec<AlphaStep> {
+    let rgb = hsl_to_rgb(color);
+    (1..=steps)
+        .map(|step| {
+            let alpha = step as f32 / steps as f32;
+            let premultiplied = Vector3(rgb.0 * alpha, rgb.1 * alpha, rgb.2 * alpha);
+            AlphaStep {
+                alpha,
+                straight_hex: rgba_to_hex(rgb, alpha),
+                premultiplied_hex: rgba_to_hex(premultiplied, alpha),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramps_straight_and_premultiplied() {
+        let ramp = alpha_ramp(Vector3(0.0, 1.0, 0.5), 2);
+        assert_eq!(ramp.len(), 2);
+        assert_eq!(ramp[0].straight_hex, "#ff000080");
+        assert_eq!(ramp[0].premultiplied_hex, "#80000080");
+        assert_eq!(ramp[1].straight_hex, "#ff0000ff");
+        assert_eq!(ramp[1].premultiplied_hex, "#ff0000ff");
+    }
+}
@@ -0,0 +1,122 @@
+//! Stable anchor ids. Indices shift as soon as anchors are inserted or
+//! removed, ids stay with their anchor for the life of the palette.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    color_point::{ColorPoint, ColorPointCollection},
+    Poline, PolineErrors,
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    /// The id of the anchor currently at `index`
+    pub fn anchor_id(&self, index: usize) -> Option<u32> {
+        self.anchor_ids.get(index).copied()
+    }
+
+    /// Where the anchor with `id` currently is
+    pub fn anchor_index(&self, id: u32) -> Option<usize> {
+        self.anchor_ids.iter().position(|&anchor| anchor == id)
+    }
+
+    pub fn anchor_by_id(&self, id: u32) -> Option<ColorPoint> {
+        self.anchor_index(id).map(|index| self.anchor_points[index])
+    }
+
+    pub fn update_anchor_point_by_id(
+        &mut self,
+        id: u32,
+        initial: ColorPointCollection,
+    ) -> Result<ColorPoint, PolineErrors> {
+        let index = self.anchor_index(id).ok_or(PolineErrors::PointNotFound)?;
+        self.update_anchor_point_at_index(index, initial)
+    }
+
+    pub fn remove_anchor_point_by_id(&mut self, id: u32) -> Result<(), PolineErrors> {
+        let index = self.anchor_index(id).ok_or(PolineErrors::PointNotFound)?;
+        self.remove_anchor_point_at_index(index)
+    }
+}
+
+impl Poline {
+    /// Numbers the anchors from scratch when the ids no longer line up with
+    /// them, as after building or loading a palette, and keeps the next id
+    /// past every id in use, which saves without it lack
+    pub(crate) fn sync_anchor_ids(&mut self) {
+        if self.anchor_ids.len() != self.anchor_points.len() {
+            self.anchor_ids = (0..self.anchor_points.len() as u32).collect();
+        }
+        let unused = self.anchor_ids.iter().max().map_or(0, |&id| id + 1);
+        self.next_anchor_id = self.next_anchor_id.max(unused);
+    }
+
+    pub(crate) fn allocate_anchor_id(&mut self) -> u32 {
+        let id = self.next_anchor_id;
+        self.next_anchor_id += 1;
+        id
+    }
+
+    /// The ids of the anchors in order
+    pub fn anchor_ids(&self) -> &[u32] {
+        &self.anchor_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::Vector3, PolineOptions};
+
+    fn color(hsl: Vector3) -> ColorPointCollection {
+        ColorPointCollection {
+            xyz: None,
+            color: Some(hsl),
+            inverted_lightness: true,
+            model: Default::default(),
+        }
+    }
+
+    #[test]
+    fn ids_survive_insertions_and_removals() {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.9, 0.2), Vector3(200.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        assert_eq!(poline.anchor_ids(), [0, 1]);
+        // A failed insertion doesn't use up an id
+        assert!(poline
+            .add_anchor_point(color(Vector3(100.0, 0.5, 0.5)), Some(5))
+            .is_err());
+        let first = poline
+            .add_anchor_point(color(Vector3(100.0, 0.5, 0.5)), Some(0))
+            .unwrap();
+        let last = poline
+            .add_anchor_point(color(Vector3(300.0, 0.5, 0.5)), None)
+            .unwrap();
+        assert_eq!((first, last), (2, 3));
+        assert_eq!(poline.anchor_index(1), Some(2));
+
+        poline.remove_anchor_point_by_id(0).unwrap();
+        assert_eq!(poline.anchor_ids(), [2, 1, 3]);
+        let updated = poline
+            .update_anchor_point_by_id(3, color(Vector3(310.0, 0.5, 0.5)))
+            .unwrap();
+        assert_eq!(poline.anchor_by_id(3), Some(updated));
+        assert!(poline.remove_anchor_point_by_id(0).is_err());
+
+        let reloaded = Poline::from_json(&poline.to_json()).unwrap();
+        assert_eq!(reloaded.anchor_ids(), poline.anchor_ids());
+
+        // A save with ids but no next id still hands out unused ids
+        let mut json: serde_json::Value = serde_json::from_str(&poline.to_json()).unwrap();
+        assert!(json.as_object_mut().unwrap().remove("next_anchor_id").is_some());
+        let mut reloaded = Poline::from_json(&json.to_string()).unwrap();
+        let added = reloaded
+            .add_anchor_point(color(Vector3(40.0, 0.5, 0.5)), None)
+            .unwrap();
+        assert_eq!(added, 4);
+    }
+}
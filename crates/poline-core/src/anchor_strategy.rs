@@ -0,0 +1,205 @@
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    import::reduce_anchors,
+    types::{Vector2, Vector3},
+    utils::{random_hsl_pair_with_rng, random_hsl_triple_with_rng, random_in},
+    PolineOptions,
+};
+
+/// Hue relationships for [`AnchorStrategy::Harmony`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HarmonyKind {
+    Complementary,
+    Analogous,
+    Triadic,
+    SplitComplementary,
+    Tetradic,
+}
+
+impl HarmonyKind {
+    /// Hue offsets in degrees from the first anchor
+    pub fn hue_offsets(self) -> &'static [f32] {
+        match self {
+            HarmonyKind::Complementary => &[0.0, 180.0],
+            HarmonyKind::Analogous => &[0.0, 30.0, 60.0],
+            HarmonyKind::Triadic => &[0.0, 120.0, 240.0],
+            HarmonyKind::SplitComplementary => &[0.0, 150.0, 210.0],
+            HarmonyKind::Tetradic => &[0.0, 90.0, 180.0, 270.0],
+        }
+    }
+}
+
+/// How the anchors of a new palette are chosen when
+/// `PolineOptions::anchor_colors` is not set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum AnchorStrategy {
+    /// A light and a dark anchor 60 to 240 degrees apart, as poline.js does
+    #[default]
+    RandomPair,
+    /// Light, dark and light anchors
+    RandomTriple,
+    /// Anchors with a random start hue and the hue offsets of a harmony
+    Harmony(HarmonyKind),
+    /// The `count` most representative of some HSL image pixels
+    FromImage {
+        pixels: Vec<Vector3>,
+        count: usize,
+    },
+    /// These anchors, like setting `PolineOptions::anchor_colors` to them.
+    /// When both are set `anchor_colors` wins.
+    Explicit(Vec<Vector3>),
+}
+
+impl AnchorStrategy {
+    /// Generates anchor colors, drawing random saturations and lightnesses
+    /// from the given ranges when set
+    pub fn anchor_colors(
+        &self,
+        saturation_range: Option<Vector2>,
+        lightness_range: Option<Vector2>,
+    ) -> Vec<Vector3> {
+        self.anchor_colors_with_rng(&mut thread_rng(), saturation_range, lightness_range)
+    }
+
+    /// [`AnchorStrategy::anchor_colors`] drawn from a `StdRng` seeded with
+    /// `seed`, the same anchors for the same seed with this version of rand
+    pub fn seeded_anchor_colors(
+        &self,
+        seed: u64,
+        saturation_range: Option<Vector2>,
+        lightness_range: Option<Vector2>,
+    ) -> Vec<Vector3> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.anchor_colors_with_rng(&mut rng, saturation_range, lightness_range)
+    }
+
+    /// [`AnchorStrategy::anchor_colors`] drawn from `rng`
+    pub fn anchor_colors_with_rng(
+        &self,
+        rng: &mut impl Rng,
+        saturation_range: Option<Vector2>,
+        lightness_range: Option<Vector2>,
+    ) -> Vec<Vector3> {
+        let saturation_range = saturation_range.unwrap_or(Vector2(0.0, 1.0));
+        let saturation = |rng: &mut _| random_in(rng, saturation_range);
+        // Without a range, anchors alternate between light and dark
+        let lightness = |rng: &mut _, index: usize| {
+            let classic = if index.is_multiple_of(2) {
+                Vector2(0.75, 0.95)
+            } else {
+                Vector2(0.3, 0.5)
+            };
+            random_in(rng, lightness_range.unwrap_or(classic))
+        };
+        match self {
+            AnchorStrategy::RandomPair => {
+                let saturations = Vector2(saturation(rng), saturation(rng));
+                let lightnesses = Vector2(lightness(rng, 0), lightness(rng, 1));
+                random_hsl_pair_with_rng(rng, None, Some(saturations), Some(lightnesses))
+            }
+            AnchorStrategy::RandomTriple => {
+                let saturations = Vector3(saturation(rng), saturation(rng), saturation(rng));
+                let lightnesses = Vector3(lightness(rng, 0), lightness(rng, 1), lightness(rng, 2));
+                random_hsl_triple_with_rng(rng, None, Some(saturations), Some(lightnesses))
+            }
+            AnchorStrategy::Harmony(kind) => {
+                let start_hue = rng.gen::<f32>() * 360.0;
+                kind.hue_offsets()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, offset)| {
+                        let saturation = saturation(rng);
+                        Vector3((start_hue + offset) % 360.0, saturation, lightness(rng, index))
+                    })
+                    .collect()
+            }
+            AnchorStrategy::FromImage { pixels, count } => reduce_anchors(pixels, *count),
+            AnchorStrategy::Explicit(colors) => colors.clone(),
+        }
+    }
+
+    /// A 64 bit FNV-1a hash of the pixels of a `FromImage` strategy, in hex,
+    /// for [`Provenance::source_image_hash`](crate::Provenance)
+    pub fn source_image_hash(&self) -> Option<String> {
+        let AnchorStrategy::FromImage { pixels, .. } = self else {
+            return None;
+        };
+        let hash = pixels
+            .iter()
+            .flat_map(|pixel| [pixel.0, pixel.1, pixel.2])
+            .flat_map(f32::to_le_bytes)
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        Some(format!("{hash:016x}"))
+    }
+}
+
+impl PolineOptions {
+    /// Anchors from the strategy and random ranges, drawn from the seed
+    /// when there is one
+    pub(crate) fn generate_anchor_colors(&self) -> Vec<Vector3> {
+        let (saturation, lightness) = (self.random_saturation_range, self.random_lightness_range);
+        match self.seed {
+            Some(seed) => self
+                .anchor_strategy
+                .seeded_anchor_colors(seed, saturation, lightness),
+            None => self.anchor_strategy.anchor_colors(saturation, lightness),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Poline, PolineOptions};
+
+    #[test]
+    fn strategies_generate_anchors() {
+        let triadic = AnchorStrategy::Harmony(HarmonyKind::Triadic).anchor_colors(None, None);
+        assert_eq!(triadic.len(), 3);
+        let spread = (triadic[1].0 - triadic[0].0).rem_euclid(360.0);
+        assert!((spread - 120.0).abs() < 1e-3);
+
+        let dark = AnchorStrategy::RandomTriple.anchor_colors(None, Some(Vector2(0.1, 0.2)));
+        assert!(dark.iter().all(|color| (0.1..=0.2).contains(&color.2)));
+
+        let pixels = [Vector3(0.0, 1.0, 0.5), Vector3(240.0, 1.0, 0.5)].repeat(10);
+        let strategy = AnchorStrategy::FromImage { pixels, count: 2 };
+        let poline = Poline::from(PolineOptions {
+            anchor_strategy: strategy.clone(),
+            ..Default::default()
+        });
+        assert_eq!(poline.anchors().len(), 2);
+        let hash = poline.provenance().and_then(|p| p.source_image_hash.clone());
+        assert_eq!(hash, strategy.source_image_hash());
+        assert_eq!(hash.map(|hash| hash.len()), Some(16));
+        assert_eq!(AnchorStrategy::RandomPair.source_image_hash(), None);
+    }
+
+    #[test]
+    fn seeds_reproduce_palettes() {
+        let seeded = |seed| {
+            Poline::from(PolineOptions {
+                anchor_strategy: AnchorStrategy::RandomTriple,
+                seed: Some(seed),
+                ..Default::default()
+            })
+        };
+        assert_eq!(seeded(42).anchors(), seeded(42).anchors());
+        assert_ne!(seeded(42).anchors(), seeded(43).anchors());
+        assert_eq!(seeded(42).provenance().and_then(|p| p.seed), Some(42));
+        assert_eq!(Poline::from(seeded(42).options()), seeded(42));
+
+        let built = Poline::builder().seed(7).build().unwrap();
+        let expected = AnchorStrategy::RandomPair.seeded_anchor_colors(7, None, None);
+        assert_eq!(built.options().anchor_colors, Some(expected));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let pair = random_hsl_pair_with_rng(&mut rng, Some(10.0), None, None);
+        assert_eq!(pair[0].0, 10.0);
+        assert!((0.75..=0.95).contains(&pair[0].2));
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color_space::{hsl_to_rgb, rgb_to_hsl},
+    types::Vector3,
+};
+
+/// Separable blend modes as defined by the W3C compositing spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    Multiply,
+    Screen,
+    Overlay,
+    SoftLight,
+}
+
+impl BlendMode {
+    fn channel(self, base: f32, top: f32) -> f32 {
+        match self {
+            BlendMode::Multiply => base * top,
+            BlendMode::Screen => base + top - base * top,
+            BlendMode::Overlay => {
+                if base <= 0.5 {
+                    2.0 * base * top
+                } else {
+                    1.0 - 2.0 * (1.0 - base) * (1.0 - top)
+                }
+            }
+            BlendMode::SoftLight => {
+                if top <= 0.5 {
+                    base - (1.0 - 2.0 * top) * base * (1.0 - base)
+                } else {
+                    let d = if base <= 0.25 {
+                        ((16.0 * base - 12.0) * base + 4.0) * base
+                    } else {
+                        base.sqrt()
+                    };
+                    base + (2.0 * top - 1.0) * (d - base)
+                }
+            }
+        }
+    }
+}
+
+///
+/// Blends the HSL color `top` onto `base` in sRGB
+///
+/// blend([0, 0, 1], [0, 1, 0.5], BlendMode::Multiply); // [0, 1, 0.5]
+///
+pub fn blend(base: Vector3, top: Vector3, mode: BlendMode) -> Vector3 {
+    let b = hsl_to_rgb(base);
+    let t = hsl_to_rgb(top);
+    rgb_to_hsl(Vector3(
+        mode.channel(b.0, t.0),
+        mode.channel(b.1, t.1),
+        mode.channel(b.2, t.2),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vector3, b: Vector3) {
+        let eps = 1e-4;
+        assert!(
+            (a.0 - b.0).abs() < eps && (a.1 - b.1).abs() < eps && (a.2 - b.2).abs() < eps,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    fn gray(l: f32) -> Vector3 {
+        Vector3(0.0, 0.0, l)
+    }
+
+    #[test]
+    fn multiply_and_screen() {
+        // 0.3 * 0.6
+        assert_close(blend(gray(0.3), gray(0.6), BlendMode::Multiply), gray(0.18));
+        // 0.3 + 0.6 - 0.3 * 0.6
+        assert_close(blend(gray(0.3), gray(0.6), BlendMode::Screen), gray(0.72));
+
+        let red = Vector3(0.0, 1.0, 0.5);
+        let yellow = Vector3(60.0, 1.0, 0.5);
+        let blue = Vector3(240.0, 1.0, 0.5);
+        assert_close(blend(gray(1.0), red, BlendMode::Multiply), red);
+        // (1, 0, 0) * (1, 1, 0) = (1, 0, 0)
+        assert_close(blend(red, yellow, BlendMode::Multiply), red);
+        // (1, 0, 0) screen (0, 0, 1) = (1, 0, 1), magenta
+        assert_close(blend(red, blue, BlendMode::Screen), Vector3(300.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn overlay() {
+        // dark base: 2 * 0.3 * 0.6
+        assert_close(blend(gray(0.3), gray(0.6), BlendMode::Overlay), gray(0.36));
+        // light base: 1 - 2 * (1 - 0.8) * (1 - 0.6)
+        assert_close(blend(gray(0.8), gray(0.6), BlendMode::Overlay), gray(0.84));
+    }
+
+    #[test]
+    fn soft_light() {
+        // dark top: 0.3 - (1 - 2 * 0.2) * 0.3 * (1 - 0.3)
+        assert_close(blend(gray(0.3), gray(0.2), BlendMode::SoftLight), gray(0.174));
+        // light top, base above 0.25: 0.3 + (2 * 0.6 - 1) * (sqrt(0.3) - 0.3)
+        assert_close(blend(gray(0.3), gray(0.6), BlendMode::SoftLight), gray(0.349_544_5));
+        // light top, base at most 0.25: d = ((16 * 0.2 - 12) * 0.2 + 4) * 0.2 = 0.448,
+        // 0.2 + (2 * 0.75 - 1) * (0.448 - 0.2)
+        assert_close(blend(gray(0.2), gray(0.75), BlendMode::SoftLight), gray(0.324));
+    }
+}
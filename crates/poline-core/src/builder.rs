@@ -0,0 +1,266 @@
+use crate::{
+    AnchorStrategy, ColorModel, HueInterpolation, InterpolationSpace, PointCountMode, Poline,
+    PolineErrors, PolineOptions, PositionScale, Snapping, Vector2, Vector3,
+};
+
+/// Builds a [`Poline`] step by step, checking the options before any
+/// sampling happens
+///
+/// ```
+/// # use poline_core::{Poline, PolineErrors, Vector3};
+/// let poline = Poline::builder()
+///     .num_points(6)
+///     .closed_loop(true)
+///     .anchor_hsl(Vector3(20.0, 0.8, 0.3))
+///     .anchor_hsl(Vector3(200.0, 0.6, 0.8))
+///     .build()?;
+/// assert_eq!(poline.anchors().len(), 2);
+/// # Ok::<_, PolineErrors>(())
+/// ```
+///
+/// Without anchors, they are generated by the anchor strategy.
+#[derive(Debug, Clone, Default)]
+pub struct PolineBuilder {
+    options: PolineOptions,
+    anchors: Vec<Vector3>,
+}
+
+fn check_range(range: Option<Vector2>) -> Result<(), PolineErrors> {
+    match range {
+        Some(Vector2(min, max)) if !(0.0 <= min && min <= max && max <= 1.0) => {
+            Err(PolineErrors::InvalidOption)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_hsl(Vector3(h, s, l): Vector3) -> Result<(), PolineErrors> {
+    let in_range =
+        (0.0..=360.0).contains(&h) && (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&l);
+    if in_range {
+        Ok(())
+    } else {
+        Err(PolineErrors::ColorOutOfRange)
+    }
+}
+
+impl PolineBuilder {
+    /// Points between each pair of anchors, or in the whole palette with
+    /// [`PointCountMode::TotalOutput`]. At least 1.
+    pub fn num_points(mut self, num_points: usize) -> Self {
+        self.options.num_points = num_points;
+        self
+    }
+
+    pub fn point_count_mode(mut self, mode: PointCountMode) -> Self {
+        self.options.point_count_mode = mode;
+        self
+    }
+
+    pub fn closed_loop(mut self, closed_loop: bool) -> Self {
+        self.options.closed_loop = closed_loop;
+        self
+    }
+
+    pub fn inverted_lightness(mut self, inverted_lightness: bool) -> Self {
+        self.options.inverted_lightness = inverted_lightness;
+        self
+    }
+
+    pub fn color_model(mut self, model: ColorModel) -> Self {
+        self.options.color_model = model;
+        self
+    }
+
+    /// Appends an anchor, hue in 0..360 and saturation and lightness in 0..1
+    pub fn anchor_hsl(mut self, hsl: Vector3) -> Self {
+        self.anchors.push(hsl);
+        self
+    }
+
+    /// Appends several anchors, see [`PolineBuilder::anchor_hsl`]
+    pub fn anchors(mut self, anchors: impl IntoIterator<Item = Vector3>) -> Self {
+        self.anchors.extend(anchors);
+        self
+    }
+
+    /// How anchors are generated when none are given
+    pub fn anchor_strategy(mut self, strategy: AnchorStrategy) -> Self {
+        self.options.anchor_strategy = strategy;
+        self
+    }
+
+    /// Lightness range of generated anchors, within 0..1
+    pub fn random_lightness_range(mut self, range: Vector2) -> Self {
+        self.options.random_lightness_range = Some(range);
+        self
+    }
+
+    /// Saturation range of generated anchors, within 0..1
+    pub fn random_saturation_range(mut self, range: Vector2) -> Self {
+        self.options.random_saturation_range = Some(range);
+        self
+    }
+
+    /// Draws the generated anchors from `seed`, the same seed builds the
+    /// same palette
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.options.seed = Some(seed);
+        self
+    }
+
+    /// The position function of all three axes
+    pub fn position_function(mut self, scale: PositionScale) -> Self {
+        self.options.position_function = scale;
+        self.options.position_function_x = None;
+        self.options.position_function_y = None;
+        self.options.position_function_z = None;
+        self
+    }
+
+    pub fn position_function_x(mut self, scale: PositionScale) -> Self {
+        self.options.position_function_x = Some(scale);
+        self
+    }
+
+    pub fn position_function_y(mut self, scale: PositionScale) -> Self {
+        self.options.position_function_y = Some(scale);
+        self
+    }
+
+    pub fn position_function_z(mut self, scale: PositionScale) -> Self {
+        self.options.position_function_z = Some(scale);
+        self
+    }
+
+    pub fn reverse(mut self, reverse_x: bool, reverse_y: bool, reverse_z: bool) -> Self {
+        self.options.reverse_x = reverse_x;
+        self.options.reverse_y = reverse_y;
+        self.options.reverse_z = reverse_z;
+        self
+    }
+
+    pub fn monotonic_lightness(mut self, monotonic_lightness: bool) -> Self {
+        self.options.monotonic_lightness = monotonic_lightness;
+        self
+    }
+
+    pub fn smooth_seam(mut self, smooth_seam: bool) -> Self {
+        self.options.smooth_seam = smooth_seam;
+        self
+    }
+
+    pub fn include_anchor_duplicates(mut self, include_anchor_duplicates: bool) -> Self {
+        self.options.include_anchor_duplicates = include_anchor_duplicates;
+        self
+    }
+
+    pub fn interpolation(mut self, interpolation: InterpolationSpace) -> Self {
+        self.options.interpolation = interpolation;
+        self
+    }
+
+    pub fn hue_interpolation(mut self, hue_interpolation: HueInterpolation) -> Self {
+        self.options.hue_interpolation = hue_interpolation;
+        self
+    }
+
+    /// The most anchors the palette accepts, [`crate::MAX_ANCHORS`] by default
+    pub fn max_anchors(mut self, max_anchors: usize) -> Self {
+        self.options.max_anchors = Some(max_anchors);
+        self
+    }
+
+    /// The most colors the palette outputs, [`crate::MAX_TOTAL_POINTS`] by default
+    pub fn max_total_points(mut self, max_total_points: usize) -> Self {
+        self.options.max_total_points = Some(max_total_points);
+        self
+    }
+
+    /// Steps anchors are rounded to when they are edited later on
+    pub fn snapping(mut self, snapping: Snapping) -> Self {
+        self.options.snapping = Some(snapping);
+        self
+    }
+
+    /// Checks the options and samples the palette. Fails with
+    /// `InvalidOption` for a point count of 0 or a bad random range,
+    /// `NotEnoughAnchors` for fewer than two anchors, `ColorOutOfRange`
+    /// for anchors outside of HSL's ranges and `TooManyAnchors` or
+    /// `TooManyPoints` past the limits.
+    pub fn build(self) -> Result<Poline, PolineErrors> {
+        let Self {
+            mut options,
+            anchors,
+        } = self;
+        if options.num_points == 0 {
+            return Err(PolineErrors::InvalidOption);
+        }
+        check_range(options.random_lightness_range)?;
+        check_range(options.random_saturation_range)?;
+        let anchors = if anchors.is_empty() {
+            options.generate_anchor_colors()
+        } else {
+            anchors
+        };
+        if anchors.len() < 2 {
+            return Err(PolineErrors::NotEnoughAnchors);
+        }
+        anchors.iter().try_for_each(|&hsl| check_hsl(hsl))?;
+        options.anchor_colors = Some(anchors);
+        Poline::try_from_options(options)
+    }
+}
+
+impl Poline {
+    pub fn builder() -> PolineBuilder {
+        PolineBuilder::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_palettes() {
+        let poline = Poline::builder()
+            .num_points(6)
+            .closed_loop(true)
+            .anchor_hsl(Vector3(20.0, 0.8, 0.3))
+            .anchor_hsl(Vector3(200.0, 0.6, 0.8))
+            .build()
+            .unwrap();
+        assert_eq!(poline.anchors().len(), 2);
+        assert_eq!(poline.colors_vec().len(), 14);
+        assert!(Poline::builder().build().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        let two = [Vector3(0.0, 0.5, 0.5), Vector3(90.0, 0.5, 0.5)];
+        let error = Poline::builder().num_points(0).anchors(two).build();
+        assert!(matches!(error, Err(PolineErrors::InvalidOption)));
+
+        let error = Poline::builder().anchor_hsl(two[0]).build();
+        assert!(matches!(error, Err(PolineErrors::NotEnoughAnchors)));
+
+        let error = Poline::builder()
+            .anchors(two)
+            .anchor_hsl(Vector3(0.0, 1.5, 0.5))
+            .build();
+        assert!(matches!(error, Err(PolineErrors::ColorOutOfRange)));
+
+        let error = Poline::builder()
+            .random_lightness_range(Vector2(0.8, 0.2))
+            .build();
+        assert!(matches!(error, Err(PolineErrors::InvalidOption)));
+
+        let error = Poline::builder()
+            .anchors(two)
+            .num_points(100)
+            .max_total_points(50)
+            .build();
+        assert!(matches!(error, Err(PolineErrors::TooManyPoints)));
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    types::Vector3,
+    utils::{
+        hsl_to_point, hsl_to_point_lightness_z, hsl_to_point_oklch, point_to_hsl,
+        point_to_hsl_lightness_z, point_to_hsl_oklch,
+    },
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Defines how an (x, y, z) position in the unit cube maps onto a color
+pub enum ColorModel {
+    /// The angle around (0.5, 0.5) is the hue, the radius is the lightness
+    /// and z is the saturation. This is the layout used by poline.js.
+    #[default]
+    Hsl,
+    /// The angle around (0.5, 0.5) is the hue, the radius is the saturation
+    /// and z is the lightness.
+    LightnessOnZ,
+    /// The disc is OKLCH hue and chroma and z is OKLab lightness, so the
+    /// interpolation between anchors happens in a perceptual space.
+    OklchCylinder,
+}
+
+impl ColorModel {
+    /// Every model, indexed by its [`ColorModel::id`]
+    pub const ALL: &'static [ColorModel] = &[
+        ColorModel::Hsl,
+        ColorModel::LightnessOnZ,
+        ColorModel::OklchCylinder,
+    ];
+
+    /// The stable number `number_as_color_model` and the share string take
+    pub const fn id(self) -> usize {
+        self as usize
+    }
+
+    /// The model numbered `id`, or `None` past the last one, for numbers
+    /// that come from outside like JS options
+    pub fn from_id(id: usize) -> Option<Self> {
+        Self::ALL.get(id).copied()
+    }
+
+    /// Converts a position to an HSL color using this model's layout
+    pub fn point_to_hsl(self, xyz: Vector3, inverted_lightness: bool) -> Vector3 {
+        match self {
+            ColorModel::Hsl => point_to_hsl(xyz, inverted_lightness),
+            ColorModel::LightnessOnZ => point_to_hsl_lightness_z(xyz, inverted_lightness),
+            ColorModel::OklchCylinder => point_to_hsl_oklch(xyz, inverted_lightness),
+        }
+    }
+
+    /// Converts an HSL color to a position using this model's layout
+    pub fn hsl_to_point(self, hsl: Vector3, inverted_lightness: bool) -> Vector3 {
+        match self {
+            ColorModel::Hsl => hsl_to_point(hsl, inverted_lightness),
+            ColorModel::LightnessOnZ => hsl_to_point_lightness_z(hsl, inverted_lightness),
+            ColorModel::OklchCylinder => hsl_to_point_oklch(hsl, inverted_lightness),
+        }
+    }
+
+    /// [`ColorModel::point_to_hsl`] over a batch of positions, picking the
+    /// conversion once instead of per point
+    pub fn points_to_hsl(self, points: &[Vector3], inverted_lightness: bool) -> Vec<Vector3> {
+        let convert = match self {
+            ColorModel::Hsl => point_to_hsl,
+            ColorModel::LightnessOnZ => point_to_hsl_lightness_z,
+            ColorModel::OklchCylinder => point_to_hsl_oklch,
+        };
+        points
+            .iter()
+            .map(|&xyz| convert(xyz, inverted_lightness))
+            .collect()
+    }
+
+    /// [`ColorModel::hsl_to_point`] over a batch of colors, picking the
+    /// conversion once instead of per color
+    pub fn hsl_to_points(self, colors: &[Vector3], inverted_lightness: bool) -> Vec<Vector3> {
+        let convert = match self {
+            ColorModel::Hsl => hsl_to_point,
+            ColorModel::LightnessOnZ => hsl_to_point_lightness_z,
+            ColorModel::OklchCylinder => hsl_to_point_oklch,
+        };
+        colors
+            .iter()
+            .map(|&hsl| convert(hsl, inverted_lightness))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        for (id, &model) in ColorModel::ALL.iter().enumerate() {
+            assert_eq!(model.id(), id);
+            assert_eq!(ColorModel::from_id(id), Some(model));
+        }
+        assert_eq!(ColorModel::from_id(ColorModel::ALL.len()), None);
+    }
+
+    #[test]
+    fn oklch_cylinder_keeps_color() {
+        let hsl = Vector3(200.0, 0.5, 0.5);
+        let point = ColorModel::OklchCylinder.hsl_to_point(hsl, false);
+        let Vector3(h, s, l) = ColorModel::OklchCylinder.point_to_hsl(point, false);
+        assert!((h - hsl.0).abs() < 0.5);
+        assert!((s - hsl.1).abs() < 1e-2);
+        assert!((l - hsl.2).abs() < 1e-2);
+    }
+
+    #[test]
+    fn batch_conversions_match_single() {
+        let colors = [Vector3(10.0, 0.2, 0.3), Vector3(300.0, 0.9, 0.6)];
+        for &model in ColorModel::ALL {
+            let points = model.hsl_to_points(&colors, true);
+            for (point, &color) in points.iter().zip(&colors) {
+                assert_eq!(*point, model.hsl_to_point(color, true));
+            }
+            let back = model.points_to_hsl(&points, true);
+            for (color, &point) in back.iter().zip(&points) {
+                assert_eq!(*color, model.point_to_hsl(point, true));
+            }
+        }
+    }
+}
@@ -1,20 +1,27 @@
 
+use std::fmt;
+
 use serde::{Serialize, Deserialize};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
 use crate::{
+    color_model::ColorModel,
+    float_format::{css_hsl, FloatFormat},
     types::Vector3,
-    utils::{hsl_to_point, point_to_hsl}, PolineErrors,
 };
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ColorPointCollection {
     pub xyz: Option<Vector3>,
     pub color: Option<Vector3>,
     pub inverted_lightness: bool,
+    #[serde(default)]
+    pub model: ColorModel,
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl ColorPointCollection {
     pub fn new(init: JsValue) -> Self {
@@ -22,7 +29,7 @@ impl ColorPointCollection {
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ColorPoint {
     pub x: f32,
@@ -30,6 +37,8 @@ pub struct ColorPoint {
     pub z: f32,
     pub color: Vector3,
     _inverted_lightness: bool,
+    #[serde(default)]
+    _model: ColorModel,
 }
 
 impl Default for ColorPoint {
@@ -40,27 +49,53 @@ impl Default for ColorPoint {
             z: 0.0,
             color: Vector3(0.0_f32, 0.0_f32, 0.0_f32),
             _inverted_lightness: false,
+            _model: ColorModel::Hsl,
         }
     }
 }
 
 
-#[wasm_bindgen]
+impl ColorPoint {
+    /// Rebuilds a point from a position and color that already agree
+    pub(crate) fn from_parts(
+        position: Vector3,
+        color: Vector3,
+        inverted_lightness: bool,
+        model: ColorModel,
+    ) -> Self {
+        let Vector3(x, y, z) = position;
+        Self {
+            x,
+            y,
+            z,
+            color,
+            _inverted_lightness: inverted_lightness,
+            _model: model,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl ColorPoint {
     pub fn new(initial: ColorPointCollection) -> Self {
-        let mut result = Self::default();
-        result._inverted_lightness = initial.inverted_lightness;
+        let mut result = Self {
+            _inverted_lightness: initial.inverted_lightness,
+            _model: initial.model,
+            ..Default::default()
+        };
         match (initial.xyz, initial.color) {
             (Some(Vector3(x, y, z)), _) => {
                 result.x = x;
                 result.y = y;
                 result.z = z;
-                result.color = point_to_hsl(Vector3(x, y, z), initial.inverted_lightness);
+                result.color = initial
+                    .model
+                    .point_to_hsl(Vector3(x, y, z), initial.inverted_lightness);
                 result
             }
             (_, Some(color)) => {
                 result.color = color;
-                let Vector3(x, y, z) = hsl_to_point(color, initial.inverted_lightness);
+                let Vector3(x, y, z) = initial.model.hsl_to_point(color, initial.inverted_lightness);
                 result.x = x;
                 result.y = y;
                 result.z = z;
@@ -75,7 +110,7 @@ impl ColorPoint {
         self.x = x;
         self.y = y;
         self.z = z;
-        self.color = point_to_hsl(new_position, self._inverted_lightness)
+        self.color = self._model.point_to_hsl(new_position, self._inverted_lightness)
     }
 
     pub fn position(&self) -> Vector3 {
@@ -84,7 +119,7 @@ impl ColorPoint {
 
     pub fn set_hsl(&mut self, new_color: Vector3) {
         self.color = new_color;
-        let Vector3(x, y, z) = hsl_to_point(new_color, self._inverted_lightness);
+        let Vector3(x, y, z) = self._model.hsl_to_point(new_color, self._inverted_lightness);
         self.x = x;
         self.y = y;
         self.z = z;
@@ -95,20 +130,49 @@ impl ColorPoint {
     }
 
     pub fn hsl_css(&self) -> String {
-        let Vector3(h, s, l) = self.color;
-        let hue = h;
-        let saturation = s * 100.0;
-        let luminance = l * 100.0;
-        format!("hsl({hue},{saturation}%,{luminance}%")
+        css_hsl(self.color, FloatFormat::new(2))
     }
 
     pub fn shift_hue(&mut self, angle: f32) {
         self.color.0 = (360.0 + (self.color.0 + angle)) % 360.0;
-        let Vector3(x, y, z) = hsl_to_point(self.color, self._inverted_lightness);
+        let Vector3(x, y, z) = self._model.hsl_to_point(self.color, self._inverted_lightness);
         self.x = x;
         self.y = y;
         self.z = z;
     }
+
+    pub fn model(&self) -> ColorModel {
+        self._model
+    }
+
+    /// Switches the point to another coordinate mapping, keeping its color
+    pub fn set_model(&mut self, model: ColorModel) {
+        self._model = model;
+        self.set_hsl(self.color);
+    }
+
+    /// Flips which end of the lightness axis is white, keeping its color
+    pub fn set_inverted_lightness(&mut self, inverted_lightness: bool) {
+        self._inverted_lightness = inverted_lightness;
+        self.set_hsl(self.color);
+    }
+}
+
+/// `hsl(210, 60%, 50%) @ (0.42, 0.61, 0.60)`
+impl fmt::Display for ColorPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Vector3(h, s, l) = self.color;
+        write!(
+            f,
+            "hsl({:.0}, {:.0}%, {:.0}%) @ ({:.2}, {:.2}, {:.2})",
+            h,
+            s * 100.0,
+            l * 100.0,
+            self.x,
+            self.y,
+            self.z
+        )
+    }
 }
 
 #[cfg(test)]
@@ -120,6 +184,7 @@ mod tests {
             xyz: Some(Vector3(1.0, 1.0, 1.0)),
             color: None,
             inverted_lightness: true,
+            model: ColorModel::Hsl,
         });
         assert_eq!(color_point.color, Vector3(
             45.0,
@@ -135,10 +200,22 @@ mod tests {
             xyz: None,
             color: Some(Vector3(1.0, 1.0, 1.0)),
             inverted_lightness: true,
+            model: ColorModel::Hsl,
         });
         assert_eq!(color_point.position(), Vector3(
            0.5,0.5,1.0
         )
         );
     }
+
+    #[test]
+    fn displays_color_and_position() {
+        let color_point = ColorPoint::new(ColorPointCollection {
+            xyz: None,
+            color: Some(Vector3(210.0, 0.6, 0.5)),
+            inverted_lightness: false,
+            model: ColorModel::LightnessOnZ,
+        });
+        assert_eq!(color_point.to_string(), "hsl(210, 60%, 50%) @ (0.24, 0.35, 0.50)");
+    }
 }
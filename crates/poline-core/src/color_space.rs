@@ -0,0 +1,294 @@
+use crate::types::Vector3;
+
+/// Chroma mapped to the rim of the OKLCH cylinder, roughly the most
+/// saturated color sRGB can display
+pub const OKLCH_MAX_CHROMA: f32 = 0.37;
+
+///
+/// Converts an HSL color (hue in degrees, saturation and lightness in 0..1)
+/// to sRGB with channels in 0..1
+///
+/// hsl_to_rgb([0, 1, 0.5]); // [1, 0, 0]
+///
+pub fn hsl_to_rgb(hsl: Vector3) -> Vector3 {
+    let Vector3(h, s, l) = hsl;
+    let h = h.rem_euclid(360.0);
+    let s = s.clamp(0.0, 1.0);
+    let l = l.clamp(0.0, 1.0);
+
+    let channel = |n: f32| {
+        let k = (n + h / 30.0) % 12.0;
+        let a = s * l.min(1.0 - l);
+        l - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+
+    Vector3(channel(0.0), channel(8.0), channel(4.0))
+}
+
+///
+/// Converts an sRGB color with channels in 0..1 to HSL
+///
+/// rgb_to_hsl([1, 0, 0]); // [0, 1, 0.5]
+///
+pub fn rgb_to_hsl(rgb: Vector3) -> Vector3 {
+    let Vector3(r, g, b) = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let delta = max - min;
+
+    if delta == 0.0 {
+        return Vector3(0.0, 0.0, l);
+    }
+
+    let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+
+    Vector3(h * 60.0, s, l)
+}
+
+fn srgb_channel_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_channel_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Removes the sRGB transfer curve from each channel
+pub fn srgb_to_linear(rgb: Vector3) -> Vector3 {
+    Vector3(
+        srgb_channel_to_linear(rgb.0),
+        srgb_channel_to_linear(rgb.1),
+        srgb_channel_to_linear(rgb.2),
+    )
+}
+
+/// Applies the sRGB transfer curve to each linear channel
+pub fn linear_to_srgb(rgb: Vector3) -> Vector3 {
+    Vector3(
+        linear_channel_to_srgb(rgb.0),
+        linear_channel_to_srgb(rgb.1),
+        linear_channel_to_srgb(rgb.2),
+    )
+}
+
+/// Converts linear sRGB to OKLab
+pub fn linear_rgb_to_oklab(rgb: Vector3) -> Vector3 {
+    let Vector3(r, g, b) = rgb;
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+    Vector3(
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    )
+}
+
+/// Converts OKLab to linear sRGB, the result may lie outside of 0..1
+pub fn oklab_to_linear_rgb(lab: Vector3) -> Vector3 {
+    let Vector3(l, a, b) = lab;
+    let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+    Vector3(
+        4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_,
+        -1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_,
+        -0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_,
+    )
+}
+
+/// Converts an sRGB color with channels in 0..1 to OKLab
+pub fn rgb_to_oklab(rgb: Vector3) -> Vector3 {
+    linear_rgb_to_oklab(srgb_to_linear(rgb))
+}
+
+/// Converts OKLab to sRGB, clamping to the displayable gamut
+pub fn oklab_to_rgb(lab: Vector3) -> Vector3 {
+    clamp_rgb(linear_to_srgb(oklab_to_linear_rgb(lab)))
+}
+
+/// Converts OKLab to OKLCH (lightness, chroma, hue in degrees)
+pub fn oklab_to_oklch(lab: Vector3) -> Vector3 {
+    let Vector3(l, a, b) = lab;
+    let c = (a * a + b * b).sqrt();
+    let h = b.atan2(a).to_degrees().rem_euclid(360.0);
+    Vector3(l, c, h)
+}
+
+/// Converts OKLCH (lightness, chroma, hue in degrees) to OKLab
+pub fn oklch_to_oklab(lch: Vector3) -> Vector3 {
+    let Vector3(l, c, h) = lch;
+    let radians = h.to_radians();
+    Vector3(l, c * radians.cos(), c * radians.sin())
+}
+
+/// Converts an HSL color to OKLab
+pub fn hsl_to_oklab(hsl: Vector3) -> Vector3 {
+    rgb_to_oklab(hsl_to_rgb(hsl))
+}
+
+/// Converts an OKLab color to HSL, clamping to the sRGB gamut
+pub fn oklab_to_hsl(lab: Vector3) -> Vector3 {
+    rgb_to_hsl(oklab_to_rgb(lab))
+}
+
+/// Clamps each channel into 0..1
+pub fn clamp_rgb(rgb: Vector3) -> Vector3 {
+    Vector3(
+        rgb.0.clamp(0.0, 1.0),
+        rgb.1.clamp(0.0, 1.0),
+        rgb.2.clamp(0.0, 1.0),
+    )
+}
+
+/// Quantizes an sRGB color with channels in 0..1 to 8-bit channels
+pub fn rgb_to_rgb8(rgb: Vector3) -> [u8; 3] {
+    let Vector3(r, g, b) = clamp_rgb(rgb);
+    [
+        (r * 255.0).round() as u8,
+        (g * 255.0).round() as u8,
+        (b * 255.0).round() as u8,
+    ]
+}
+
+/// The perceptual difference between two HSL colors as Euclidean distance
+/// in OKLab, where about 0.02 is a just noticeable difference
+pub fn delta_e(a: Vector3, b: Vector3) -> f32 {
+    let a = hsl_to_oklab(a);
+    let b = hsl_to_oklab(b);
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+/// Formats an sRGB color with channels in 0..1 as `#rrggbb`
+pub fn rgb_to_hex(rgb: Vector3) -> String {
+    let [r, g, b] = rgb_to_rgb8(rgb);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Formats an sRGB color and alpha with channels in 0..1 as `#rrggbbaa`
+pub fn rgba_to_hex(rgb: Vector3, alpha: f32) -> String {
+    let a = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("{}{a:02x}", rgb_to_hex(rgb))
+}
+
+/// Converts linear sRGB to CIE XYZ (D65, Y of white is 1.0)
+pub fn linear_rgb_to_xyz(rgb: Vector3) -> Vector3 {
+    let Vector3(r, g, b) = rgb;
+    Vector3(
+        0.412_338_95 * r + 0.357_620_64 * g + 0.180_510_42 * b,
+        0.2126 * r + 0.7152 * g + 0.0722 * b,
+        0.019_321_41 * r + 0.119_163_82 * g + 0.950_344_8 * b,
+    )
+}
+
+/// Converts CIE XYZ (D65, Y of white is 1.0) to linear sRGB
+pub fn xyz_to_linear_rgb(xyz: Vector3) -> Vector3 {
+    let Vector3(x, y, z) = xyz;
+    Vector3(
+        3.241_377_5 * x - 1.537_665_2 * y - 0.498_853_67 * z,
+        -0.969_145_24 * x + 1.875_885_3 * y + 0.041_565_856 * z,
+        0.055_620_94 * x - 0.203_955_24 * y + 1.057_18 * z,
+    )
+}
+
+/// Converts linear sRGB to linear Rec.2020 (BT.2087 primaries conversion)
+pub fn linear_rgb_to_rec2020(rgb: Vector3) -> Vector3 {
+    let Vector3(r, g, b) = rgb;
+    Vector3(
+        0.627_403_9 * r + 0.329_283_04 * g + 0.043_313_067 * b,
+        0.069_097_29 * r + 0.919_540_4 * g + 0.011_362_316 * b,
+        0.016_391_44 * r + 0.088_013_31 * g + 0.895_595_3 * b,
+    )
+}
+
+fn rec2020_oetf(c: f32) -> f32 {
+    let alpha = 1.099_296_8;
+    let beta = 0.018_053_97;
+    if c < beta {
+        4.5 * c
+    } else {
+        alpha * c.powf(0.45) - (alpha - 1.0)
+    }
+}
+
+/// Converts an sRGB color to non-linear Rec.2020 with channels in 0..1
+pub fn rgb_to_rec2020(rgb: Vector3) -> Vector3 {
+    let Vector3(r, g, b) = linear_rgb_to_rec2020(srgb_to_linear(rgb));
+    clamp_rgb(Vector3(rec2020_oetf(r), rec2020_oetf(g), rec2020_oetf(b)))
+}
+
+/// Converts an sRGB color to scRGB, which is linear sRGB where 1.0 is
+/// 80 nits and values above 1.0 are brighter than SDR white
+pub fn rgb_to_scrgb(rgb: Vector3, brightness: f32) -> Vector3 {
+    let Vector3(r, g, b) = srgb_to_linear(rgb);
+    Vector3(r * brightness, g * brightness, b * brightness)
+}
+
+///
+/// Packs a color with channels in 0..1 into a `u32` RGB10A2 value, red in
+/// the lowest 10 bits and the 2-bit alpha in the highest
+/// (`DXGI_FORMAT_R10G10B10A2_UNORM` / `GL_UNSIGNED_INT_2_10_10_10_REV`)
+///
+pub fn pack_rgb10a2(rgb: Vector3, alpha: f32) -> u32 {
+    let Vector3(r, g, b) = clamp_rgb(rgb);
+    let ten_bit = |c: f32| (c * 1023.0).round() as u32;
+    let a = (alpha.clamp(0.0, 1.0) * 3.0).round() as u32;
+    ten_bit(r) | (ten_bit(g) << 10) | (ten_bit(b) << 20) | (a << 30)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vector3, b: Vector3) {
+        let eps = 1e-3;
+        assert!(
+            (a.0 - b.0).abs() < eps && (a.1 - b.1).abs() < eps && (a.2 - b.2).abs() < eps,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn hsl_rgb_round_trip() {
+        assert_close(hsl_to_rgb(Vector3(0.0, 1.0, 0.5)), Vector3(1.0, 0.0, 0.0));
+        assert_close(hsl_to_rgb(Vector3(240.0, 1.0, 0.5)), Vector3(0.0, 0.0, 1.0));
+        let hsl = Vector3(210.0, 0.6, 0.4);
+        assert_close(rgb_to_hsl(hsl_to_rgb(hsl)), hsl);
+    }
+
+    #[test]
+    fn oklab_white_and_round_trip() {
+        assert_close(rgb_to_oklab(Vector3(1.0, 1.0, 1.0)), Vector3(1.0, 0.0, 0.0));
+        let rgb = Vector3(0.2, 0.5, 0.8);
+        assert_close(oklab_to_rgb(rgb_to_oklab(rgb)), rgb);
+        let lab = rgb_to_oklab(rgb);
+        assert_close(oklch_to_oklab(oklab_to_oklch(lab)), lab);
+    }
+
+    #[test]
+    fn rec2020_and_packing() {
+        assert_close(rgb_to_rec2020(Vector3(1.0, 1.0, 1.0)), Vector3(1.0, 1.0, 1.0));
+        let red = rgb_to_rec2020(Vector3(1.0, 0.0, 0.0));
+        assert!(red.0 < 1.0 && red.1 > 0.0);
+        assert_eq!(pack_rgb10a2(Vector3(1.0, 0.0, 0.0), 1.0), 0xC000_03FF);
+        assert_eq!(pack_rgb10a2(Vector3(0.0, 0.0, 1.0), 0.0), 0x3FF0_0000);
+    }
+}
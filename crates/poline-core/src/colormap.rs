@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color_space::{hsl_to_rgb, rgb_to_hex},
+    types::Vector3,
+    Poline,
+};
+
+/// Maps normalized values onto a palette, matplotlib style
+#[derive(Debug, Clone, PartialEq)]
+pub struct Colormap {
+    /// sRGB colors with channels in 0..1, evenly spaced over 0..1 unless
+    /// `positions` places them
+    colors: Vec<Vector3>,
+    /// Increasing position of each color in 0..1
+    positions: Option<Vec<f64>>,
+    reversed: bool,
+    /// Values outside of 0..1 wrap around instead of being clamped
+    cyclic: bool,
+}
+
+/// One colorbar tick of a [`Poline::legend`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LegendTick {
+    /// Position of the tick in 0..1
+    pub t: f64,
+    /// sRGB color with channels in 0..1
+    pub color: [f32; 3],
+    /// The color as a CSS hex string
+    pub css: String,
+}
+
+impl Colormap {
+    pub fn new(colors: Vec<Vector3>, cyclic: bool) -> Self {
+        Self {
+            colors,
+            positions: None,
+            reversed: false,
+            cyclic,
+        }
+    }
+
+    /// A colormap with each color at its own position in 0..1, cyclic
+    /// colormaps interpolate from the last color back to the first at 1
+    pub fn with_positions(colors: Vec<Vector3>, positions: Vec<f64>, cyclic: bool) -> Self {
+        debug_assert_eq!(colors.len(), positions.len());
+        Self {
+            colors,
+            positions: Some(positions),
+            reversed: false,
+            cyclic,
+        }
+    }
+
+    /// The same colormap running from the last color to the first
+    pub fn reversed(&self) -> Self {
+        Self {
+            reversed: !self.reversed,
+            ..self.clone()
+        }
+    }
+
+    /// The same colormap clamping values outside of 0..1 to the end colors,
+    /// even when built from a closed loop
+    pub fn clamped(&self) -> Self {
+        Self {
+            cyclic: false,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    pub fn is_cyclic(&self) -> bool {
+        self.cyclic
+    }
+
+    /// The sRGB color at `t`, linearly interpolated between neighbouring swatches
+    pub fn at(&self, t: f64) -> [f32; 3] {
+        let Some(last) = self.colors.len().checked_sub(1) else {
+            return [0.0, 0.0, 0.0];
+        };
+        let mut t = if self.cyclic {
+            t.rem_euclid(1.0)
+        } else {
+            t.clamp(0.0, 1.0)
+        };
+        if self.reversed {
+            t = 1.0 - t;
+        }
+        if let Some(positions) = &self.positions {
+            return self.at_position(positions, t);
+        }
+
+        let position = t * last as f64;
+        let index = (position.floor() as usize).min(last.saturating_sub(1));
+        let next = (index + 1).min(last);
+        let fraction = (position - index as f64).clamp(0.0, 1.0) as f32;
+        let a = self.colors[index];
+        let b = self.colors[next];
+        [
+            a.0 + (b.0 - a.0) * fraction,
+            a.1 + (b.1 - a.1) * fraction,
+            a.2 + (b.2 - a.2) * fraction,
+        ]
+    }
+
+    fn at_position(&self, positions: &[f64], t: f64) -> [f32; 3] {
+        let lerp = |a: Vector3, b: Vector3, fraction: f32| {
+            [
+                a.0 + (b.0 - a.0) * fraction,
+                a.1 + (b.1 - a.1) * fraction,
+                a.2 + (b.2 - a.2) * fraction,
+            ]
+        };
+        let first = self.colors[0];
+        let last = self.colors.len() - 1;
+        let next = positions.partition_point(|&position| position < t);
+        if next == 0 {
+            return [first.0, first.1, first.2];
+        }
+        let (to, to_position) = match positions.get(next) {
+            Some(&position) => (self.colors[next], position),
+            None if self.cyclic => (first, 1.0),
+            None => {
+                let color = self.colors[last];
+                return [color.0, color.1, color.2];
+            }
+        };
+        let from_position = positions[next - 1];
+        let span = to_position - from_position;
+        let fraction = if span > 0.0 {
+            ((t - from_position) / span).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        };
+        lerp(self.colors[next - 1], to, fraction)
+    }
+
+    /// `n` colors evenly spaced from 0 to 1, computed on the rayon thread
+    /// pool when the `rayon` feature is enabled
+    pub fn discrete(&self, n: usize) -> Vec<[f32; 3]> {
+        match n {
+            0 => Vec::new(),
+            1 => vec![self.at(0.0)],
+            #[cfg(feature = "rayon")]
+            _ => {
+                use rayon::prelude::*;
+                (0..n)
+                    .into_par_iter()
+                    .map(|i| self.at(i as f64 / (n - 1) as f64))
+                    .collect()
+            }
+            #[cfg(not(feature = "rayon"))]
+            _ => (0..n).map(|i| self.at(i as f64 / (n - 1) as f64)).collect(),
+        }
+    }
+}
+
+impl Poline {
+    /// A colormap over the palette with the colors at their [`Poline::stops`],
+    /// wrapping around for closed loops
+    pub fn colormap(&self) -> Colormap {
+        let (positions, colors) = self
+            .stops()
+            .into_iter()
+            .map(|(position, color)| (position as f64, hsl_to_rgb(color)))
+            .unzip();
+        Colormap::with_positions(colors, positions, self.connect_last_and_first_anchor)
+    }
+
+    /// `n_ticks` evenly spaced colorbar ticks from 0 to 1, sampled from the
+    /// same [`Colormap`] that maps values to colors
+    pub fn legend(&self, n_ticks: usize) -> Vec<LegendTick> {
+        let colormap = self.colormap().clamped();
+        (0..n_ticks)
+            .map(|i| {
+                let t = if n_ticks > 1 {
+                    i as f64 / (n_ticks - 1) as f64
+                } else {
+                    0.0
+                };
+                let color = colormap.at(t);
+                let [r, g, b] = color;
+                LegendTick {
+                    t,
+                    color,
+                    css: rgb_to_hex(Vector3(r, g, b)),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_between_colors() {
+        let colormap = Colormap::new(vec![Vector3(0.0, 0.0, 0.0), Vector3(1.0, 1.0, 1.0)], false);
+        assert_eq!(colormap.at(0.5), [0.5, 0.5, 0.5]);
+        assert_eq!(colormap.at(2.0), [1.0, 1.0, 1.0]);
+        assert_eq!(colormap.reversed().at(0.25), [0.75, 0.75, 0.75]);
+        assert_eq!(colormap.discrete(3)[1], [0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn samples_between_positioned_colors() {
+        let black = Vector3(0.0, 0.0, 0.0);
+        let white = Vector3(1.0, 1.0, 1.0);
+        let colormap =
+            Colormap::with_positions(vec![black, white, black], vec![0.0, 0.25, 1.0], false);
+        assert_eq!(colormap.at(0.125), [0.5, 0.5, 0.5]);
+        assert_eq!(colormap.at(0.625), [0.5, 0.5, 0.5]);
+
+        let cyclic = Colormap::with_positions(vec![black, white], vec![0.0, 0.5], true);
+        assert_eq!(cyclic.at(0.75), [0.5, 0.5, 0.5]);
+        assert_eq!(cyclic.at(1.25), [0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn legend_ticks_match_the_colormap() {
+        let poline = Poline::from(crate::PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.9, 0.2), Vector3(180.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let legend = poline.legend(5);
+        assert_eq!(legend.len(), 5);
+        assert_eq!(legend[0].t, 0.0);
+        assert_eq!(legend[4].t, 1.0);
+        assert_eq!(legend[2].color, poline.colormap().at(0.5));
+        assert!(legend[2].css.starts_with('#'));
+    }
+}
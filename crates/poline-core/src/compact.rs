@@ -0,0 +1,101 @@
+//! Compact binary encodings of a palette for save files, URLs and network
+//! payloads. Only the options and anchors are stored, the sampled points
+//! are regenerated when decoding.
+
+#[cfg(feature = "postcard")]
+use crate::SCHEMA_VERSION;
+use crate::{Poline, PolineErrors, PolineOptions};
+
+impl Poline {
+    /// The palette as postcard bytes. Postcard is positional, so the bytes
+    /// only decode with the same [`SCHEMA_VERSION`] they were written with.
+    #[cfg(feature = "postcard")]
+    pub fn to_postcard(&self) -> Vec<u8> {
+        postcard::to_allocvec(&self.options()).expect("options always serialize")
+    }
+
+    /// Reads a palette written by [`Poline::to_postcard`]
+    #[cfg(feature = "postcard")]
+    pub fn from_postcard(bytes: &[u8]) -> Result<Poline, PolineErrors> {
+        // The version is the first field, check it before decoding the rest
+        let (version, _) =
+            postcard::take_from_bytes::<u32>(bytes).map_err(|_| PolineErrors::InvalidOption)?;
+        if version != SCHEMA_VERSION {
+            return Err(PolineErrors::UnsupportedVersion);
+        }
+        let options: PolineOptions =
+            postcard::from_bytes(bytes).map_err(|_| PolineErrors::InvalidOption)?;
+        options_to_poline(options)
+    }
+
+    /// The palette as MessagePack with named fields, readable by later
+    /// versions of poline
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        rmp_serde::to_vec_named(&self.options()).expect("options always serialize")
+    }
+
+    /// Reads a palette written by [`Poline::to_msgpack`] with any earlier
+    /// version of poline
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Poline, PolineErrors> {
+        let options: PolineOptions =
+            rmp_serde::from_slice(bytes).map_err(|_| PolineErrors::InvalidOption)?;
+        options_to_poline(options.migrate()?)
+    }
+}
+
+fn options_to_poline(options: PolineOptions) -> Result<Poline, PolineErrors> {
+    match &options.anchor_colors {
+        Some(colors) if colors.len() >= 2 => Poline::try_from_options(options),
+        _ => Err(PolineErrors::MissingArgument),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Vector3;
+
+    fn poline() -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.9, 0.2), Vector3(180.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            closed_loop: true,
+            ..Default::default()
+        })
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_round_trip() {
+        let poline = poline();
+        let bytes = poline.to_postcard();
+        assert!(bytes.len() < poline.options().to_json().len() / 2);
+        assert_eq!(Poline::from_postcard(&bytes).unwrap(), poline);
+
+        let mut newer = bytes.clone();
+        newer[0] = 99;
+        assert!(matches!(
+            Poline::from_postcard(&newer),
+            Err(PolineErrors::UnsupportedVersion)
+        ));
+
+        let mut huge = poline.options();
+        huge.num_points = 5_000_000;
+        let bytes = postcard::to_allocvec(&huge).unwrap();
+        assert!(matches!(Poline::from_postcard(&bytes), Err(PolineErrors::TooManyPoints)));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_round_trip() {
+        let poline = poline();
+        assert_eq!(Poline::from_msgpack(&poline.to_msgpack()).unwrap(), poline);
+
+        let mut huge = poline.options();
+        huge.num_points = 5_000_000;
+        let bytes = rmp_serde::to_vec_named(&huge).unwrap();
+        assert!(matches!(Poline::from_msgpack(&bytes), Err(PolineErrors::TooManyPoints)));
+    }
+}
@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color_space::{delta_e, hsl_to_rgb, linear_to_srgb, rgb_to_hex, rgb_to_hsl, srgb_to_linear},
+    types::Vector3,
+};
+
+/// Adjacent swatches closer than this OKLab distance are reported as hard
+/// to tell apart under a color vision deficiency
+pub const CVD_DISTINGUISHABLE_DELTA_E: f32 = 0.05;
+
+/// WCAG 2.x relative luminance of an HSL color
+pub fn relative_luminance(color: Vector3) -> f32 {
+    let Vector3(r, g, b) = srgb_to_linear(hsl_to_rgb(color));
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+///
+/// WCAG 2.x contrast ratio between two HSL colors, from 1 to 21
+///
+/// contrast_ratio([0, 0, 0], [0, 0, 1]); // 21
+///
+pub fn contrast_ratio(a: Vector3, b: Vector3) -> f32 {
+    let la = relative_luminance(a);
+    let lb = relative_luminance(b);
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+fn apca_luminance(color: Vector3) -> f32 {
+    let Vector3(r, g, b) = hsl_to_rgb(color);
+    let y = 0.212_672_9 * r.powf(2.4) + 0.715_152_2 * g.powf(2.4) + 0.072_175 * b.powf(2.4);
+    let black_threshold = 0.022;
+    if y > black_threshold {
+        y
+    } else {
+        y + (black_threshold - y).powf(1.414)
+    }
+}
+
+///
+/// APCA (0.0.98G-4g) lightness contrast Lc of HSL `text` on `background`.
+/// Positive for dark text on light backgrounds, negative for light text on
+/// dark backgrounds, roughly within -108..106.
+///
+/// apca_contrast([0, 0, 0], [0, 0, 1]); // ≈ 106.04
+///
+pub fn apca_contrast(text: Vector3, background: Vector3) -> f32 {
+    let y_text = apca_luminance(text);
+    let y_background = apca_luminance(background);
+    if (y_background - y_text).abs() < 0.0005 {
+        return 0.0;
+    }
+
+    let low_clip = 0.1;
+    let offset = 0.027;
+    let scale = 1.14;
+    let output = if y_background > y_text {
+        let sapc = (y_background.powf(0.56) - y_text.powf(0.57)) * scale;
+        if sapc < low_clip {
+            0.0
+        } else {
+            sapc - offset
+        }
+    } else {
+        let sapc = (y_background.powf(0.65) - y_text.powf(0.62)) * scale;
+        if sapc > -low_clip {
+            0.0
+        } else {
+            sapc + offset
+        }
+    };
+    output * 100.0
+}
+
+/// The highest WCAG 2.x level a contrast ratio passes for text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum WcagLevel {
+    Fail,
+    /// 3:1, enough for large text and UI components
+    AALarge,
+    /// 4.5:1
+    AA,
+    /// 7:1
+    AAA,
+}
+
+impl WcagLevel {
+    pub fn from_ratio(ratio: f32) -> Self {
+        if ratio >= 7.0 {
+            WcagLevel::AAA
+        } else if ratio >= 4.5 {
+            WcagLevel::AA
+        } else if ratio >= 3.0 {
+            WcagLevel::AALarge
+        } else {
+            WcagLevel::Fail
+        }
+    }
+
+    /// The smallest ratio that passes the level
+    pub fn min_ratio(self) -> f32 {
+        match self {
+            WcagLevel::Fail => 1.0,
+            WcagLevel::AALarge => 3.0,
+            WcagLevel::AA => 4.5,
+            WcagLevel::AAA => 7.0,
+        }
+    }
+}
+
+/// Dichromatic color vision deficiencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorVisionDeficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl ColorVisionDeficiency {
+    pub const ALL: [ColorVisionDeficiency; 3] = [
+        ColorVisionDeficiency::Protanopia,
+        ColorVisionDeficiency::Deuteranopia,
+        ColorVisionDeficiency::Tritanopia,
+    ];
+
+    /// Machado et al. (2009) simulation matrices at full severity
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorVisionDeficiency::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            ColorVisionDeficiency::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            ColorVisionDeficiency::Tritanopia => [
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+        }
+    }
+
+    /// Simulates how an HSL color looks with this deficiency
+    pub fn simulate(self, color: Vector3) -> Vector3 {
+        let Vector3(r, g, b) = srgb_to_linear(hsl_to_rgb(color));
+        let [m0, m1, m2] = self.matrix();
+        let row = |m: [f32; 3]| (m[0] * r + m[1] * g + m[2] * b).clamp(0.0, 1.0);
+        rgb_to_hsl(linear_to_srgb(Vector3(row(m0), row(m1), row(m2))))
+    }
+}
+
+/// Contrast of one swatch against the report background
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwatchContrast {
+    pub color: Vector3,
+    pub hex: String,
+    pub contrast_ratio: f32,
+    pub wcag: WcagLevel,
+    /// APCA Lc of the swatch used as text on the background
+    pub apca_lc: f32,
+}
+
+/// How well adjacent swatches stay apart under one deficiency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CvdCheck {
+    pub deficiency: ColorVisionDeficiency,
+    pub min_adjacent_delta_e: f32,
+    /// Index pairs of adjacent swatches below `CVD_DISTINGUISHABLE_DELTA_E`
+    pub indistinguishable_pairs: Vec<(usize, usize)>,
+}
+
+/// Accessibility findings for a palette on a given background
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct A11yReport {
+    pub background: Vector3,
+    pub swatches: Vec<SwatchContrast>,
+    pub cvd: Vec<CvdCheck>,
+}
+
+impl A11yReport {
+    pub fn new(colors: &[Vector3], background: Vector3) -> Self {
+        let swatches = colors
+            .iter()
+            .map(|&color| {
+                let ratio = contrast_ratio(color, background);
+                SwatchContrast {
+                    color,
+                    hex: rgb_to_hex(hsl_to_rgb(color)),
+                    contrast_ratio: ratio,
+                    wcag: WcagLevel::from_ratio(ratio),
+                    apca_lc: apca_contrast(color, background),
+                }
+            })
+            .collect();
+
+        let cvd = ColorVisionDeficiency::ALL
+            .into_iter()
+            .map(|deficiency| {
+                let simulated: Vec<Vector3> =
+                    colors.iter().map(|&c| deficiency.simulate(c)).collect();
+                let mut min_adjacent_delta_e = f32::INFINITY;
+                let mut indistinguishable_pairs = Vec::new();
+                for (i, pair) in simulated.windows(2).enumerate() {
+                    let difference = delta_e(pair[0], pair[1]);
+                    min_adjacent_delta_e = min_adjacent_delta_e.min(difference);
+                    if difference < CVD_DISTINGUISHABLE_DELTA_E {
+                        indistinguishable_pairs.push((i, i + 1));
+                    }
+                }
+                CvdCheck {
+                    deficiency,
+                    min_adjacent_delta_e,
+                    indistinguishable_pairs,
+                }
+            })
+            .collect();
+
+        Self {
+            background,
+            swatches,
+            cvd,
+        }
+    }
+
+    /// Renders the report as a standalone HTML table
+    pub fn to_html(&self) -> String {
+        let background = rgb_to_hex(hsl_to_rgb(self.background));
+        let mut html = format!(
+            "<table class=\"poline-a11y\">\n<tr><th>Swatch</th><th>Contrast on {background}</th><th>WCAG</th><th>APCA Lc</th></tr>\n"
+        );
+        for swatch in &self.swatches {
+            html.push_str(&format!(
+                "<tr><td style=\"background:{hex};color:{background}\">{hex}</td><td>{ratio:.2}:1</td><td>{wcag:?}</td><td>{lc:.1}</td></tr>\n",
+                hex = swatch.hex,
+                ratio = swatch.contrast_ratio,
+                wcag = swatch.wcag,
+                lc = swatch.apca_lc,
+            ));
+        }
+        html.push_str("</table>\n<ul class=\"poline-cvd\">\n");
+        for check in &self.cvd {
+            html.push_str(&format!(
+                "<li>{:?}: minimum adjacent &Delta;E {:.3}, {} hard to distinguish pair(s)</li>\n",
+                check.deficiency,
+                check.min_adjacent_delta_e,
+                check.indistinguishable_pairs.len()
+            ));
+        }
+        html.push_str("</ul>\n");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wcag_contrast() {
+        let black = Vector3(0.0, 0.0, 0.0);
+        let white = Vector3(0.0, 0.0, 1.0);
+        assert!((contrast_ratio(black, white) - 21.0).abs() < 1e-3);
+        assert_eq!(WcagLevel::from_ratio(4.6), WcagLevel::AA);
+        assert_eq!(WcagLevel::from_ratio(WcagLevel::AAA.min_ratio()), WcagLevel::AAA);
+    }
+
+    #[test]
+    fn apca_contrast_of_black_and_white() {
+        let black = Vector3(0.0, 0.0, 0.0);
+        let white = Vector3(0.0, 0.0, 1.0);
+        assert!((apca_contrast(black, white) - 106.04).abs() < 0.1);
+        assert!((apca_contrast(white, black) + 107.88).abs() < 0.1);
+    }
+
+    #[test]
+    fn red_and_green_collide_for_deuteranopes() {
+        let colors = [Vector3(0.0, 1.0, 0.5), Vector3(120.0, 1.0, 0.35)];
+        let report = A11yReport::new(&colors, Vector3(0.0, 0.0, 1.0));
+        let deuteranopia = &report.cvd[1];
+        assert_eq!(deuteranopia.deficiency, ColorVisionDeficiency::Deuteranopia);
+
+        // about 0.41 apart with normal vision, about 0.02 for deuteranopes
+        let normal = delta_e(colors[0], colors[1]);
+        let simulated = delta_e(
+            ColorVisionDeficiency::Deuteranopia.simulate(colors[0]),
+            ColorVisionDeficiency::Deuteranopia.simulate(colors[1]),
+        );
+        assert!(normal > 0.3, "{normal}");
+        assert!(simulated < CVD_DISTINGUISHABLE_DELTA_E / 2.0, "{simulated}");
+        assert!((deuteranopia.min_adjacent_delta_e - simulated).abs() < 1e-6);
+        assert_eq!(deuteranopia.indistinguishable_pairs, [(0, 1)]);
+    }
+}
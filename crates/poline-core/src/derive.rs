@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{types::Vector3, Poline};
+
+/// A fixed relationship between a source palette and a palette derived from
+/// it, applied to every anchor
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Relation {
+    /// Degrees added to the hue
+    pub hue_offset: f32,
+    /// Factor the saturation is multiplied by, clamped to 0..1
+    pub saturation_scale: f32,
+    /// Lightness 0..1 is remapped onto `lightness_min..lightness_max`
+    pub lightness_min: f32,
+    pub lightness_max: f32,
+}
+
+impl Default for Relation {
+    fn default() -> Self {
+        Self {
+            hue_offset: 0.0,
+            saturation_scale: 1.0,
+            lightness_min: 0.0,
+            lightness_max: 1.0,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Relation {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(
+        hue_offset: f32,
+        saturation_scale: f32,
+        lightness_min: f32,
+        lightness_max: f32,
+    ) -> Relation {
+        Relation {
+            hue_offset,
+            saturation_scale,
+            lightness_min,
+            lightness_max,
+        }
+    }
+}
+
+impl Relation {
+    /// The related color of an HSL `color`
+    pub fn apply(&self, color: Vector3) -> Vector3 {
+        let Vector3(h, s, l) = color;
+        Vector3(
+            (h + self.hue_offset).rem_euclid(360.0),
+            (s * self.saturation_scale).clamp(0.0, 1.0),
+            (self.lightness_min + l * (self.lightness_max - self.lightness_min)).clamp(0.0, 1.0),
+        )
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    /// A palette related to this one by `relation`, e.g. an accent palette
+    /// that follows the brand palette. Derive again after the source changes,
+    /// or subscribe through a store to have it done for you.
+    pub fn derive(&self, relation: &Relation) -> Poline {
+        let mut derived = self.clone();
+        derived.map_anchor_colors(|color| relation.apply(color));
+        derived
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolineOptions;
+
+    #[test]
+    fn derived_palette_follows_the_relation() {
+        let mut source = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.8, 0.0), Vector3(300.0, 0.4, 1.0)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let relation = Relation::new(90.0, 0.5, 0.2, 0.6);
+        let derived = source.derive(&relation);
+        let Vector3(h, s, l) = derived.anchors()[0].color;
+        assert!((h - 110.0).abs() < 1e-3 && (s - 0.4).abs() < 1e-6 && (l - 0.2).abs() < 1e-6);
+        let Vector3(h, _, l) = derived.anchors()[1].color;
+        assert!((h - 30.0).abs() < 1e-3 && (l - 0.6).abs() < 1e-6);
+
+        source.shift_hue(10.0);
+        let rederived = source.derive(&relation);
+        assert!((rederived.anchors()[0].color.0 - 120.0).abs() < 1e-3);
+        assert_eq!(source.derive(&Relation::default()), source);
+    }
+}
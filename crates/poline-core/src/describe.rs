@@ -0,0 +1,111 @@
+use std::fmt::{self, Write};
+
+use crate::{color_space::hsl_to_rgb, rgb_to_hex, Poline};
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// `Poline(2 anchors, 1 segment x 6 points, Sinusoidal/Sinusoidal/Sinusoidal easing)`
+impl fmt::Display for Poline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments = self.anchor_pairs.len();
+        write!(
+            f,
+            "Poline({} anchors, {} segment{} x {} points, {:?}/{:?}/{:?} easing",
+            self.anchor_points.len(),
+            segments,
+            if segments == 1 { "" } else { "s" },
+            self.points_per_segment(),
+            self.position_function_x,
+            self.position_function_y,
+            self.position_function_z,
+        )?;
+        if self.connect_last_and_first_anchor {
+            write!(f, ", closed loop")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Poline {
+    /// A multi-line, human readable report of the palette's settings,
+    /// anchors and output colors, meant for logs and debugging
+    pub fn describe(&self) -> String {
+        let mut report = String::new();
+        let reversed: Vec<&str> = [
+            (self.reverse_x, "x"),
+            (self.reverse_y, "y"),
+            (self.reverse_z, "z"),
+        ]
+        .into_iter()
+        .filter_map(|(reverse, axis)| reverse.then_some(axis))
+        .collect();
+        let colors = self.output_points();
+
+        // Writing to a String can't fail
+        let _ = writeln!(report, "{self}");
+        let _ = writeln!(
+            report,
+            "  easing: x {:?}, y {:?}, z {:?}, reversed: {}",
+            self.position_function_x,
+            self.position_function_y,
+            self.position_function_z,
+            if reversed.is_empty() {
+                "none".to_string()
+            } else {
+                reversed.join(", ")
+            }
+        );
+        let _ = writeln!(
+            report,
+            "  model: {:?}, interpolation: {:?} ({:?} hue), inverted lightness: {}, closed loop: {}, monotonic lightness: {}, smooth seam: {}",
+            self.color_model,
+            self.interpolation,
+            self.hue_interpolation,
+            yes_no(self.inverted_lightness),
+            yes_no(self.connect_last_and_first_anchor),
+            yes_no(self.monotonic_lightness),
+            yes_no(self.smooth_seam),
+        );
+        let _ = writeln!(report, "  anchors:");
+        for (i, anchor) in self.anchor_points.iter().enumerate() {
+            let _ = writeln!(report, "    {i}: {anchor}");
+        }
+        let _ = writeln!(report, "  colors ({}):", colors.len());
+        for point in colors {
+            let _ = writeln!(
+                report,
+                "    {} {point}",
+                rgb_to_hex(hsl_to_rgb(point.color))
+            );
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{types::Vector3, Poline, PolineOptions};
+
+    #[test]
+    fn summarizes_the_palette() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.9, 0.2), Vector3(180.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            poline.to_string(),
+            "Poline(2 anchors, 1 segment x 6 points, Sinusoidal/Sinusoidal/Sinusoidal easing)"
+        );
+        let report = poline.describe();
+        assert!(report.starts_with(&poline.to_string()));
+        assert!(report.contains("    1: hsl(180, 90%, 80%)"));
+        assert!(report.contains("reversed: none"));
+    }
+}
@@ -0,0 +1,137 @@
+//! What changed between two versions of a palette, so that renderers can
+//! repaint only the anchors and segments that moved.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{color_point::ColorPoint, storage::SampledPoints, Poline};
+
+/// The anchors and samples of a palette at one point in time
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolineSnapshot {
+    anchor_ids: Vec<u32>,
+    anchors: Vec<ColorPoint>,
+    points: SampledPoints,
+}
+
+/// The result of [`Poline::diff_since_snapshot`]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolineDiff {
+    changed_anchors: Vec<usize>,
+    removed_anchor_ids: Vec<u32>,
+    changed_segments: Vec<usize>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl PolineDiff {
+    /// Current indices of anchors that were added or moved
+    pub fn changed_anchors(&self) -> Vec<usize> {
+        self.changed_anchors.clone()
+    }
+
+    /// Ids of anchors that no longer exist
+    pub fn removed_anchor_ids(&self) -> Vec<u32> {
+        self.removed_anchor_ids.clone()
+    }
+
+    /// Current indices of segments whose samples differ, including new ones
+    pub fn changed_segments(&self) -> Vec<usize> {
+        self.changed_segments.clone()
+    }
+
+    /// Also true when segments were only dropped from the end
+    pub fn is_empty(&self) -> bool {
+        self.changed_anchors.is_empty()
+            && self.removed_anchor_ids.is_empty()
+            && self.changed_segments.is_empty()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    pub fn snapshot(&self) -> PolineSnapshot {
+        PolineSnapshot {
+            anchor_ids: self.anchor_ids.clone(),
+            anchors: self.anchor_points.clone(),
+            points: self.sampled_points().clone(),
+        }
+    }
+
+    /// Compares the palette with an earlier [`Poline::snapshot`]. Anchors
+    /// are matched by id, so reordering alone reports only the segments.
+    pub fn diff_since_snapshot(&self, snapshot: &PolineSnapshot) -> PolineDiff {
+        let changed_anchors = self
+            .anchor_ids
+            .iter()
+            .zip(&self.anchor_points)
+            .enumerate()
+            .filter(|(_, (id, anchor))| {
+                let before = snapshot.anchor_ids.iter().position(|old| old == *id);
+                before.map(|index| &snapshot.anchors[index]) != Some(*anchor)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let removed_anchor_ids = snapshot
+            .anchor_ids
+            .iter()
+            .filter(|id| !self.anchor_ids.contains(id))
+            .copied()
+            .collect();
+        let points = self.sampled_points();
+        let changed_segments = (0..points.segment_count())
+            .filter(|&index| {
+                index >= snapshot.points.segment_count()
+                    || points.segment_positions(index) != snapshot.points.segment_positions(index)
+                    || points.segment_colors(index) != snapshot.points.segment_colors(index)
+            })
+            .collect();
+        PolineDiff {
+            changed_anchors,
+            removed_anchor_ids,
+            changed_segments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ColorPointCollection, Poline, PolineOptions, Vector3};
+
+    #[test]
+    fn reports_dirty_anchors_and_segments() {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.5, 0.2),
+                Vector3(120.0, 0.5, 0.5),
+                Vector3(240.0, 0.5, 0.8),
+                Vector3(300.0, 0.5, 0.4),
+            ]),
+            ..Default::default()
+        });
+        let snapshot = poline.snapshot();
+        assert!(poline.diff_since_snapshot(&snapshot).is_empty());
+
+        poline.update_anchor_point_at_index(
+            3,
+            ColorPointCollection {
+                xyz: None,
+                color: Some(Vector3(310.0, 0.5, 0.4)),
+                inverted_lightness: false,
+                model: Default::default(),
+            },
+        )
+        .unwrap();
+        let diff = poline.diff_since_snapshot(&snapshot);
+        assert_eq!(diff.changed_anchors(), [3]);
+        assert_eq!(diff.changed_segments(), [2]);
+
+        let removed = poline.anchor_id(0).unwrap();
+        poline.remove_anchor_point_at_index(0).unwrap();
+        let diff = poline.diff_since_snapshot(&snapshot);
+        assert_eq!(diff.removed_anchor_ids(), [removed]);
+        assert_eq!(diff.changed_anchors(), [2]);
+        assert_eq!(diff.changed_segments(), [0, 1]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Dragging anchors around in an editor, with optional constraints and hue
+//! snapping, regenerating only the segments next to the dragged anchor.
+
+use crate::{color_point::ColorPoint, types::Vector3, Poline, PolineErrors};
+
+/// Limits on how a dragged anchor may move. Radius and angle are measured
+/// around the center (0.5, 0.5) of the x/y disc.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DragConstraint {
+    /// Keep the anchor's distance from the center
+    pub lock_radius: bool,
+    /// Keep the anchor's angle around the center
+    pub lock_angle: bool,
+    /// Keep the anchor's z
+    pub lock_z: bool,
+    /// Round the hue of the dragged color to multiples of this many degrees
+    pub hue_snap: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DragState {
+    /// Stable id of the dragged anchor, so edits that move or remove
+    /// anchors mid-drag can't point the drag at another one
+    anchor_id: u32,
+    origin: Vector3,
+    constraint: DragConstraint,
+}
+
+fn polar(xyz: Vector3) -> (f32, f32) {
+    let (dx, dy) = (xyz.0 - 0.5, xyz.1 - 0.5);
+    ((dx * dx + dy * dy).sqrt(), dy.atan2(dx))
+}
+
+impl DragConstraint {
+    fn apply(&self, origin: Vector3, target: Vector3) -> Vector3 {
+        let (origin_radius, origin_angle) = polar(origin);
+        let (radius, angle) = polar(target);
+        let radius = if self.lock_radius {
+            origin_radius
+        } else {
+            radius
+        };
+        let angle = if self.lock_angle { origin_angle } else { angle };
+        let (sin, cos) = angle.sin_cos();
+        let z = if self.lock_z { origin.2 } else { target.2 };
+        Vector3(
+            (0.5 + radius * cos).clamp(0.0, 1.0),
+            (0.5 + radius * sin).clamp(0.0, 1.0),
+            z.clamp(0.0, 1.0),
+        )
+    }
+}
+
+impl Poline {
+    /// Starts dragging the anchor at `anchor_index` without constraints
+    pub fn begin_drag(&mut self, anchor_index: usize) -> Result<(), PolineErrors> {
+        self.begin_drag_with(anchor_index, DragConstraint::default())
+    }
+
+    /// Starts dragging the anchor at `anchor_index`, every following
+    /// `drag_to` is limited by `constraint`
+    pub fn begin_drag_with(
+        &mut self,
+        anchor_index: usize,
+        constraint: DragConstraint,
+    ) -> Result<(), PolineErrors> {
+        let (anchor, anchor_id) = self
+            .anchor_points
+            .get(anchor_index)
+            .zip(self.anchor_id(anchor_index))
+            .ok_or(PolineErrors::PointNotFound)?;
+        self.drag = Some(DragState {
+            anchor_id,
+            origin: anchor.position(),
+            constraint,
+        });
+        Ok(())
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Moves the dragged anchor towards `xyz` and returns it, or `None` when
+    /// no drag is in progress or the dragged anchor was removed, which also
+    /// ends the drag. Positions that end up where the anchor already is
+    /// don't regenerate anything.
+    pub fn drag_to(&mut self, xyz: Vector3) -> Option<ColorPoint> {
+        let DragState {
+            anchor_id,
+            origin,
+            constraint,
+        } = self.drag?;
+        let Some(index) = self.anchor_index(anchor_id) else {
+            self.drag = None;
+            return None;
+        };
+        let mut anchor = self.anchor_points[index];
+        anchor.set_position(constraint.apply(origin, xyz));
+        if let Some(step) = constraint.hue_snap.filter(|step| *step > 0.0) {
+            let Vector3(h, s, l) = anchor.color;
+            anchor.set_hsl(Vector3(((h / step).round() * step).rem_euclid(360.0), s, l));
+        }
+        self.snap_anchor(&mut anchor);
+
+        if anchor != self.anchor_points[index] {
+            self.anchor_points[index] = anchor;
+            self.update_segments_touching(index);
+        }
+        Some(anchor)
+    }
+
+    /// Finishes the drag, returning the anchor where it was left or `None`
+    /// when it was removed
+    pub fn end_drag(&mut self) -> Option<ColorPoint> {
+        let DragState { anchor_id, .. } = self.drag.take()?;
+        let index = self.anchor_index(anchor_id)?;
+        Some(self.anchor_points[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolineOptions;
+
+    fn poline() -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.9, 0.3),
+                Vector3(120.0, 0.9, 0.5),
+                Vector3(240.0, 0.9, 0.7),
+            ]),
+            inverted_lightness: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn drags_an_anchor() {
+        let mut poline = poline();
+        assert!(poline.drag_to(Vector3(0.5, 0.5, 0.5)).is_none());
+        poline.begin_drag(1).unwrap();
+        let moved = poline.drag_to(Vector3(0.9, 0.5, 0.2)).unwrap();
+        assert_eq!(moved.position(), Vector3(0.9, 0.5, 0.2));
+        assert_eq!(poline.end_drag(), Some(moved));
+        assert!(!poline.is_dragging());
+
+        let incremental = poline.sampled_points().clone();
+        poline.update_anchor_pairs();
+        assert_eq!(&incremental, poline.sampled_points());
+        assert!(poline.begin_drag(7).is_err());
+    }
+
+    #[test]
+    fn follows_the_anchor_through_edits() {
+        let mut removed = poline();
+        removed.begin_drag(2).unwrap();
+        removed.remove_anchor_point_at_index(2).unwrap();
+        assert!(removed.drag_to(Vector3(0.9, 0.5, 0.2)).is_none());
+        assert!(!removed.is_dragging());
+
+        let mut shifted = poline();
+        shifted.begin_drag(2).unwrap();
+        shifted.remove_anchor_point_at_index(0).unwrap();
+        let moved = shifted.drag_to(Vector3(0.9, 0.5, 0.2)).unwrap();
+        assert_eq!(shifted.anchors()[1], moved);
+        assert_eq!(shifted.end_drag(), Some(moved));
+    }
+
+    #[test]
+    fn constraints_and_snapping() {
+        let mut poline = poline();
+        let origin = poline.anchors()[0].position();
+        poline
+            .begin_drag_with(
+                0,
+                DragConstraint {
+                    lock_radius: true,
+                    lock_z: true,
+                    hue_snap: Some(15.0),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let moved = poline.drag_to(Vector3(0.5, 0.9, 0.9)).unwrap();
+        assert_eq!(moved.position().2, origin.2);
+        assert!((polar(moved.position()).0 - polar(origin).0).abs() < 1e-5);
+        assert!((moved.color.0 % 15.0).abs() < 1e-3, "{:?}", moved.color);
+    }
+}
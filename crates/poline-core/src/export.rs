@@ -0,0 +1,450 @@
+use serde_json::json;
+
+use crate::{
+    color_space::{hsl_to_rgb, rgb_to_hex, rgb_to_rgb8},
+    types::Vector3,
+    Poline,
+};
+
+/// Number of entries in exported colormaps, matching matplotlib's default
+pub const COLORMAP_ENTRIES: usize = 256;
+
+/// Texel formats for [`Poline::to_lut_bytes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LutFormat {
+    /// 4 bytes per texel (`Rgba8Unorm` / `gl.RGBA` + `gl.UNSIGNED_BYTE`)
+    Rgba8,
+    /// 8 bytes per texel, little endian half floats (`Rgba16Float` /
+    /// `gl.RGBA16F` + `gl.HALF_FLOAT`)
+    #[cfg(feature = "half")]
+    Rgba16Float,
+}
+
+impl LutFormat {
+    pub fn bytes_per_texel(self) -> usize {
+        match self {
+            LutFormat::Rgba8 => 4,
+            #[cfg(feature = "half")]
+            LutFormat::Rgba16Float => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SvgGradientKind {
+    /// Left to right unless transformed
+    #[default]
+    Linear,
+    /// From the center outwards
+    Radial,
+}
+
+/// Options for [`Poline::to_svg_gradient`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgGradientOptions {
+    pub id: String,
+    pub kind: SvgGradientKind,
+    /// An SVG transform list, e.g. `rotate(90)` for top to bottom
+    pub gradient_transform: Option<String>,
+}
+
+impl Default for SvgGradientOptions {
+    fn default() -> Self {
+        Self {
+            id: "poline".to_string(),
+            kind: SvgGradientKind::Linear,
+            gradient_transform: None,
+        }
+    }
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Poline {
+    fn colormap_entries(&self) -> Vec<[f32; 3]> {
+        self.colormap().clamped().discrete(COLORMAP_ENTRIES)
+    }
+
+    /// `r g b` joined by `separator` with the export precision
+    fn rgb_text(&self, [r, g, b]: [f32; 3], separator: &str) -> String {
+        let format = self.float_format(6);
+        [r, g, b].map(|c| format.format(c)).join(separator)
+    }
+
+    /// A 256 entry matplotlib `ListedColormap` definition:
+    /// `{"name": ..., "N": 256, "colors": [[r, g, b], ...]}` with channels in 0..1,
+    /// load it with `ListedColormap(data["colors"], name=data["name"])`
+    pub fn to_matplotlib_json(&self, name: &str) -> String {
+        let format = self.float_format(6);
+        let colors: Vec<[f64; 3]> = self
+            .colormap_entries()
+            .into_iter()
+            .map(|rgb| rgb.map(|c| format.round(c)))
+            .collect();
+        let mut json = json!({
+            "name": name,
+            "N": colors.len(),
+            "colors": colors,
+        });
+        if let Some(provenance) = &self.provenance {
+            json["provenance"] = json!(provenance);
+        }
+        json.to_string()
+    }
+
+    /// A 256 entry Vega color scheme, an array of hex colors ready for
+    /// `vega.scheme(name, colors)`
+    pub fn to_vega_scheme_json(&self) -> String {
+        let colors: Vec<String> = self
+            .colormap_entries()
+            .into_iter()
+            .map(|[r, g, b]| rgb_to_hex(Vector3(r, g, b)))
+            .collect();
+        json!(colors).to_string()
+    }
+
+    /// [`Poline::stops`], closed loops ending on the first color again
+    fn gradient_stops(&self) -> Vec<(f32, Vector3)> {
+        let mut stops = self.stops();
+        if self.connect_last_and_first_anchor {
+            if let Some(&(_, first)) = stops.first() {
+                stops.push((1.0, first));
+            }
+        }
+        stops
+    }
+
+    /// A CSS `linear-gradient` at `angle` degrees with a stop for every
+    /// palette color at its [`Poline::stops`] position. Closed loops end on
+    /// the first color again.
+    pub fn to_css_gradient(&self, angle: f32) -> String {
+        let format = self.float_format(2);
+        let stops: Vec<String> = self
+            .gradient_stops()
+            .into_iter()
+            .map(|(position, color)| {
+                let position = format.format(position * 100.0);
+                format!("{} {position}%", rgb_to_hex(hsl_to_rgb(color)))
+            })
+            .collect();
+        format!("linear-gradient({angle}deg, {})", stops.join(", "))
+    }
+
+    /// SVG `<defs>` with one gradient element holding a `<stop>` for every
+    /// palette color, ready to reference as `fill="url(#id)"`
+    pub fn to_svg_gradient(&self, options: &SvgGradientOptions) -> String {
+        let element = match options.kind {
+            SvgGradientKind::Linear => "linearGradient",
+            SvgGradientKind::Radial => "radialGradient",
+        };
+        // `--` may not appear inside an XML comment, so every dash that
+        // follows another is spaced out, `---` becoming `- - -`
+        let mut svg: String = self
+            .provenance_comment("")
+            .lines()
+            .map(|line| {
+                let mut escaped = String::with_capacity(line.len());
+                for char in line.chars() {
+                    if char == '-' && escaped.ends_with('-') {
+                        escaped.push(' ');
+                    }
+                    escaped.push(char);
+                }
+                format!("<!-- {escaped} -->\n")
+            })
+            .collect();
+        svg.push_str(&format!("<defs>\n  <{element} id=\"{}\"", escape_xml(&options.id)));
+        if let Some(transform) = &options.gradient_transform {
+            svg.push_str(&format!(" gradientTransform=\"{}\"", escape_xml(transform)));
+        }
+        svg.push_str(">\n");
+        let format = self.float_format(4);
+        for (position, color) in self.gradient_stops() {
+            svg.push_str(&format!(
+                "    <stop offset=\"{}\" stop-color=\"{}\"/>\n",
+                format.format(position),
+                rgb_to_hex(hsl_to_rgb(color))
+            ));
+        }
+        svg.push_str(&format!("  </{element}>\n</defs>\n"));
+        svg
+    }
+
+    /// The continuous gradient sampled into `width` opaque sRGB texels,
+    /// packed row-major for upload as a `width` x 1 texture
+    pub fn to_lut_bytes(&self, width: usize, format: LutFormat) -> Vec<u8> {
+        let texels = self.colormap().discrete(width);
+        let mut bytes = Vec::with_capacity(width * format.bytes_per_texel());
+        for [r, g, b] in texels {
+            let channels = [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0), 1.0];
+            match format {
+                LutFormat::Rgba8 => {
+                    bytes.extend(channels.map(|c| (c * 255.0).round() as u8));
+                }
+                #[cfg(feature = "half")]
+                LutFormat::Rgba16Float => {
+                    for c in channels {
+                        bytes.extend(half::f16::from_f32(c).to_le_bytes());
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    /// A 1D `.cube` LUT with `size` entries (2 to 65536) sampled along the
+    /// gradient. Each input channel is looked up on its own, so a grayscale
+    /// input is tinted with the palette, e.g. as a gradient map grade in
+    /// Resolve or Premiere.
+    pub fn to_cube_1d(&self, size: usize) -> String {
+        let size = size.clamp(2, 65536);
+        let mut cube = format!(
+            "TITLE \"poline\"\n# Generated by poline\n{}LUT_1D_SIZE {size}\n\
+             DOMAIN_MIN 0.0 0.0 0.0\nDOMAIN_MAX 1.0 1.0 1.0\n",
+            self.provenance_comment("# ")
+        );
+        for rgb in self.colormap().clamped().discrete(size) {
+            cube.push_str(&self.rgb_text(rgb, " "));
+            cube.push('\n');
+        }
+        cube
+    }
+
+    /// A row of 24-bit ANSI colored blocks, two per color, followed by
+    /// [`Poline::to_plain_preview`] on its own line for terminals and chats
+    /// that drop the escape codes
+    pub fn to_unicode_preview(&self) -> String {
+        let mut preview = String::new();
+        for rgb in self.colors_rgb() {
+            let [r, g, b] = rgb_to_rgb8(rgb);
+            preview.push_str(&format!("\x1b[38;2;{r};{g};{b}m\u{2588}\u{2588}"));
+        }
+        preview.push_str("\x1b[0m\n");
+        preview.push_str(&self.to_plain_preview());
+        preview
+    }
+
+    /// The palette as a space separated list of hex colors
+    pub fn to_plain_preview(&self) -> String {
+        let hex: Vec<String> = self.colors_rgb().into_iter().map(rgb_to_hex).collect();
+        hex.join(" ")
+    }
+
+    /// The colors baked into shader snippets, closed loops repeat the first
+    /// color at the end so the wrap around is interpolated too
+    fn shader_colors(&self) -> Vec<Vector3> {
+        let mut colors: Vec<Vector3> = self
+            .output_points()
+            .iter()
+            .map(|point| hsl_to_rgb(point.color))
+            .collect();
+        if self.connect_last_and_first_anchor {
+            if let Some(&first) = colors.first() {
+                colors.push(first);
+            }
+        }
+        colors
+    }
+
+    fn shader_parameter(&self, clamp: &str, fract: &str) -> String {
+        if self.connect_last_and_first_anchor {
+            fract.to_string()
+        } else {
+            clamp.to_string()
+        }
+    }
+
+    /// A GLSL snippet defining `vec3 poline(float t)`, which evaluates the
+    /// palette as sRGB for `t` in 0..1
+    pub fn to_glsl(&self) -> String {
+        let colors = self.shader_colors();
+        let n = colors.len();
+        let entries: Vec<String> = colors
+            .iter()
+            .map(|&Vector3(r, g, b)| format!("    vec3({})", self.rgb_text([r, g, b], ", ")))
+            .collect();
+        let t = self.shader_parameter("clamp(t, 0.0, 1.0)", "fract(t)");
+        format!(
+            "// Generated by poline, colors are sRGB
+{provenance}const int POLINE_COLOR_COUNT = {n};
+const vec3 POLINE_COLORS[{n}] = vec3[{n}](
+{entries}
+);
+
+vec3 poline(float t) {{
+    float x = {t} * float(POLINE_COLOR_COUNT - 1);
+    int i = int(floor(x));
+    int j = min(i + 1, POLINE_COLOR_COUNT - 1);
+    return mix(POLINE_COLORS[i], POLINE_COLORS[j], fract(x));
+}}
+",
+            entries = entries.join(",\n"),
+            provenance = self.provenance_comment("// ")
+        )
+    }
+
+    /// A WGSL snippet defining `fn poline(t: f32) -> vec3<f32>`, which
+    /// evaluates the palette as sRGB for `t` in 0..1
+    pub fn to_wgsl(&self) -> String {
+        let colors = self.shader_colors();
+        let n = colors.len();
+        let entries: Vec<String> = colors
+            .iter()
+            .map(|&Vector3(r, g, b)| format!("    vec3<f32>({})", self.rgb_text([r, g, b], ", ")))
+            .collect();
+        let t = self.shader_parameter("clamp(t, 0.0, 1.0)", "fract(t)");
+        format!(
+            "// Generated by poline, colors are sRGB
+{provenance}const POLINE_COLOR_COUNT: u32 = {n}u;
+var<private> poline_colors: array<vec3<f32>, {n}> = array<vec3<f32>, {n}>(
+{entries}
+);
+
+fn poline(t: f32) -> vec3<f32> {{
+    let x = {t} * f32(POLINE_COLOR_COUNT - 1u);
+    let i = u32(floor(x));
+    let j = min(i + 1u, POLINE_COLOR_COUNT - 1u);
+    return mix(poline_colors[i], poline_colors[j], vec3<f32>(fract(x)));
+}}
+",
+            entries = entries.join(",\n"),
+            provenance = self.provenance_comment("// ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LutFormat, SvgGradientKind, SvgGradientOptions};
+    use crate::{types::Vector3, Poline, PolineOptions};
+
+    #[test]
+    fn exports_256_entry_colormaps() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let matplotlib: serde_json::Value =
+            serde_json::from_str(&poline.to_matplotlib_json("poline")).unwrap();
+        assert_eq!(matplotlib["N"], 256);
+        assert_eq!(matplotlib["colors"].as_array().unwrap().len(), 256);
+
+        let vega: Vec<String> = serde_json::from_str(&poline.to_vega_scheme_json()).unwrap();
+        assert_eq!(vega.len(), 256);
+        assert_eq!(vega[0], "#000000");
+    }
+
+    #[test]
+    fn generates_shader_snippets() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 1.0, 0.5), Vector3(240.0, 1.0, 0.5)]),
+            closed_loop: true,
+            ..Default::default()
+        });
+        let n = poline.output_points().len() + 1;
+        let glsl = poline.to_glsl();
+        assert!(glsl.contains(&format!("const vec3 POLINE_COLORS[{n}]")));
+        assert!(glsl.contains("fract(t) * float"));
+        let wgsl = poline.to_wgsl();
+        assert!(wgsl.contains(&format!("array<vec3<f32>, {n}>")));
+        assert_eq!(wgsl.matches("vec3<f32>(").count(), n + 1);
+    }
+
+    #[test]
+    fn css_gradient_places_stops() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            num_points: 1,
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let css = poline.to_css_gradient(90.0);
+        assert!(css.starts_with("linear-gradient(90deg, #000000 0.00%, "));
+        // The default sinusoidal easing pulls the middle color towards the start
+        assert!(css.contains(" 29.29%, "), "{css}");
+        assert!(css.ends_with("#ffffff 100.00%)"));
+    }
+
+    #[test]
+    fn svg_gradient_defs() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            num_points: 1,
+            inverted_lightness: true,
+            closed_loop: true,
+            ..Default::default()
+        });
+        let svg = poline.to_svg_gradient(&SvgGradientOptions {
+            id: "a\"b".to_string(),
+            kind: SvgGradientKind::Radial,
+            gradient_transform: Some("rotate(90)".to_string()),
+        });
+        assert!(svg.starts_with(
+            "<defs>\n  <radialGradient id=\"a&quot;b\" gradientTransform=\"rotate(90)\">\n"
+        ));
+        assert!(svg.contains("    <stop offset=\"0.0000\" stop-color=\"#000000\"/>\n"));
+        assert!(svg.contains("    <stop offset=\"1.0000\" stop-color=\"#000000\"/>\n"));
+        assert_eq!(svg.matches("<stop ").count(), 5);
+        assert!(svg.ends_with("</radialGradient>\n</defs>\n"));
+    }
+
+    #[test]
+    fn cube_1d_lut() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let cube = poline.to_cube_1d(16);
+        let mut lines = cube.lines();
+        assert_eq!(lines.next(), Some("TITLE \"poline\""));
+        assert!(cube.contains("\nLUT_1D_SIZE 16\n"));
+        let entries: Vec<&str> = cube
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+            .collect();
+        assert_eq!(entries.len(), 16);
+        assert_eq!(entries[0], "0.000000 0.000000 0.000000");
+        assert_eq!(entries[15], "1.000000 1.000000 1.000000");
+    }
+
+    #[test]
+    fn unicode_preview_has_fallback() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            num_points: 0,
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        assert_eq!(poline.to_plain_preview(), "#000000 #ffffff");
+        assert_eq!(
+            poline.to_unicode_preview(),
+            "\x1b[38;2;0;0;0m\u{2588}\u{2588}\x1b[38;2;255;255;255m\u{2588}\u{2588}\x1b[0m\n#000000 #ffffff"
+        );
+    }
+
+    #[test]
+    fn lut_bytes_have_texel_layout() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let rgba8 = poline.to_lut_bytes(16, LutFormat::Rgba8);
+        assert_eq!(rgba8.len(), 16 * 4);
+        assert_eq!(rgba8[..4], [0, 0, 0, 255]);
+
+        #[cfg(feature = "half")]
+        {
+            let half = poline.to_lut_bytes(16, LutFormat::Rgba16Float);
+            assert_eq!(half.len(), 16 * 8);
+            assert_eq!(half[..8], [0, 0, 0, 0, 0, 0, 0x00, 0x3c]);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{types::Vector3, Poline};
+
+/// Fixed precision float formatting for the text exports. Values are
+/// rounded half to even on their exact binary value, so a palette writes
+/// the same bytes on every run and platform, and `-0` is written as `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FloatFormat {
+    /// Digits after the decimal point, at most 9
+    pub precision: usize,
+}
+
+impl FloatFormat {
+    pub const MAX_PRECISION: usize = 9;
+
+    pub const fn new(precision: usize) -> Self {
+        Self { precision }
+    }
+
+    fn digits(self) -> usize {
+        self.precision.min(Self::MAX_PRECISION)
+    }
+
+    /// `value` rounded to the precision
+    pub fn round(self, value: f32) -> f64 {
+        let scale = 10_f64.powi(self.digits() as i32);
+        let rounded = (value as f64 * scale).round_ties_even() / scale;
+        if rounded == 0.0 || !rounded.is_finite() {
+            0.0
+        } else {
+            rounded
+        }
+    }
+
+    /// `value` with exactly `precision` digits after the decimal point.
+    /// Infinities and NaN are written as 0.
+    pub fn format(self, value: f32) -> String {
+        let digits = self.digits();
+        let scaled = (value as f64 * 10_f64.powi(digits as i32)).round_ties_even();
+        if scaled == 0.0 || !scaled.is_finite() {
+            return format!("{:.digits$}", 0.0);
+        }
+        // The scaled value is a whole number, so this is exact
+        let mut text = format!("{:0>width$.0}", scaled.abs(), width = digits + 1);
+        if digits > 0 {
+            text.insert(text.len() - digits, '.');
+        }
+        if scaled < 0.0 {
+            text.insert(0, '-');
+        }
+        text
+    }
+}
+
+/// `hsl(210.00, 60.00%, 50.00%)`
+pub(crate) fn css_hsl(Vector3(h, s, l): Vector3, format: FloatFormat) -> String {
+    format!(
+        "hsl({}, {}%, {}%)",
+        format.format(h),
+        format.format(s * 100.0),
+        format.format(l * 100.0)
+    )
+}
+
+impl Poline {
+    /// Digits after the decimal point in every text export, `None` keeps
+    /// each format's own precision
+    pub fn set_export_precision(&mut self, precision: Option<usize>) {
+        self.export_precision = precision;
+    }
+
+    pub fn export_precision(&self) -> Option<usize> {
+        self.export_precision
+    }
+
+    /// The export precision, or `default` when none is set
+    pub(crate) fn float_format(&self, default: usize) -> FloatFormat {
+        FloatFormat::new(self.export_precision.unwrap_or(default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_half_to_even() {
+        let two = FloatFormat::new(2);
+        assert_eq!(two.format(0.125), "0.12");
+        assert_eq!(two.format(0.375), "0.38");
+        assert_eq!(FloatFormat::new(0).format(2.5), "2");
+        assert_eq!(FloatFormat::new(0).format(3.5), "4");
+        assert_eq!(two.format(-0.001), "0.00");
+        assert_eq!(two.format(-1.5), "-1.50");
+        assert_eq!(FloatFormat::new(6).format(1.0), "1.000000");
+        assert_eq!(two.format(f32::NAN), "0.00");
+        assert_eq!(two.round(0.125), 0.12);
+    }
+
+    #[test]
+    fn exports_follow_the_precision() {
+        let mut poline = Poline::from(crate::PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            num_points: 1,
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        assert_eq!(poline.css_strings()[2], "hsl(0.00, 0.00%, 100.00%)");
+        poline.set_export_precision(Some(0));
+        assert_eq!(poline.css_strings()[0], "hsl(0, 0%, 0%)");
+        assert!(poline.to_cube_1d(2).ends_with("\n0 0 0\n1 1 1\n"));
+    }
+}
@@ -0,0 +1,62 @@
+use image::{DynamicImage, RgbaImage};
+
+use crate::{color_space::rgb_to_oklab, types::Vector3, Poline};
+
+/// Lightness levels precomputed for mapping images
+const GRADIENT_MAP_LEVELS: usize = 1024;
+
+impl Poline {
+    /// Maps every pixel's perceptual (OKLab) lightness through the palette,
+    /// like Photoshop's gradient map: black takes the first color, white the
+    /// last. Alpha is kept as is.
+    pub fn gradient_map(&self, image: &DynamicImage) -> DynamicImage {
+        let levels: Vec<[u8; 3]> = (0..GRADIENT_MAP_LEVELS)
+            .map(|i| {
+                let Vector3(r, g, b) =
+                    self.apply_to_luminance(i as f32 / (GRADIENT_MAP_LEVELS - 1) as f32);
+                [r, g, b].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect();
+
+        let mut mapped: RgbaImage = image.to_rgba8();
+        for pixel in mapped.pixels_mut() {
+            let [r, g, b, a] = pixel.0;
+            let rgb = Vector3(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+            let lightness = rgb_to_oklab(rgb).0.clamp(0.0, 1.0);
+            let level = (lightness * (GRADIENT_MAP_LEVELS - 1) as f32).round() as usize;
+            let [r, g, b] = levels[level];
+            pixel.0 = [r, g, b, a];
+        }
+        DynamicImage::ImageRgba8(mapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, GrayImage, Luma};
+
+    use crate::{types::Vector3, Poline};
+
+    #[test]
+    fn maps_black_and_white_to_the_ends() {
+        let duotone = Poline::duotone(Vector3(240.0, 0.6, 0.15), Vector3(40.0, 0.9, 0.85), 8);
+        let mut gray = GrayImage::new(2, 1);
+        gray.put_pixel(0, 0, Luma([0]));
+        gray.put_pixel(1, 0, Luma([255]));
+
+        let mapped = duotone
+            .gradient_map(&DynamicImage::ImageLuma8(gray))
+            .to_rgba8();
+        let end = |t: f32| {
+            let Vector3(r, g, b) = duotone.apply_to_luminance(t);
+            [r, g, b].map(|c| (c * 255.0).round() as i32)
+        };
+        for (x, t) in [(0, 0.0), (1, 1.0)] {
+            let pixel = mapped.get_pixel(x, 0).0;
+            for (channel, expected) in pixel.iter().zip(end(t)) {
+                assert!((*channel as i32 - expected).abs() <= 1, "{pixel:?}");
+            }
+            assert_eq!(pixel[3], 255);
+        }
+    }
+}
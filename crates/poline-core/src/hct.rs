@@ -0,0 +1,279 @@
+//! HCT (hue, chroma, tone) conversions as used by Material You.
+//!
+//! Hue and chroma come from CAM16 under the default Material viewing
+//! conditions, tone is CIELAB L*.
+
+use std::f32::consts::PI;
+
+use crate::{
+    color_space::{
+        clamp_rgb, linear_rgb_to_xyz, linear_to_srgb, srgb_to_linear, xyz_to_linear_rgb,
+    },
+    types::Vector3,
+};
+
+const WHITE_POINT: Vector3 = Vector3(95.047, 100.0, 108.883);
+
+struct ViewingConditions {
+    n: f32,
+    aw: f32,
+    nbb: f32,
+    ncb: f32,
+    c: f32,
+    nc: f32,
+    rgb_d: Vector3,
+    fl: f32,
+    z: f32,
+}
+
+impl ViewingConditions {
+    fn standard() -> Self {
+        let adapting_luminance = (200.0 / PI) * y_from_lstar(50.0) / 100.0;
+        let background_lstar = 50.0_f32;
+        let surround = 2.0_f32;
+
+        let Vector3(x, y, z) = WHITE_POINT;
+        let r_w = x * 0.401288 + y * 0.650173 + z * -0.051461;
+        let g_w = x * -0.250268 + y * 1.204414 + z * 0.045854;
+        let b_w = x * -0.002079 + y * 0.048952 + z * 0.953127;
+
+        let f = 0.8 + surround / 10.0;
+        let c = if f >= 0.9 {
+            0.59 + (0.69 - 0.59) * ((f - 0.9) * 10.0)
+        } else {
+            0.525 + (0.59 - 0.525) * ((f - 0.8) * 10.0)
+        };
+        let d =
+            (f * (1.0 - (1.0 / 3.6) * ((-adapting_luminance - 42.0) / 92.0).exp())).clamp(0.0, 1.0);
+        let rgb_d = Vector3(
+            d * (100.0 / r_w) + 1.0 - d,
+            d * (100.0 / g_w) + 1.0 - d,
+            d * (100.0 / b_w) + 1.0 - d,
+        );
+        let k = 1.0 / (5.0 * adapting_luminance + 1.0);
+        let k4 = k.powi(4);
+        let k4f = 1.0 - k4;
+        let fl = k4 * adapting_luminance + 0.1 * k4f * k4f * (5.0 * adapting_luminance).cbrt();
+        let n = y_from_lstar(background_lstar) / WHITE_POINT.1;
+        let z = 1.48 + n.sqrt();
+        let nbb = 0.725 / n.powf(0.2);
+
+        let adapt = |channel: f32, w: f32| {
+            let factor = (fl * channel * w / 100.0).powf(0.42);
+            400.0 * factor / (factor + 27.13)
+        };
+        let r_a = adapt(rgb_d.0, r_w);
+        let g_a = adapt(rgb_d.1, g_w);
+        let b_a = adapt(rgb_d.2, b_w);
+        let aw = (2.0 * r_a + g_a + 0.05 * b_a) * nbb;
+
+        Self {
+            n,
+            aw,
+            nbb,
+            ncb: nbb,
+            c,
+            nc: f,
+            rgb_d,
+            fl,
+            z,
+        }
+    }
+}
+
+fn lstar_from_y(y: f32) -> f32 {
+    let y = y / 100.0;
+    let e = 216.0 / 24389.0;
+    let kappa = 24389.0 / 27.0;
+    if y <= e {
+        kappa * y
+    } else {
+        116.0 * y.cbrt() - 16.0
+    }
+}
+
+fn y_from_lstar(lstar: f32) -> f32 {
+    let e = 216.0 / 24389.0;
+    let kappa = 24389.0 / 27.0;
+    let ft = (lstar + 16.0) / 116.0;
+    let ft3 = ft * ft * ft;
+    if ft3 > e {
+        100.0 * ft3
+    } else {
+        100.0 * lstar / kappa
+    }
+}
+
+/// Returns CAM16 (J, chroma, hue) for an XYZ color
+fn xyz_to_cam16(xyz: Vector3, vc: &ViewingConditions) -> Vector3 {
+    let Vector3(x, y, z) = xyz;
+    let r_c = 0.401288 * x + 0.650173 * y - 0.051461 * z;
+    let g_c = -0.250268 * x + 1.204414 * y + 0.045854 * z;
+    let b_c = -0.002079 * x + 0.048952 * y + 0.953127 * z;
+
+    let adapt = |c: f32| {
+        let af = (vc.fl * c.abs() / 100.0).powf(0.42);
+        c.signum() * 400.0 * af / (af + 27.13)
+    };
+    let r_a = adapt(vc.rgb_d.0 * r_c);
+    let g_a = adapt(vc.rgb_d.1 * g_c);
+    let b_a = adapt(vc.rgb_d.2 * b_c);
+
+    let a = (11.0 * r_a - 12.0 * g_a + b_a) / 11.0;
+    let b = (r_a + g_a - 2.0 * b_a) / 9.0;
+    let u = (20.0 * r_a + 20.0 * g_a + 21.0 * b_a) / 20.0;
+    let p2 = (40.0 * r_a + 20.0 * g_a + b_a) / 20.0;
+
+    let hue = b.atan2(a).to_degrees().rem_euclid(360.0);
+    let ac = p2 * vc.nbb;
+    let j = 100.0 * (ac / vc.aw).powf(vc.c * vc.z);
+
+    let hue_prime = if hue < 20.14 { hue + 360.0 } else { hue };
+    let e_hue = 0.25 * ((hue_prime.to_radians() + 2.0).cos() + 3.8);
+    let p1 = 50000.0 / 13.0 * e_hue * vc.nc * vc.ncb;
+    let t = p1 * a.hypot(b) / (u + 0.305);
+    let alpha = t.powf(0.9) * (1.64 - 0.29_f32.powf(vc.n)).powf(0.73);
+    let chroma = alpha * (j / 100.0).sqrt();
+
+    Vector3(j, chroma, hue)
+}
+
+/// Returns XYZ for CAM16 (J, chroma, hue)
+fn cam16_to_xyz(jch: Vector3, vc: &ViewingConditions) -> Vector3 {
+    let Vector3(j, chroma, hue) = jch;
+    let alpha = if chroma == 0.0 || j == 0.0 {
+        0.0
+    } else {
+        chroma / (j / 100.0).sqrt()
+    };
+    let t = (alpha / (1.64 - 0.29_f32.powf(vc.n)).powf(0.73)).powf(1.0 / 0.9);
+    let h_rad = hue.to_radians();
+    let e_hue = 0.25 * ((h_rad + 2.0).cos() + 3.8);
+    let ac = vc.aw * (j / 100.0).powf(1.0 / vc.c / vc.z);
+    let p1 = e_hue * (50000.0 / 13.0) * vc.nc * vc.ncb;
+    let p2 = ac / vc.nbb;
+    let (h_sin, h_cos) = h_rad.sin_cos();
+
+    let gamma = 23.0 * (p2 + 0.305) * t / (23.0 * p1 + 11.0 * t * h_cos + 108.0 * t * h_sin);
+    let a = gamma * h_cos;
+    let b = gamma * h_sin;
+    let r_a = (460.0 * p2 + 451.0 * a + 288.0 * b) / 1403.0;
+    let g_a = (460.0 * p2 - 891.0 * a - 261.0 * b) / 1403.0;
+    let b_a = (460.0 * p2 - 220.0 * a - 6300.0 * b) / 1403.0;
+
+    let unadapt = |c: f32| {
+        let base = (27.13 * c.abs() / (400.0 - c.abs())).max(0.0);
+        c.signum() * (100.0 / vc.fl) * base.powf(1.0 / 0.42)
+    };
+    let r_f = unadapt(r_a) / vc.rgb_d.0;
+    let g_f = unadapt(g_a) / vc.rgb_d.1;
+    let b_f = unadapt(b_a) / vc.rgb_d.2;
+
+    Vector3(
+        1.862_067_9 * r_f - 1.011_254_6 * g_f + 0.149_186_77 * b_f,
+        0.387_526_54 * r_f + 0.621_447_4 * g_f - 0.008_973_985 * b_f,
+        -0.015_841_5 * r_f - 0.034_122_94 * g_f + 1.049_964_4 * b_f,
+    )
+}
+
+/// Finds the linear RGB color with the given CAM16 hue and chroma whose Y
+/// matches `target_y`, or `None` when it falls outside of the sRGB gamut.
+fn solve_linear_rgb(
+    hue: f32,
+    chroma: f32,
+    target_y: f32,
+    vc: &ViewingConditions,
+) -> Option<Vector3> {
+    let (mut low, mut high) = (0.0_f32, 100.0_f32);
+    let mut xyz = Vector3(0.0, 0.0, 0.0);
+    for _ in 0..32 {
+        let j = (low + high) / 2.0;
+        xyz = cam16_to_xyz(Vector3(j, chroma, hue), vc);
+        if xyz.1 < target_y {
+            low = j;
+        } else {
+            high = j;
+        }
+    }
+    let rgb = xyz_to_linear_rgb(Vector3(xyz.0 / 100.0, xyz.1 / 100.0, xyz.2 / 100.0));
+    let in_gamut = |c: f32| (-1e-3..=1.0 + 1e-3).contains(&c);
+    (in_gamut(rgb.0) && in_gamut(rgb.1) && in_gamut(rgb.2)).then_some(rgb)
+}
+
+///
+/// Converts an sRGB color with channels in 0..1 to HCT (hue in degrees,
+/// CAM16 chroma, tone in 0..100)
+///
+pub fn rgb_to_hct(rgb: Vector3) -> Vector3 {
+    let vc = ViewingConditions::standard();
+    let Vector3(x, y, z) = linear_rgb_to_xyz(srgb_to_linear(rgb));
+    let xyz = Vector3(x * 100.0, y * 100.0, z * 100.0);
+    let Vector3(_, chroma, hue) = xyz_to_cam16(xyz, &vc);
+    Vector3(hue, chroma, lstar_from_y(xyz.1))
+}
+
+///
+/// Converts an HCT color to sRGB. When the requested chroma cannot be shown
+/// at that hue and tone, the most colorful displayable chroma is used.
+///
+pub fn hct_to_rgb(hct: Vector3) -> Vector3 {
+    let Vector3(hue, chroma, tone) = hct;
+    if tone <= 0.0 {
+        return Vector3(0.0, 0.0, 0.0);
+    }
+    if tone >= 100.0 {
+        return Vector3(1.0, 1.0, 1.0);
+    }
+
+    let vc = ViewingConditions::standard();
+    let target_y = y_from_lstar(tone);
+    let gray = |y: f32| clamp_rgb(linear_to_srgb(Vector3(y / 100.0, y / 100.0, y / 100.0)));
+    if chroma < 1e-4 {
+        return gray(target_y);
+    }
+
+    if let Some(rgb) = solve_linear_rgb(hue, chroma, target_y, &vc) {
+        return clamp_rgb(linear_to_srgb(rgb));
+    }
+
+    let (mut low, mut high) = (0.0_f32, chroma);
+    let mut best = None;
+    for _ in 0..20 {
+        let c = (low + high) / 2.0;
+        match solve_linear_rgb(hue, c, target_y, &vc) {
+            Some(rgb) => {
+                best = Some(rgb);
+                low = c;
+            }
+            None => high = c,
+        }
+    }
+    best.map(|rgb| clamp_rgb(linear_to_srgb(rgb)))
+        .unwrap_or_else(|| gray(target_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hct_of_known_colors() {
+        let Vector3(hue, chroma, tone) = rgb_to_hct(Vector3(1.0, 0.0, 0.0));
+        assert!((hue - 27.41).abs() < 0.1, "{hue}");
+        assert!((chroma - 113.36).abs() < 0.5, "{chroma}");
+        assert!((tone - 53.24).abs() < 0.1, "{tone}");
+
+        let Vector3(_, chroma, tone) = rgb_to_hct(Vector3(1.0, 1.0, 1.0));
+        assert!(chroma < 3.0);
+        assert!((tone - 100.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn hct_round_trip() {
+        let rgb = Vector3(0.2, 0.4, 0.7);
+        let back = hct_to_rgb(rgb_to_hct(rgb));
+        assert!((back.0 - rgb.0).abs() < 1e-2, "{back:?}");
+        assert!((back.1 - rgb.1).abs() < 1e-2, "{back:?}");
+        assert!((back.2 - rgb.2).abs() < 1e-2, "{back:?}");
+    }
+}
@@ -0,0 +1,91 @@
+//! Conversion of palette colors through ICC profiles for print and
+//! calibrated displays.
+
+use crate::{color_space::rgb_to_rgb8, types::Vector3, PolineErrors};
+
+/// Rendering intent used when mapping colors into a profile's gamut
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderingIntent {
+    #[default]
+    Perceptual,
+    RelativeColorimetric,
+    Saturation,
+    AbsoluteColorimetric,
+}
+
+impl From<RenderingIntent> for qcms::Intent {
+    fn from(intent: RenderingIntent) -> Self {
+        match intent {
+            RenderingIntent::Perceptual => qcms::Intent::Perceptual,
+            RenderingIntent::RelativeColorimetric => qcms::Intent::RelativeColorimetric,
+            RenderingIntent::Saturation => qcms::Intent::Saturation,
+            RenderingIntent::AbsoluteColorimetric => qcms::Intent::AbsoluteColorimetric,
+        }
+    }
+}
+
+/// An RGB output ICC profile
+pub struct Profile {
+    inner: Box<qcms::Profile>,
+    intent: RenderingIntent,
+}
+
+impl Profile {
+    /// Parses the bytes of an `.icc`/`.icm` file
+    pub fn from_icc_bytes(bytes: &[u8]) -> Result<Self, PolineErrors> {
+        let mut inner =
+            qcms::Profile::new_from_slice(bytes, false).ok_or(PolineErrors::InvalidProfile)?;
+        inner.precache_output_transform();
+        Ok(Self {
+            inner,
+            intent: RenderingIntent::default(),
+        })
+    }
+
+    /// The built-in sRGB profile, converting through it is a no-op
+    pub fn srgb() -> Self {
+        let mut inner = qcms::Profile::new_sRGB();
+        inner.precache_output_transform();
+        Self {
+            inner,
+            intent: RenderingIntent::default(),
+        }
+    }
+
+    pub fn with_intent(mut self, intent: RenderingIntent) -> Self {
+        self.intent = intent;
+        self
+    }
+
+    /// Converts sRGB bytes in place into this profile's device values
+    pub(crate) fn transform_srgb8(&self, data: &mut [u8]) -> Result<(), PolineErrors> {
+        let srgb = qcms::Profile::new_sRGB();
+        let transform =
+            qcms::Transform::new(&srgb, &self.inner, qcms::DataType::RGB8, self.intent.into())
+                .ok_or(PolineErrors::InvalidProfile)?;
+        transform.apply(data);
+        Ok(())
+    }
+}
+
+/// Converts sRGB colors with channels in 0..1 to 8-bit device values in `profile`
+pub fn rgb_in_profile(colors: &[Vector3], profile: &Profile) -> Result<Vec<[u8; 3]>, PolineErrors> {
+    let mut data: Vec<u8> = colors.iter().flat_map(|&rgb| rgb_to_rgb8(rgb)).collect();
+    profile.transform_srgb8(&mut data)?;
+    Ok(data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_profile_is_identity() {
+        let colors = [Vector3(1.0, 0.0, 0.0), Vector3(0.2, 0.4, 0.6)];
+        let converted = rgb_in_profile(&colors, &Profile::srgb()).unwrap();
+        assert_eq!(converted[0], [255, 0, 0]);
+        for (got, want) in converted[1].iter().zip(rgb_to_rgb8(colors[1])) {
+            assert!(got.abs_diff(want) <= 1);
+        }
+    }
+}
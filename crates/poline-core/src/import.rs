@@ -0,0 +1,690 @@
+use crate::{
+    color_space::{hsl_to_oklab, oklab_to_hsl, rgb_to_hsl},
+    positions::PositionScale,
+    types::Vector3,
+    PolineErrors, PolineOptions,
+};
+
+const NAMED_COLORS: [(&str, [u8; 3]); 17] = [
+    ("black", [0, 0, 0]),
+    ("silver", [192, 192, 192]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("white", [255, 255, 255]),
+    ("maroon", [128, 0, 0]),
+    ("red", [255, 0, 0]),
+    ("purple", [128, 0, 128]),
+    ("fuchsia", [255, 0, 255]),
+    ("green", [0, 128, 0]),
+    ("lime", [0, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("yellow", [255, 255, 0]),
+    ("navy", [0, 0, 128]),
+    ("blue", [0, 0, 255]),
+    ("teal", [0, 128, 128]),
+    ("aqua", [0, 255, 255]),
+];
+
+/// An import that stopped making sense `offset` bytes into its input
+#[derive(thiserror::Error, Debug)]
+#[error("{error} at byte {offset}")]
+pub struct ParseError {
+    pub offset: usize,
+    pub error: PolineErrors,
+}
+
+impl ParseError {
+    /// `part` is a slice of `input`
+    fn at(input: &str, part: &str, error: PolineErrors) -> Self {
+        Self {
+            offset: part.as_ptr() as usize - input.as_ptr() as usize,
+            error,
+        }
+    }
+}
+
+impl From<ParseError> for PolineErrors {
+    fn from(error: ParseError) -> Self {
+        error.error
+    }
+}
+
+/// Splits `input` on separators that are not nested inside parentheses
+fn split_top_level(input: &str, is_separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0_i32;
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if is_separator(c) && depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+fn parse_hex(hex: &str) -> Option<Vector3> {
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()?;
+    let channels = match digits.len() {
+        3 | 4 => [digits[0] * 17, digits[1] * 17, digits[2] * 17],
+        6 | 8 => [
+            digits[0] * 16 + digits[1],
+            digits[2] * 16 + digits[3],
+            digits[4] * 16 + digits[5],
+        ],
+        _ => return None,
+    };
+    Some(rgb8_to_rgb(channels))
+}
+
+fn rgb8_to_rgb([r, g, b]: [u8; 3]) -> Vector3 {
+    Vector3(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+}
+
+/// A finite number with a `.` decimal point, whatever the locale
+fn parse_number(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+/// Parses `50%` as 0.5 and a bare number as a fraction of `scale`
+fn parse_component(value: &str, scale: f32) -> Option<f32> {
+    match value.trim().strip_suffix('%') {
+        Some(percent) => parse_number(percent).map(|p| p / 100.0),
+        None => parse_number(value).map(|v| v / scale),
+    }
+}
+
+/// Parses `50%` and a bare `50` alike as 0.5, as CSS reads `hsl()`
+/// saturation and lightness
+fn parse_percentage(value: &str) -> Option<f32> {
+    parse_component(value, 100.0)
+}
+
+/// Parses `50%`, `50` and `0.5` alike as 0.5: bare numbers up to 1 are
+/// fractions, larger ones percentages
+fn parse_fraction(value: &str) -> Option<f32> {
+    match value.trim().strip_suffix('%') {
+        Some(percent) => parse_number(percent).map(|p| p / 100.0),
+        None => parse_number(value).map(|v| if v <= 1.0 { v } else { v / 100.0 }),
+    }
+}
+
+fn parse_hue(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let (number, factor) = if let Some(v) = value.strip_suffix("deg") {
+        (v, 1.0)
+    } else if let Some(v) = value.strip_suffix("grad") {
+        (v, 0.9)
+    } else if let Some(v) = value.strip_suffix("rad") {
+        (v, 180.0 / std::f32::consts::PI)
+    } else if let Some(v) = value.strip_suffix("turn") {
+        (v, 360.0)
+    } else {
+        (value, 1.0)
+    };
+    parse_number(number).map(|h| (h * factor).rem_euclid(360.0))
+}
+
+/// Splits the arguments of a color function, accepting both the legacy
+/// comma separated and the modern space separated (`/ alpha`) syntax
+fn function_arguments(arguments: &str) -> Vec<&str> {
+    let without_alpha = arguments.split('/').next().unwrap_or_default();
+    if without_alpha.contains(',') {
+        without_alpha.split(',').map(str::trim).collect()
+    } else {
+        without_alpha.split_whitespace().collect()
+    }
+}
+
+///
+/// Parses a CSS color (`#rgb`, `#rrggbb`, `rgb()`, `hsl()` or a basic named
+/// color) into an HSL color. Alpha is ignored, hues are brought into 0..360
+/// and `hsl()` takes saturation and lightness as `50%` or `50`, the bare
+/// number being a percentage as in CSS.
+///
+/// ```
+/// # use poline_core::{parse_css_color, PolineErrors, Vector3};
+/// assert_eq!(parse_css_color("#ff0000")?, Vector3(0.0, 1.0, 0.5));
+/// assert_eq!(parse_css_color("hsl(200 1 1)")?, Vector3(200.0, 0.01, 0.01));
+/// # Ok::<_, PolineErrors>(())
+/// ```
+pub fn parse_css_color(input: &str) -> Result<Vector3, PolineErrors> {
+    Ok(parse_css_color_detailed(input)?)
+}
+
+/// [`parse_css_color`], with the byte offset of the part that failed
+pub fn parse_css_color_detailed(input: &str) -> Result<Vector3, ParseError> {
+    // ASCII lowercasing keeps every byte offset
+    let lower = input.to_ascii_lowercase();
+    color_in(&lower, &lower, false)
+}
+
+/// [`parse_css_color`] for colors written by older tools, which take bare
+/// `hsl()` saturations and lightnesses up to 1 as fractions: `hsl(200 0.5
+/// 1)` is read as `hsl(200 50% 100%)`, while `hsl(200 50 25)` stays in
+/// percent. This is not valid CSS, prefer [`parse_css_color`].
+pub fn parse_legacy_css_color(input: &str) -> Result<Vector3, PolineErrors> {
+    let lower = input.to_ascii_lowercase();
+    Ok(color_in(&lower, &lower, true)?)
+}
+
+/// Parses `color`, a slice of `input`, with errors located in `input`.
+/// `legacy_fractions` reads bare `hsl()` numbers up to 1 as fractions.
+fn color_in(input: &str, color: &str, legacy_fractions: bool) -> Result<Vector3, ParseError> {
+    let invalid = |part: &str| ParseError::at(input, part, PolineErrors::InvalidColor);
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        return parse_hex(hex.trim())
+            .map(rgb_to_hsl)
+            .ok_or_else(|| invalid(hex));
+    }
+    if let Some((_, rgb)) = NAMED_COLORS.iter().find(|(name, _)| *name == color) {
+        return Ok(rgb_to_hsl(rgb8_to_rgb(*rgb)));
+    }
+
+    let (name, arguments) = color
+        .strip_suffix(')')
+        .and_then(|body| body.split_once('('))
+        .ok_or_else(|| invalid(color))?;
+    let name = name.trim();
+    let values = function_arguments(arguments);
+    if values.len() < 3 {
+        return Err(invalid(arguments));
+    }
+    match name {
+        "rgb" | "rgba" => {
+            let channel = |v: &str| {
+                parse_component(v, 255.0)
+                    .map(|c| c.clamp(0.0, 1.0))
+                    .ok_or_else(|| invalid(v))
+            };
+            let rgb = Vector3(channel(values[0])?, channel(values[1])?, channel(values[2])?);
+            Ok(rgb_to_hsl(rgb))
+        }
+        "hsl" | "hsla" => {
+            let fraction = |v: &str| {
+                let parse = if legacy_fractions { parse_fraction } else { parse_percentage };
+                parse(v)
+                    .map(|c| c.clamp(0.0, 1.0))
+                    .ok_or_else(|| invalid(v))
+            };
+            let hue = parse_hue(values[0]).ok_or_else(|| invalid(values[0]))?;
+            Ok(Vector3(hue, fraction(values[1])?, fraction(values[2])?))
+        }
+        _ => Err(invalid(name)),
+    }
+}
+
+/// Parses one `color [position] [position]` gradient stop, a slice of
+/// `input`. Positions are read like [`parse_fraction`].
+fn parse_stop(input: &str, stop: &str) -> Result<(Vector3, Vec<f32>), ParseError> {
+    let mut tokens = split_top_level(stop, char::is_whitespace)
+        .into_iter()
+        .filter(|token| !token.is_empty());
+    let color = tokens
+        .next()
+        .ok_or_else(|| ParseError::at(input, stop, PolineErrors::InvalidGradient))?;
+    let color = color_in(input, color, false)?;
+    let positions = tokens
+        .map(|token| {
+            parse_fraction(token)
+                .ok_or_else(|| ParseError::at(input, token, PolineErrors::InvalidGradient))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((color, positions))
+}
+
+/// Words a gradient's first argument can start with when it is the
+/// direction or shape rather than a color stop
+const GRADIENT_LINE_KEYWORDS: [&str; 9] = [
+    "to",
+    "from",
+    "at",
+    "circle",
+    "ellipse",
+    "closest-side",
+    "closest-corner",
+    "farthest-side",
+    "farthest-corner",
+];
+
+/// Whether a gradient's first argument is its direction, angle, shape or
+/// size. No color starts with a number, so lengths and angles count too.
+fn is_gradient_line(argument: &str) -> bool {
+    let first = argument.split_whitespace().next().unwrap_or("");
+    first.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '.' | '-' | '+'))
+        || GRADIENT_LINE_KEYWORDS.contains(&first)
+}
+
+/// Fills in missing stop positions the way CSS does: the first stop defaults
+/// to 0, the last to 1 and the ones in between are spread evenly
+fn resolve_positions(positions: &mut [Option<f32>]) {
+    let last = positions.len() - 1;
+    positions[0].get_or_insert(0.0);
+    positions[last].get_or_insert(1.0);
+    let mut previous = 0;
+    for i in 1..=last {
+        if let Some(position) = positions[i] {
+            let start = positions[previous].unwrap_or(0.0);
+            let position = position.max(start);
+            positions[i] = Some(position);
+            let gap = i - previous;
+            for (k, missing) in positions[previous + 1..i].iter_mut().enumerate() {
+                *missing = Some(start + (position - start) * (k + 1) as f32 / gap as f32);
+            }
+            previous = i;
+        }
+    }
+}
+
+/// Picks the position function whose curve best matches where the stops
+/// sit relative to evenly spaced anchors
+fn closest_position_function(positions: &[f32]) -> PositionScale {
+    let last = (positions.len() - 1) as f32;
+    let error = |scale: PositionScale| -> f32 {
+        positions
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (scale.position(i as f32 / last, false) - p).powi(2))
+            .sum()
+    };
+    PositionScale::ALL
+        .iter()
+        .fold((PositionScale::Linear, f32::INFINITY), |best, &scale| {
+            let e = error(scale);
+            if e + 1e-6 < best.1 {
+                (scale, e)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+impl PolineOptions {
+    ///
+    /// Builds options from a CSS `linear-gradient`, `radial-gradient` or
+    /// `conic-gradient`, using the color stops as anchors and approximating
+    /// the position function from where the stops are placed
+    ///
+    /// ```
+    /// # use poline_core::{PolineErrors, PolineOptions};
+    /// let options =
+    ///     PolineOptions::from_css_gradient("linear-gradient(90deg, #f00 0%, #00f 100%)")?;
+    /// assert_eq!(options.anchor_colors.map(|anchors| anchors.len()), Some(2));
+    /// # Ok::<_, PolineErrors>(())
+    /// ```
+    pub fn from_css_gradient(gradient: &str) -> Result<PolineOptions, PolineErrors> {
+        Ok(Self::from_css_gradient_detailed(gradient)?)
+    }
+
+    /// [`PolineOptions::from_css_gradient`], with the byte offset of the
+    /// part that failed
+    pub fn from_css_gradient_detailed(gradient: &str) -> Result<PolineOptions, ParseError> {
+        let lower = gradient.to_ascii_lowercase();
+        let trimmed = lower.trim();
+        let body = trimmed
+            .strip_suffix(')')
+            .and_then(|g| g.split_once('('))
+            .filter(|(name, _)| name.trim().ends_with("-gradient"))
+            .map(|(_, body)| body)
+            .ok_or_else(|| ParseError::at(&lower, trimmed, PolineErrors::InvalidGradient))?;
+
+        let mut colors = Vec::new();
+        let mut positions = Vec::new();
+        for (i, argument) in split_top_level(body, |c| c == ',').into_iter().enumerate() {
+            match parse_stop(&lower, argument) {
+                Ok((color, stop_positions)) => {
+                    if stop_positions.is_empty() {
+                        colors.push(color);
+                        positions.push(None);
+                    }
+                    for position in stop_positions {
+                        colors.push(color);
+                        positions.push(Some(position));
+                    }
+                }
+                Err(_) if i == 0 && is_gradient_line(argument) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if colors.len() < 2 {
+            return Err(ParseError::at(&lower, body, PolineErrors::MissingArgument));
+        }
+        resolve_positions(&mut positions);
+        let positions: Vec<f32> = positions.into_iter().flatten().collect();
+
+        Ok(PolineOptions {
+            anchor_colors: Some(colors),
+            position_function: closest_position_function(&positions),
+            ..Default::default()
+        })
+    }
+}
+
+///
+/// Reads the colors of a GIMP `.gpl` palette as HSL anchor colors
+///
+pub fn read_gpl(contents: &str) -> Result<Vec<Vector3>, PolineErrors> {
+    let mut lines = contents.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err(PolineErrors::InvalidPalette);
+    }
+    lines
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with('#')
+                && !line.starts_with("Name:")
+                && !line.starts_with("Columns:")
+        })
+        .map(|line| {
+            let channels: Vec<u8> = line
+                .split_whitespace()
+                .take(3)
+                .map(|c| c.parse::<u8>().map_err(|_| PolineErrors::InvalidPalette))
+                .collect::<Result<_, _>>()?;
+            match channels[..] {
+                [r, g, b] => Ok(rgb_to_hsl(rgb8_to_rgb([r, g, b]))),
+                _ => Err(PolineErrors::InvalidPalette),
+            }
+        })
+        .collect()
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PolineErrors> {
+        let slice = self
+            .bytes
+            .get(self.offset..self.offset.saturating_add(len))
+            .ok_or(PolineErrors::InvalidPalette)?;
+        self.offset += len;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, PolineErrors> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, PolineErrors> {
+        self.take(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, PolineErrors> {
+        self.u32().map(f32::from_bits)
+    }
+}
+
+///
+/// Reads the RGB, CMYK and grayscale swatches of an Adobe `.ase` swatch
+/// exchange file as HSL anchor colors. LAB swatches are not supported.
+///
+pub fn read_ase(bytes: &[u8]) -> Result<Vec<Vector3>, PolineErrors> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.take(4)? != b"ASEF" {
+        return Err(PolineErrors::InvalidPalette);
+    }
+    // Major and minor version
+    reader.take(4)?;
+    let block_count = reader.u32()?;
+
+    let mut colors = Vec::new();
+    for _ in 0..block_count {
+        let block_type = reader.u16()?;
+        let length = reader.u32()? as usize;
+        let block_end = reader
+            .offset
+            .checked_add(length)
+            .filter(|&end| end <= bytes.len())
+            .ok_or(PolineErrors::InvalidPalette)?;
+        if block_type == 0x0001 {
+            let name_length = reader.u16()? as usize;
+            reader.take(name_length * 2)?;
+            let rgb = match reader.take(4)? {
+                b"RGB " => Vector3(reader.f32()?, reader.f32()?, reader.f32()?),
+                b"CMYK" => {
+                    let (c, m, y, k) = (reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?);
+                    Vector3(
+                        (1.0 - c) * (1.0 - k),
+                        (1.0 - m) * (1.0 - k),
+                        (1.0 - y) * (1.0 - k),
+                    )
+                }
+                b"Gray" => {
+                    let gray = reader.f32()?;
+                    Vector3(gray, gray, gray)
+                }
+                _ => return Err(PolineErrors::InvalidPalette),
+            };
+            colors.push(rgb_to_hsl(rgb));
+        }
+        reader.offset = block_end;
+    }
+    Ok(colors)
+}
+
+///
+/// Reads a JSON array of CSS color strings as HSL anchor colors
+///
+/// ```
+/// # use poline_core::{read_json_hex, PolineErrors};
+/// let anchors = read_json_hex(r##"["#ff0000", "#0000ff"]"##)?;
+/// assert_eq!(anchors.len(), 2);
+/// # Ok::<_, PolineErrors>(())
+/// ```
+pub fn read_json_hex(contents: &str) -> Result<Vec<Vector3>, PolineErrors> {
+    let colors: Vec<String> =
+        serde_json::from_str(contents).map_err(|_| PolineErrors::InvalidPalette)?;
+    colors.iter().map(|color| parse_css_color(color)).collect()
+}
+
+///
+/// Reduces `colors` to at most `k` representative colors with k-means in
+/// OKLab. Clusters keep the order in which their colors first appear.
+///
+pub fn reduce_anchors(colors: &[Vector3], k: usize) -> Vec<Vector3> {
+    if k == 0 || colors.len() <= k {
+        return colors.to_vec();
+    }
+    let labs: Vec<Vector3> = colors.iter().map(|&c| hsl_to_oklab(c)).collect();
+    let distance =
+        |a: Vector3, b: Vector3| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2);
+
+    // Farthest point initialization keeps the result deterministic
+    let mut centers = vec![labs[0]];
+    while centers.len() < k {
+        let next = labs
+            .iter()
+            .copied()
+            .max_by(|&a, &b| {
+                let da = centers
+                    .iter()
+                    .map(|&c| distance(a, c))
+                    .fold(f32::MAX, f32::min);
+                let db = centers
+                    .iter()
+                    .map(|&c| distance(b, c))
+                    .fold(f32::MAX, f32::min);
+                da.total_cmp(&db)
+            })
+            .unwrap();
+        centers.push(next);
+    }
+
+    let mut assignments = vec![0; labs.len()];
+    for _ in 0..32 {
+        for (assignment, &lab) in assignments.iter_mut().zip(&labs) {
+            *assignment = (0..k)
+                .min_by(|&a, &b| distance(lab, centers[a]).total_cmp(&distance(lab, centers[b])))
+                .unwrap();
+        }
+        for (index, center) in centers.iter_mut().enumerate() {
+            let members: Vec<Vector3> = labs
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, &a)| a == index)
+                .map(|(&lab, _)| lab)
+                .collect();
+            if !members.is_empty() {
+                let n = members.len() as f32;
+                *center = members.iter().fold(Vector3(0.0, 0.0, 0.0), |sum, m| {
+                    Vector3(sum.0 + m.0 / n, sum.1 + m.1 / n, sum.2 + m.2 / n)
+                });
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = Vec::with_capacity(k);
+    for &assignment in &assignments {
+        if !order.contains(&assignment) {
+            order.push(assignment);
+        }
+    }
+    order
+        .into_iter()
+        .map(|i| oklab_to_hsl(centers[i]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_css_colors() {
+        assert_eq!(parse_css_color("#f00").unwrap(), Vector3(0.0, 1.0, 0.5));
+        assert_eq!(
+            parse_css_color("rgb(0 0 255)").unwrap(),
+            Vector3(240.0, 1.0, 0.5)
+        );
+        assert_eq!(
+            parse_css_color("hsl(120, 50%, 25%)").unwrap(),
+            Vector3(120.0, 0.5, 0.25)
+        );
+        assert!(parse_css_color("nope").is_err());
+    }
+
+    #[test]
+    fn normalizes_numbers_and_locates_errors() {
+        let expected = Vector3(330.0, 0.5, 0.25);
+        for input in [
+            "hsl(-30, 50%, 25%)",
+            "HSL( 690deg 50 25 )",
+            "hsl(330,50,25)",
+            "hsl(\t330 ,\n50% , 25 %)",
+        ] {
+            let Vector3(h, s, l) = parse_css_color(input).unwrap();
+            assert!((h - expected.0).abs() < 1e-3, "{input}");
+            assert_eq!((s, l), (expected.1, expected.2), "{input}");
+        }
+        assert!(parse_css_color("hsl(inf, 50%, 25%)").is_err());
+        // Bare numbers are percentages, even up to 1
+        assert_eq!(parse_css_color("hsl(200 1 1)").unwrap(), Vector3(200.0, 0.01, 0.01));
+        assert_eq!(parse_css_color("hsl(200 0.5 100)").unwrap(), Vector3(200.0, 0.005, 1.0));
+        let legacy = parse_legacy_css_color("HSL( 690deg 0.5 0.25 )").unwrap();
+        assert_eq!((legacy.1, legacy.2), (expected.1, expected.2));
+        assert_eq!(parse_legacy_css_color("hsl(200 1 1)").unwrap(), Vector3(200.0, 1.0, 1.0));
+
+        let error = parse_css_color_detailed("rgb(255, 0x, 0)").unwrap_err();
+        assert_eq!(error.offset, 9);
+        assert!(matches!(error.error, PolineErrors::InvalidColor));
+        assert_eq!(parse_css_color_detailed("  #12345").unwrap_err().offset, 3);
+
+        let options =
+            PolineOptions::from_css_gradient("linear-gradient(red\t0.25, blue 1)").unwrap();
+        assert_eq!(options.anchor_colors.map(|colors| colors.len()), Some(2));
+        let error =
+            PolineOptions::from_css_gradient_detailed("linear-gradient(red, blue 5px)").unwrap_err();
+        assert_eq!(error.offset, 26);
+        assert!(matches!(error.error, PolineErrors::InvalidGradient));
+        let error =
+            PolineOptions::from_css_gradient_detailed("linear-gradient(#gg, red)").unwrap_err();
+        assert_eq!(error.offset, 17);
+        assert!(matches!(error.error, PolineErrors::InvalidColor));
+        for line in ["to right", "-45deg", "circle at 20% 30%", "50px 20px"] {
+            let gradient = format!("radial-gradient({line}, red, blue)");
+            assert!(PolineOptions::from_css_gradient(&gradient).is_ok(), "{line}");
+        }
+    }
+
+    #[test]
+    fn builds_options_from_gradient() {
+        let options =
+            PolineOptions::from_css_gradient("linear-gradient(90deg, #f00 0%, #00f 100%)").unwrap();
+        assert_eq!(
+            options.anchor_colors.unwrap(),
+            vec![Vector3(0.0, 1.0, 0.5), Vector3(240.0, 1.0, 0.5)]
+        );
+        assert!(matches!(options.position_function, PositionScale::Linear));
+
+        let eased =
+            PolineOptions::from_css_gradient("linear-gradient(red, lime 12.5%, blue)").unwrap();
+        assert!(matches!(eased.position_function, PositionScale::Cubic));
+    }
+
+    #[test]
+    fn reads_palette_files() {
+        let gpl = "GIMP Palette\nName: Test\nColumns: 2\n#\n255   0   0\tRed\n  0   0 255 Blue\n";
+        assert_eq!(
+            read_gpl(gpl).unwrap(),
+            vec![Vector3(0.0, 1.0, 0.5), Vector3(240.0, 1.0, 0.5)]
+        );
+
+        let mut ase = b"ASEF".to_vec();
+        ase.extend([0, 1, 0, 0, 0, 0, 0, 1]);
+        let mut block = vec![0, 2, 0, b'a', 0, 0];
+        block.extend(b"RGB ");
+        for c in [0.0_f32, 1.0, 0.0] {
+            block.extend(c.to_be_bytes());
+        }
+        block.extend([0, 2]);
+        ase.extend([0, 1]);
+        ase.extend((block.len() as u32).to_be_bytes());
+        ase.extend(block);
+        assert_eq!(read_ase(&ase).unwrap(), vec![Vector3(120.0, 1.0, 0.5)]);
+
+        let mut oversized = b"ASEF".to_vec();
+        oversized.extend([0, 1, 0, 0, 0, 0, 0, 1, 0, 1]);
+        oversized.extend(u32::MAX.to_be_bytes());
+        assert!(matches!(read_ase(&oversized), Err(PolineErrors::InvalidPalette)));
+
+        assert_eq!(
+            read_json_hex(r##"["#00f"]"##).unwrap(),
+            vec![Vector3(240.0, 1.0, 0.5)]
+        );
+    }
+
+    #[test]
+    fn reduces_to_k_anchors() {
+        let colors = [
+            Vector3(0.0, 1.0, 0.5),
+            Vector3(2.0, 1.0, 0.5),
+            Vector3(240.0, 1.0, 0.5),
+            Vector3(238.0, 1.0, 0.5),
+        ];
+        let reduced = reduce_anchors(&colors, 2);
+        assert_eq!(reduced.len(), 2);
+        assert!(reduced[0].0 < 10.0 || reduced[0].0 > 350.0);
+        assert!((reduced[1].0 - 239.0).abs() < 5.0);
+    }
+}
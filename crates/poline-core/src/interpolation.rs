@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    color_point::ColorPoint,
+    types::Vector3,
+    utils::{vector_on_line, vectors_on_line},
+    Poline, PolineErrors,
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// What the position functions ease between two anchors
+pub enum InterpolationSpace {
+    /// The x, y and z position in the color model's cube, as in poline.js
+    #[default]
+    Position,
+    /// The hue, saturation and lightness of the anchors, with the x, y and
+    /// z functions easing each channel in that order. Which way hues go
+    /// around the wheel is set by [`HueInterpolation`].
+    Hsl,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Which way around the wheel hues are eased with
+/// [`InterpolationSpace::Hsl`], as CSS `hue-interpolation-method`
+pub enum HueInterpolation {
+    #[default]
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+impl HueInterpolation {
+    /// How many degrees the hue turns going from `from` to `to`, negative
+    /// when it decreases
+    pub fn hue_delta(self, from: f32, to: f32) -> f32 {
+        let delta = (to - from).rem_euclid(360.0);
+        match self {
+            HueInterpolation::Shorter if delta > 180.0 => delta - 360.0,
+            HueInterpolation::Longer if delta == 0.0 => 360.0,
+            HueInterpolation::Longer if delta < 180.0 => delta - 360.0,
+            HueInterpolation::Decreasing if delta > 0.0 => delta - 360.0,
+            _ => delta,
+        }
+    }
+}
+
+/// The anchors' colors with the end hue moved so that easing from the start
+/// one goes around the wheel the way `hue` asks
+fn unwrapped(start: Vector3, end: Vector3, hue: HueInterpolation) -> (Vector3, Vector3) {
+    let delta = hue.hue_delta(start.0, end.0);
+    (start, Vector3(start.0 + delta, end.1, end.2))
+}
+
+impl Poline {
+    fn hsl_point(&self, color: Vector3) -> ColorPoint {
+        let color = Vector3(color.0.rem_euclid(360.0), color.1, color.2);
+        let position = self
+            .color_model
+            .hsl_to_point(color, self.inverted_lightness);
+        ColorPoint::from_parts(position, color, self.inverted_lightness, self.color_model)
+    }
+
+    /// [`Poline::sample_segment`] easing the anchors' HSL channels
+    pub(crate) fn sample_segment_hsl(&self, idx: usize, count: usize) -> Vec<ColorPoint> {
+        let (p1, p2) = self.anchor_pairs[idx];
+        let (start, end) = unwrapped(p1.color, p2.color, self.segment_hue_interpolation(idx));
+        vectors_on_line(
+            start,
+            end,
+            Some(count),
+            self.segment_invert(idx),
+            Some(self.position_function_x),
+            Some(self.position_function_y),
+            Some(self.position_function_z),
+        )
+        .into_iter()
+        .map(|color| self.hsl_point(color))
+        .collect()
+    }
+
+    /// [`Poline::segment_point`] easing the anchors' HSL channels
+    pub(crate) fn segment_point_hsl(&self, idx: usize, t: f32) -> ColorPoint {
+        let (p1, p2) = self.anchor_pairs[idx];
+        let (start, end) = unwrapped(p1.color, p2.color, self.segment_hue_interpolation(idx));
+        self.hsl_point(vector_on_line(
+            t,
+            start,
+            end,
+            self.segment_invert(idx),
+            Some(self.position_function_x),
+            Some(self.position_function_y),
+            Some(self.position_function_z),
+        ))
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    pub fn interpolation(&self) -> InterpolationSpace {
+        self.interpolation
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: InterpolationSpace) {
+        self.interpolation = interpolation;
+        self.update_anchor_pairs();
+    }
+
+    pub fn hue_interpolation(&self) -> HueInterpolation {
+        self.hue_interpolation
+    }
+
+    /// Sets how hues are eased on every segment without its own override
+    pub fn set_hue_interpolation(&mut self, hue_interpolation: HueInterpolation) {
+        self.hue_interpolation = hue_interpolation;
+        self.update_anchor_pairs();
+    }
+
+    /// How hues are eased on the segment at `index`, its own override or
+    /// the palette's
+    pub fn segment_hue_interpolation(&self, index: usize) -> HueInterpolation {
+        self.anchor_ids
+            .get(index)
+            .and_then(|id| {
+                self.segment_hue_interpolations
+                    .iter()
+                    .find(|(start, _)| start == id)
+            })
+            .map_or(self.hue_interpolation, |&(_, hue)| hue)
+    }
+
+    /// Overrides how hues are eased on the segment at `index`, or goes back
+    /// to the palette's with `None`. The override stays with the segment's
+    /// first anchor as anchors are added or removed.
+    pub fn set_segment_hue_interpolation(
+        &mut self,
+        index: usize,
+        hue_interpolation: Option<HueInterpolation>,
+    ) -> Result<(), PolineErrors> {
+        if index >= self.anchor_pairs.len() {
+            return Err(PolineErrors::IndexOutOfBounds);
+        }
+        let id = self.anchor_ids[index];
+        let anchor_ids = &self.anchor_ids;
+        self.segment_hue_interpolations
+            .retain(|(start, _)| *start != id && anchor_ids.contains(start));
+        if let Some(hue) = hue_interpolation {
+            self.segment_hue_interpolations.push((id, hue));
+        }
+        self.resample_segments(&[index]);
+        Ok(())
+    }
+}
+
+impl Poline {
+    /// The segment overrides by segment index, as `PolineOptions` keeps them
+    pub(crate) fn segment_hue_interpolations_by_index(&self) -> Vec<(usize, HueInterpolation)> {
+        let mut overrides: Vec<(usize, HueInterpolation)> = self
+            .segment_hue_interpolations
+            .iter()
+            .filter_map(|&(id, hue)| Some((self.anchor_index(id)?, hue)))
+            .collect();
+        overrides.sort_unstable_by_key(|&(index, _)| index);
+        overrides
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorPointCollection, PolineOptions, PositionScale};
+
+    fn poline(interpolation: InterpolationSpace) -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(350.0, 0.2, 0.3), Vector3(30.0, 0.8, 0.7)]),
+            num_points: 3,
+            position_function: PositionScale::Linear,
+            inverted_lightness: true,
+            interpolation,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn hsl_channels_are_eased() {
+        let colors = poline(InterpolationSpace::Hsl).colors_vec();
+        assert_eq!(colors.len(), 5);
+        let expected = [
+            Vector3(350.0, 0.2, 0.3),
+            Vector3(0.0, 0.35, 0.4),
+            Vector3(10.0, 0.5, 0.5),
+            Vector3(20.0, 0.65, 0.6),
+            Vector3(30.0, 0.8, 0.7),
+        ];
+        for (color, expected) in colors.iter().zip(expected) {
+            let hue = (color.0 - expected.0 + 180.0).rem_euclid(360.0) - 180.0;
+            assert!(hue.abs() < 1e-3, "{color:?}");
+            assert!((color.1 - expected.1).abs() < 1e-4, "{color:?}");
+            assert!((color.2 - expected.2).abs() < 1e-4, "{color:?}");
+        }
+    }
+
+    #[test]
+    fn switching_spaces_resamples() {
+        let mut palette = poline(InterpolationSpace::Position);
+        let before = palette.colors_vec();
+        palette.set_interpolation(InterpolationSpace::Hsl);
+        assert_ne!(palette.colors_vec(), before);
+        assert_eq!(
+            palette.colors_vec(),
+            poline(InterpolationSpace::Hsl).colors_vec()
+        );
+        assert_eq!(
+            Poline::from(palette.options()).colors_vec(),
+            palette.colors_vec()
+        );
+    }
+
+    #[test]
+    fn hue_deltas_match_css() {
+        use HueInterpolation::*;
+        let cases = [
+            (Shorter, 350.0, 30.0, 40.0),
+            (Shorter, 30.0, 350.0, -40.0),
+            (Longer, 350.0, 30.0, -320.0),
+            (Longer, 30.0, 350.0, 320.0),
+            (Longer, 90.0, 90.0, 360.0),
+            (Increasing, 30.0, 350.0, 320.0),
+            (Increasing, 350.0, 30.0, 40.0),
+            (Decreasing, 350.0, 30.0, -320.0),
+            (Decreasing, 30.0, 350.0, -40.0),
+            (Decreasing, 90.0, 90.0, 0.0),
+        ];
+        for (hue, from, to, delta) in cases {
+            assert_eq!(hue.hue_delta(from, to), delta, "{hue:?} {from} {to}");
+        }
+    }
+
+    #[test]
+    fn segment_overrides_follow_their_anchor() {
+        let mut palette = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(350.0, 0.5, 0.5),
+                Vector3(30.0, 0.5, 0.5),
+                Vector3(90.0, 0.5, 0.5),
+            ]),
+            num_points: 1,
+            position_function: PositionScale::Linear,
+            inverted_lightness: true,
+            interpolation: InterpolationSpace::Hsl,
+            ..Default::default()
+        });
+        palette
+            .set_segment_hue_interpolation(0, Some(HueInterpolation::Longer))
+            .unwrap();
+        assert!(matches!(
+            palette.set_segment_hue_interpolation(2, None),
+            Err(PolineErrors::IndexOutOfBounds)
+        ));
+        // Halfway along the longer way from 350 to 30 is 190
+        assert!((palette.colors_vec()[1].0 - 190.0).abs() < 1e-2);
+        assert!((palette.colors_vec()[3].0 - 60.0).abs() < 1e-2);
+
+        palette
+            .add_anchor_point(
+                ColorPointCollection {
+                    xyz: None,
+                    color: Some(Vector3(200.0, 0.5, 0.5)),
+                    inverted_lightness: true,
+                    model: Default::default(),
+                },
+                Some(0),
+            )
+            .unwrap();
+        assert_eq!(
+            palette.segment_hue_interpolation(0),
+            HueInterpolation::Shorter
+        );
+        assert_eq!(
+            palette.segment_hue_interpolation(1),
+            HueInterpolation::Longer
+        );
+        let options = palette.options();
+        assert_eq!(
+            options.segment_hue_interpolations,
+            vec![(1, HueInterpolation::Longer)]
+        );
+        assert_eq!(Poline::from(options), palette);
+
+        palette.set_segment_hue_interpolation(1, None).unwrap();
+        assert!(palette.options().segment_hue_interpolations.is_empty());
+    }
+}
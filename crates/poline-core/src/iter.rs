@@ -0,0 +1,301 @@
+//! Lazy iterators over the palette, reading the sampled buffers in place.
+
+use std::{
+    iter::FusedIterator,
+    ops::{Index, Range},
+};
+
+use crate::{color_point::ColorPoint, same_position, types::Vector3, Poline};
+
+/// Walks the buffer range of the flattened points in every segment. A
+/// segment's points are contiguous in the buffers, only its first point can
+/// be a joint shared with the previous segment and only the very last point
+/// can be the repeated first anchor that ends a closed loop, so each range
+/// is found without visiting the points inside it.
+#[derive(Debug, Clone)]
+pub(crate) struct Runs<'a> {
+    positions: &'a [Vector3],
+    offsets: &'a [usize],
+    skip_joints: bool,
+    drop_closing: bool,
+    segment: usize,
+    first: Option<usize>,
+    previous: Option<usize>,
+}
+
+impl<'a> Runs<'a> {
+    pub(crate) fn new(poline: &'a Poline, drop_closing: bool) -> Self {
+        Self {
+            positions: poline.sampled_points().positions(),
+            offsets: poline.sampled_points().offsets(),
+            skip_joints: !poline.include_anchor_duplicates,
+            drop_closing,
+            segment: 0,
+            first: None,
+            previous: None,
+        }
+    }
+}
+
+impl Iterator for Runs<'_> {
+    type Item = (usize, Range<usize>);
+
+    fn next(&mut self) -> Option<(usize, Range<usize>)> {
+        loop {
+            let segment = self.segment;
+            let mut range = self.offsets[segment]..*self.offsets.get(segment + 1)?;
+            self.segment += 1;
+            if range.is_empty() {
+                continue;
+            }
+            let position = |idx: usize| self.positions[idx];
+            if self.skip_joints
+                && self
+                    .previous
+                    .is_some_and(|previous| same_position(position(previous), position(range.start)))
+            {
+                range.start += 1;
+            }
+            let last = range.end - 1;
+            if self.drop_closing && range.end == self.positions.len() {
+                let first = self.first.or((range.start < last).then_some(range.start));
+                if first.is_some_and(|first| same_position(position(first), position(last))) {
+                    range.end = last;
+                }
+            }
+            if range.is_empty() {
+                continue;
+            }
+            self.first.get_or_insert(range.start);
+            self.previous = Some(range.end - 1);
+            return Some((segment, range));
+        }
+    }
+}
+
+impl FusedIterator for Runs<'_> {}
+
+/// Walks the segment and buffer index of every flattened point, skipping
+/// the joints adjacent segments share and optionally the repeated first
+/// anchor that ends a closed loop
+#[derive(Debug, Clone)]
+pub(crate) struct FlatIndices<'a> {
+    runs: Runs<'a>,
+    segment: usize,
+    run: Range<usize>,
+}
+
+impl<'a> FlatIndices<'a> {
+    pub(crate) fn new(poline: &'a Poline, drop_closing: bool) -> Self {
+        Self {
+            runs: Runs::new(poline, drop_closing),
+            segment: 0,
+            run: 0..0,
+        }
+    }
+}
+
+impl Iterator for FlatIndices<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        loop {
+            if let Some(idx) = self.run.next() {
+                return Some((self.segment, idx));
+            }
+            (self.segment, self.run) = self.runs.next()?;
+        }
+    }
+}
+
+impl FusedIterator for FlatIndices<'_> {}
+
+/// The palette's points, see [`Poline::iter_points`]
+#[derive(Debug, Clone)]
+pub struct Points<'a> {
+    poline: &'a Poline,
+    indices: FlatIndices<'a>,
+}
+
+impl Iterator for Points<'_> {
+    type Item = ColorPoint;
+
+    fn next(&mut self) -> Option<ColorPoint> {
+        let (_, idx) = self.indices.next()?;
+        Some(ColorPoint::from_parts(
+            self.poline.sampled_points().positions()[idx],
+            self.poline.sampled_points().colors()[idx],
+            self.poline.inverted_lightness,
+            self.poline.color_model,
+        ))
+    }
+}
+
+impl FusedIterator for Points<'_> {}
+
+/// The palette's HSL colors, see [`Poline::iter_colors`]
+#[derive(Debug, Clone)]
+pub struct Colors<'a> {
+    colors: &'a [Vector3],
+    indices: FlatIndices<'a>,
+}
+
+impl Iterator for Colors<'_> {
+    type Item = Vector3;
+
+    fn next(&mut self) -> Option<Vector3> {
+        let (_, idx) = self.indices.next()?;
+        Some(self.colors[idx])
+    }
+}
+
+impl FusedIterator for Colors<'_> {}
+
+impl Poline {
+    fn output_indices(&self) -> FlatIndices<'_> {
+        let drop_closing = self.connect_last_and_first_anchor && !self.include_anchor_duplicates;
+        FlatIndices::new(self, drop_closing)
+    }
+
+    /// The palette's points in order without allocating, the same points
+    /// [`Poline::colors_vec`] takes its colors from
+    pub fn iter_points(&self) -> Points<'_> {
+        Points {
+            poline: self,
+            indices: self.output_indices(),
+        }
+    }
+
+    /// The palette's HSL colors in order without allocating
+    pub fn iter_colors(&self) -> Colors<'_> {
+        Colors {
+            colors: self.sampled_points().colors(),
+            indices: self.output_indices(),
+        }
+    }
+}
+
+impl Poline {
+    fn output_runs(&self) -> Runs<'_> {
+        let drop_closing = self.connect_last_and_first_anchor && !self.include_anchor_duplicates;
+        Runs::new(self, drop_closing)
+    }
+
+    /// Where the palette's color at `index` is in the sampled buffers,
+    /// walking segments rather than points
+    fn buffer_index(&self, index: usize) -> Option<usize> {
+        let mut index = index;
+        for (_, run) in self.output_runs() {
+            if index < run.len() {
+                return Some(run.start + index);
+            }
+            index -= run.len();
+        }
+        None
+    }
+
+    /// Number of colors in the palette
+    pub fn len(&self) -> usize {
+        self.output_runs().map(|(_, run)| run.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.output_runs().next().is_none()
+    }
+
+    /// Borrows the palette's HSL color at `index`, like indexing does
+    pub fn get(&self, index: usize) -> Option<&Vector3> {
+        self.buffer_index(index)
+            .map(|idx| &self.sampled_points().colors()[idx])
+    }
+
+    /// The palette's point at `index`. The samples are stored as flat
+    /// position and color buffers with no `ColorPoint` to borrow, so the
+    /// point is assembled on the fly and returned by value.
+    pub fn get_point(&self, index: usize) -> Option<ColorPoint> {
+        let idx = self.buffer_index(index)?;
+        Some(ColorPoint::from_parts(
+            self.sampled_points().positions()[idx],
+            self.sampled_points().colors()[idx],
+            self.inverted_lightness,
+            self.color_model,
+        ))
+    }
+}
+
+/// Borrows the HSL color at `index` of the palette, panicking past its end
+impl Index<usize> for Poline {
+    type Output = Vector3;
+
+    fn index(&self, index: usize) -> &Vector3 {
+        self.get(index)
+            .unwrap_or_else(|| panic!("index out of bounds: {index} is past the palette's end"))
+    }
+}
+
+impl<'a> IntoIterator for &'a Poline {
+    type Item = ColorPoint;
+    type IntoIter = Points<'a>;
+
+    fn into_iter(self) -> Points<'a> {
+        self.iter_points()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Poline, PolineOptions, Vector3};
+
+    #[test]
+    fn iterators_match_the_palette() {
+        for closed_loop in [false, true] {
+            for include_anchor_duplicates in [false, true] {
+                let poline = Poline::from(PolineOptions {
+                    anchor_colors: Some(vec![
+                        Vector3(0.0, 0.5, 0.2),
+                        Vector3(120.0, 0.5, 0.5),
+                        Vector3(240.0, 0.5, 0.8),
+                    ]),
+                    closed_loop,
+                    include_anchor_duplicates,
+                    ..Default::default()
+                });
+                let points: Vec<_> = poline.into_iter().collect();
+                assert_eq!(points, poline.output_points());
+                let colors: Vec<_> = poline.iter_colors().collect();
+                let expected: Vec<_> = points.iter().map(|point| point.color).collect();
+                assert_eq!(colors, expected);
+                assert_eq!(poline.len(), colors.len());
+                for (index, color) in colors.iter().enumerate() {
+                    assert_eq!(poline.get(index), Some(color));
+                    assert_eq!(poline.get_point(index).as_ref(), points.get(index));
+                }
+                assert_eq!(poline.get(colors.len()), None);
+            }
+        }
+    }
+
+    #[test]
+    fn random_access() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.5, 0.2), Vector3(120.0, 0.5, 0.5)]),
+            closed_loop: true,
+            ..Default::default()
+        });
+        let colors = poline.colors_vec();
+        assert_eq!(poline.len(), colors.len());
+        assert!(!poline.is_empty());
+        assert_eq!(poline[3], colors[3]);
+        assert_eq!(poline.get(2), Some(&colors[2]));
+        assert_eq!(poline.get_point(2).map(|point| point.color), Some(colors[2]));
+        assert_eq!(poline.get(colors.len()), None);
+        assert_eq!(poline.get_point(colors.len()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn indexing_past_the_end_panics() {
+        let poline = Poline::default();
+        let _ = poline[poline.len()];
+    }
+}
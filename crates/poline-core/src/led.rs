@@ -0,0 +1,97 @@
+use crate::{color_space::hsl_to_rgb, types::Vector3, Poline};
+
+/// Byte order of each LED's color
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LedOrder {
+    Rgb,
+    /// The order WS2812 / NeoPixel strips expect
+    #[default]
+    Grb,
+}
+
+/// How palette colors are turned into LED bytes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LedSettings {
+    /// Exponent applied to each channel to compensate for the LEDs' linear
+    /// response, 2.8 suits most WS2812 strips, `None` sends sRGB values
+    pub gamma: Option<f32>,
+    /// Channels are scaled so none goes above this fraction of full power
+    pub brightness: f32,
+    pub order: LedOrder,
+}
+
+impl Default for LedSettings {
+    fn default() -> Self {
+        Self {
+            gamma: Some(2.8),
+            brightness: 1.0,
+            order: LedOrder::Grb,
+        }
+    }
+}
+
+impl Poline {
+    /// `n` colors evenly spaced over the palette as 3 bytes per LED, ready to
+    /// be written to a strip or ring. On closed loops the last LED stops one
+    /// step short of the first so the ring wraps around smoothly.
+    pub fn to_rgb8_ring(&self, n: usize, settings: LedSettings) -> Vec<u8> {
+        let steps = if self.connect_last_and_first_anchor {
+            n
+        } else {
+            n.saturating_sub(1)
+        }
+        .max(1);
+        let brightness = settings.brightness.clamp(0.0, 1.0);
+        let to_byte = |c: f32| {
+            let c = c.clamp(0.0, 1.0);
+            let c = settings.gamma.map_or(c, |gamma| c.powf(gamma));
+            (c * brightness * 255.0).round() as u8
+        };
+
+        let mut bytes = Vec::with_capacity(n * 3);
+        for i in 0..n {
+            let Vector3(r, g, b) = hsl_to_rgb(self.curve_point(i as f32 / steps as f32).color);
+            let [r, g, b] = [r, g, b].map(to_byte);
+            match settings.order {
+                LedOrder::Rgb => bytes.extend([r, g, b]),
+                LedOrder::Grb => bytes.extend([g, r, b]),
+            }
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolineOptions;
+
+    #[test]
+    fn ring_bytes_follow_the_settings() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 1.0, 0.5), Vector3(120.0, 1.0, 0.5)]),
+            position_function: crate::PositionScale::Linear,
+            color_model: crate::ColorModel::LightnessOnZ,
+            ..Default::default()
+        });
+        let raw = LedSettings {
+            gamma: None,
+            brightness: 1.0,
+            order: LedOrder::Rgb,
+        };
+        let bytes = poline.to_rgb8_ring(4, raw);
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(bytes[..3], [255, 0, 0]);
+
+        let strip = poline.to_rgb8_ring(
+            4,
+            LedSettings {
+                brightness: 0.5,
+                ..Default::default()
+            },
+        );
+        // Green first, half power
+        assert_eq!(strip[..3], [0, 128, 0]);
+        assert_eq!(strip[9..], [128, 0, 0]);
+    }
+}
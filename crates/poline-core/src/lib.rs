@@ -1,87 +1,368 @@
-use std::ops::Index;
+use std::{ops::Index, sync::OnceLock};
 
-use color_point::ColorPoint;
 use decorum::R32;
+use drag::DragState;
 use serde::Deserialize;
 use serde::Serialize;
-use types::{PartialVector3, Vector3};
-use utils::{distance, optional_vector3, vectors_on_line};
+use utils::{distance, optional_vector3};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::wasm_bindgen;
-use wasm_bindgen::JsValue;
-
-use crate::color_point::ColorPointCollection;
 
+pub(crate) mod adaptation;
+pub(crate) mod anchor_ids;
+pub(crate) mod anchor_strategy;
+pub(crate) mod alpha;
+pub(crate) mod blend;
+pub(crate) mod builder;
+pub(crate) mod color_model;
 pub(crate) mod color_point;
+pub(crate) mod color_space;
+pub(crate) mod colormap;
+#[cfg(any(feature = "postcard", feature = "msgpack"))]
+pub(crate) mod compact;
+pub(crate) mod contrast;
+pub(crate) mod derive;
+pub(crate) mod diff;
+pub(crate) mod describe;
+pub(crate) mod drag;
+pub(crate) mod export;
+pub(crate) mod float_format;
+#[cfg(feature = "image")]
+pub(crate) mod gradient_map;
+#[cfg(feature = "hct")]
+pub(crate) mod hct;
+#[cfg(feature = "icc")]
+pub(crate) mod icc;
+pub(crate) mod import;
+pub(crate) mod interpolation;
+pub(crate) mod iter;
+pub(crate) mod led;
+pub(crate) mod lightness;
+pub(crate) mod limits;
+pub mod math;
+pub(crate) mod mix;
+pub(crate) mod modulation;
+pub(crate) mod noise;
+pub(crate) mod ops;
+pub(crate) mod ordering;
+pub(crate) mod palette_export;
+pub(crate) mod palette_set;
+pub(crate) mod parameters;
+pub(crate) mod png;
+pub(crate) mod point_count;
 pub(crate) mod positions;
+pub(crate) mod presets;
+pub(crate) mod provenance;
+pub(crate) mod registry;
+pub(crate) mod sampling;
+pub(crate) mod schema;
+pub(crate) mod share;
+pub(crate) mod shared;
+pub(crate) mod simplify;
+pub(crate) mod snapping;
+pub(crate) mod static_palette;
+pub(crate) mod stops;
+pub(crate) mod storage;
+pub(crate) mod theme;
+pub(crate) mod trace;
+pub(crate) mod transform;
 pub(crate) mod types;
 pub(crate) mod utils;
 
+pub use adaptation::{adapt_rgb, adapt_xyz, WhitePoint};
+pub use alpha::{alpha_ramp, AlphaStep};
+pub use anchor_strategy::{AnchorStrategy, HarmonyKind};
+pub use blend::{blend, BlendMode};
+pub use builder::PolineBuilder;
+pub use color_model::ColorModel;
+pub use color_point::{ColorPoint, ColorPointCollection};
+pub use color_space::{
+    delta_e, hsl_to_oklab, hsl_to_rgb, oklab_to_hsl, oklab_to_oklch, oklab_to_rgb,
+    oklch_to_oklab, pack_rgb10a2, rgb_to_hex, rgb_to_hsl, rgb_to_oklab, rgb_to_rec2020,
+    rgb_to_rgb8, rgb_to_scrgb, rgba_to_hex,
+};
+pub use colormap::{Colormap, LegendTick};
+pub use contrast::{
+    apca_contrast, contrast_ratio, relative_luminance, A11yReport, ColorVisionDeficiency, CvdCheck,
+    SwatchContrast, WcagLevel,
+};
+pub use derive::Relation;
+pub use diff::{PolineDiff, PolineSnapshot};
+pub use drag::DragConstraint;
+pub use export::{LutFormat, SvgGradientKind, SvgGradientOptions, COLORMAP_ENTRIES};
+pub use float_format::FloatFormat;
+#[cfg(feature = "hct")]
+pub use hct::{hct_to_rgb, rgb_to_hct};
+#[cfg(feature = "icc")]
+pub use icc::{rgb_in_profile, Profile, RenderingIntent};
+pub use import::{
+    parse_css_color, parse_css_color_detailed, parse_legacy_css_color, read_ase, read_gpl,
+    read_json_hex, reduce_anchors, ParseError,
+};
+pub use interpolation::{HueInterpolation, InterpolationSpace};
+pub use iter::{Colors, Points};
+pub use led::{LedOrder, LedSettings};
+pub use limits::{MAX_ANCHORS, MAX_TOTAL_POINTS};
+pub use mix::{color_distance, mix, MixSpace};
+pub use modulation::{Modulation, ModulationTarget, Smoother};
+pub use noise::NoiseConfig;
+pub use ops::PolineOp;
+pub use palette_export::{
+    LimitPolicy, LimitedExport, PixelPaletteOptions, PAINT_NET_MAX_COLORS, PROCREATE_MAX_COLORS,
+};
+pub use palette_set::{CrossContrast, PolineSet};
+pub use parameters::ParameterMap;
+pub use point_count::PointCountMode;
 pub use positions::{position_from_scale, PositionScale};
+pub use provenance::Provenance;
+pub use registry::{registry, Exporter, ExporterRegistry, FnExporter};
+pub use sampling::Segment;
+pub use schema::SCHEMA_VERSION;
+pub use shared::SharedPoline;
+pub use snapping::Snapping;
+pub use static_palette::StaticPalette;
+pub use storage::SampledPoints;
+pub use types::{PartialVector3, Vector2, Vector3};
+pub use utils::number_as_color_model;
 pub use utils::number_as_enum;
 pub use utils::random_hsl_pair;
+pub use utils::random_hsl_pair_with_rng;
 pub use utils::random_hsl_triple;
+pub use utils::random_hsl_triple_with_rng;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(thiserror::Error, Debug, Serialize, Deserialize)]
 pub enum PolineErrors {
     #[error("At least one is required")]
     MissingArgument,
     #[error("Point not found")]
     PointNotFound,
+    #[error("Invalid or unsupported ICC profile")]
+    InvalidProfile,
+    #[error("Invalid CSS color")]
+    InvalidColor,
+    #[error("Invalid CSS gradient")]
+    InvalidGradient,
+    #[error("Invalid or unsupported palette file")]
+    InvalidPalette,
+    #[error("Invalid palette options")]
+    InvalidOption,
+    #[error("Saved by a newer version of poline")]
+    UnsupportedVersion,
+    #[error("At least two anchors are required")]
+    NotEnoughAnchors,
+    #[error("HSL color out of range")]
+    ColorOutOfRange,
+    #[error("Index out of bounds")]
+    IndexOutOfBounds,
+    #[error("More anchors than the palette allows")]
+    TooManyAnchors,
+    #[error("More points than the palette allows")]
+    TooManyPoints,
+    #[error("No exporter registered for that format")]
+    UnknownFormat,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PolineOptions {
+    /// The [`SCHEMA_VERSION`] these options were written with, 0 when saved
+    /// before options were versioned
+    #[serde(default)]
+    pub version: u32,
+    /// The anchors, generated by `anchor_strategy` when `None`. When set
+    /// they win over any strategy, an [`AnchorStrategy::Explicit`] one
+    /// included.
     pub anchor_colors: Option<Vec<Vector3>>,
+    #[serde(default)]
+    pub anchor_strategy: AnchorStrategy,
+    /// Lightness range of randomly generated anchors, by default they
+    /// alternate between light (0.75 to 0.95) and dark (0.3 to 0.5)
+    #[serde(default)]
+    pub random_lightness_range: Option<Vector2>,
+    /// Saturation range of randomly generated anchors, 0 to 1 by default
+    #[serde(default)]
+    pub random_saturation_range: Option<Vector2>,
+    /// Draws the generated anchors from this seed, so the same seed makes
+    /// the same palette. Kept in the palette's [`Provenance`].
+    #[serde(default)]
+    pub seed: Option<u64>,
     pub num_points: usize,
+    #[serde(default)]
+    pub point_count_mode: PointCountMode,
     pub position_function: PositionScale,
     pub position_function_x: Option<PositionScale>,
     pub position_function_y: Option<PositionScale>,
     pub position_function_z: Option<PositionScale>,
+    #[serde(default)]
+    pub reverse_x: bool,
+    #[serde(default)]
+    pub reverse_y: bool,
+    #[serde(default)]
+    pub reverse_z: bool,
     pub inverted_lightness: bool,
     pub closed_loop: bool,
+    #[serde(default)]
+    pub color_model: ColorModel,
+    /// Adjust the sampled colors so lightness only increases (or only
+    /// decreases) along the palette, as sequential colormaps need
+    #[serde(default)]
+    pub monotonic_lightness: bool,
+    /// On closed loops, bend the last to first segment along its
+    /// neighbours so cyclic gradients have no visible seam
+    #[serde(default)]
+    pub smooth_seam: bool,
+    /// Output every sampled point of every segment, keeping the anchors
+    /// that adjacent segments share (and the repeated first anchor of a
+    /// closed loop) twice
+    #[serde(default)]
+    pub include_anchor_duplicates: bool,
+    /// Whether the position functions ease the x, y, z position or the
+    /// HSL channels between anchors
+    #[serde(default)]
+    pub interpolation: InterpolationSpace,
+    /// Which way hues go around the wheel with `InterpolationSpace::Hsl`
+    #[serde(default)]
+    pub hue_interpolation: HueInterpolation,
+    /// Per segment overrides of `hue_interpolation`, by segment index
+    #[serde(default)]
+    pub segment_hue_interpolations: Vec<(usize, HueInterpolation)>,
+    /// The most anchors the palette accepts, [`MAX_ANCHORS`] when `None`
+    #[serde(default)]
+    pub max_anchors: Option<usize>,
+    /// The most colors the palette outputs, [`MAX_TOTAL_POINTS`] when `None`
+    #[serde(default)]
+    pub max_total_points: Option<usize>,
+    /// Steps edited anchors are rounded to, see [`Poline::set_snapping`].
+    /// The anchors given here are taken as they are.
+    #[serde(default)]
+    pub snapping: Option<Snapping>,
 }
 
 impl Default for PolineOptions {
     fn default() -> Self {
         Self {
-            anchor_colors: Some(random_hsl_pair(None, None, None)),
+            version: SCHEMA_VERSION,
+            anchor_colors: None,
+            anchor_strategy: AnchorStrategy::RandomPair,
+            random_lightness_range: None,
+            random_saturation_range: None,
+            seed: None,
             num_points: 4,
+            point_count_mode: PointCountMode::PerSegment,
             position_function: PositionScale::Sinusoidal,
             position_function_x: None,
             position_function_y: None,
             position_function_z: None,
+            reverse_x: false,
+            reverse_y: false,
+            reverse_z: false,
             inverted_lightness: false,
             closed_loop: false,
+            color_model: ColorModel::Hsl,
+            monotonic_lightness: false,
+            smooth_seam: false,
+            include_anchor_duplicates: false,
+            interpolation: InterpolationSpace::Position,
+            hue_interpolation: HueInterpolation::Shorter,
+            segment_hue_interpolations: Vec::new(),
+            max_anchors: None,
+            max_total_points: None,
+            snapping: None,
         }
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Poline {
-    #[allow(dead_code)]
-    needs_update: bool,
+    #[serde(default)]
+    version: u32,
     anchor_points: Vec<ColorPoint>,
+    /// Stable ids of the anchors, in the same order
+    #[serde(default)]
+    anchor_ids: Vec<u32>,
+    #[serde(default)]
+    next_anchor_id: u32,
     num_points: usize,
-    points: Vec<Vec<ColorPoint>>,
+    #[serde(default)]
+    point_count_mode: PointCountMode,
+    /// Derived from the anchors, sampled on first read after an edit
+    #[serde(skip)]
+    points: OnceLock<SampledPoints>,
     position_function_x: PositionScale,
     position_function_y: PositionScale,
     position_function_z: PositionScale,
+    #[serde(default)]
+    reverse_x: bool,
+    #[serde(default)]
+    reverse_y: bool,
+    #[serde(default)]
+    reverse_z: bool,
+    /// Derived from the anchors, regenerated after loading
+    #[serde(default, skip_deserializing)]
     anchor_pairs: Vec<(ColorPoint, ColorPoint)>,
     connect_last_and_first_anchor: bool,
     #[allow(dead_code)]
     animation_frame: Option<f32>,
     inverted_lightness: bool,
+    #[serde(default)]
+    color_model: ColorModel,
+    #[serde(default)]
+    monotonic_lightness: bool,
+    #[serde(default)]
+    smooth_seam: bool,
+    #[serde(default)]
+    include_anchor_duplicates: bool,
+    #[serde(default)]
+    interpolation: InterpolationSpace,
+    #[serde(default)]
+    hue_interpolation: HueInterpolation,
+    /// Overrides of `hue_interpolation` by the id of the segment's first
+    /// anchor
+    #[serde(default)]
+    segment_hue_interpolations: Vec<(u32, HueInterpolation)>,
+    #[serde(default)]
+    max_anchors: Option<usize>,
+    #[serde(default)]
+    max_total_points: Option<usize>,
+    #[serde(default)]
+    snapping: Option<Snapping>,
+    #[serde(default)]
+    export_precision: Option<usize>,
+    #[serde(default)]
+    provenance: Option<Provenance>,
+    #[serde(skip)]
+    drag: Option<DragState>,
 }
 
+/// Panics with fewer than two anchors or more anchors or points than the
+/// options allow, see [`Poline::try_from_options`]
 impl From<PolineOptions> for Poline {
     fn from(options: PolineOptions) -> Self {
-        let anchor_colors = options
-            .anchor_colors
-            .unwrap_or(random_hsl_pair(None, None, None));
-        assert!(anchor_colors.len() >= 2);
+        Self::try_from_options(options).expect("palette options out of bounds")
+    }
+}
+
+impl Poline {
+    /// Samples the palette, failing as [`PolineOptions::validate`] does when
+    /// the anchor or point counts are out of bounds
+    pub fn try_from_options(mut options: PolineOptions) -> Result<Self, PolineErrors> {
+        options.validate()?;
+        let mut provenance = Provenance {
+            seed: options.seed,
+            ..Default::default()
+        };
+        let anchor_colors = match options.anchor_colors.take() {
+            Some(anchor_colors) => anchor_colors,
+            None => {
+                provenance.source_image_hash = options.anchor_strategy.source_image_hash();
+                options.generate_anchor_colors()
+            }
+        };
+        let provenance = Some(provenance).filter(|provenance| !provenance.is_empty());
+        // The strategy's count is checked above, this is the one sampled
+        options.check_anchor_count(anchor_colors.len())?;
         let anchor_points: Vec<ColorPoint> = anchor_colors
             .into_iter()
             .map(|point| {
@@ -89,9 +370,11 @@ impl From<PolineOptions> for Poline {
                     xyz: None,
                     color: Some(point),
                     inverted_lightness: options.inverted_lightness,
+                    model: options.color_model,
                 })
             })
             .collect();
+        let anchor_count = anchor_points.len();
         let num_points = options.num_points + 2;
         let position_function_x = options
             .position_function_x
@@ -102,153 +385,183 @@ impl From<PolineOptions> for Poline {
         let position_function_z = options
             .position_function_z
             .unwrap_or(options.position_function);
-        let (anchor_pairs, points) = Self::_update_anchor_pairs(
-            options.closed_loop,
-            anchor_points.clone(),
-            options.inverted_lightness,
-            options.num_points,
-            position_function_x,
-            position_function_y,
-            position_function_z,
-        );
-        Self {
+        let mut poline = Self {
+            version: SCHEMA_VERSION,
             anchor_points,
+            anchor_ids: Vec::new(),
+            next_anchor_id: 0,
             num_points,
+            point_count_mode: options.point_count_mode,
             position_function_x,
             position_function_y,
             position_function_z,
+            reverse_x: options.reverse_x,
+            reverse_y: options.reverse_y,
+            reverse_z: options.reverse_z,
             connect_last_and_first_anchor: options.closed_loop,
             inverted_lightness: options.inverted_lightness,
-            needs_update: true,
-            anchor_pairs,
+            color_model: options.color_model,
+            monotonic_lightness: options.monotonic_lightness,
+            smooth_seam: options.smooth_seam,
+            include_anchor_duplicates: options.include_anchor_duplicates,
+            interpolation: options.interpolation,
+            hue_interpolation: options.hue_interpolation,
+            // Anchor ids start out as the anchors' indices
+            segment_hue_interpolations: options
+                .segment_hue_interpolations
+                .iter()
+                .filter(|&&(index, _)| index < anchor_count)
+                .map(|&(index, hue)| (index as u32, hue))
+                .collect(),
+            max_anchors: options.max_anchors,
+            max_total_points: options.max_total_points,
+            snapping: options.snapping,
+            export_precision: None,
+            provenance,
+            drag: None,
+            anchor_pairs: Vec::new(),
             animation_frame: None,
-            points,
-        }
+            points: OnceLock::new(),
+        };
+        poline.update_anchor_pairs();
+        Ok(poline)
     }
 }
 
-#[wasm_bindgen]
+impl Default for Poline {
+    fn default() -> Self {
+        Self::from(PolineOptions::default())
+    }
+}
+
+/// Two palettes are equal when they have the same anchors and options, the
+/// sampled points follow from those
+impl PartialEq for Poline {
+    fn eq(&self, other: &Self) -> bool {
+        self.anchor_points == other.anchor_points
+            && self.num_points == other.num_points
+            && self.point_count_mode == other.point_count_mode
+            && self.position_function_x == other.position_function_x
+            && self.position_function_y == other.position_function_y
+            && self.position_function_z == other.position_function_z
+            && self.reverse_x == other.reverse_x
+            && self.reverse_y == other.reverse_y
+            && self.reverse_z == other.reverse_z
+            && self.connect_last_and_first_anchor == other.connect_last_and_first_anchor
+            && self.inverted_lightness == other.inverted_lightness
+            && self.color_model == other.color_model
+            && self.monotonic_lightness == other.monotonic_lightness
+            && self.smooth_seam == other.smooth_seam
+            && self.include_anchor_duplicates == other.include_anchor_duplicates
+            && self.interpolation == other.interpolation
+            && self.hue_interpolation == other.hue_interpolation
+            && self.segment_hue_interpolations_by_index()
+                == other.segment_hue_interpolations_by_index()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl Poline {
-    fn _update_anchor_pairs(
-        _loop: bool,
-        anchor_points: Vec<ColorPoint>,
-        inverted_lightness: bool,
-        num_points: usize,
-        fx: PositionScale,
-        fy: PositionScale,
-        fz: PositionScale,
-    ) -> (Vec<(ColorPoint, ColorPoint)>, Vec<Vec<ColorPoint>>) {
-        let mut anchor_pairs = Vec::with_capacity(anchor_points.len());
-        let anchor_points_length = if _loop {
-            anchor_points.len()
+    /// Rebuilds the anchor pairs and marks the samples stale, they are
+    /// sampled again on the next read
+    pub fn update_anchor_pairs(&mut self) {
+        trace::span!("update_anchor_pairs", anchors = self.anchor_points.len());
+        self.sync_anchor_ids();
+        let anchors = self.anchor_points.len();
+        let segments = if self.connect_last_and_first_anchor {
+            anchors
         } else {
-            anchor_points.len() - 1
+            anchors - 1
         };
+        self.anchor_pairs.clear();
+        self.anchor_pairs.extend(
+            (0..segments).map(|i| (self.anchor_points[i], self.anchor_points[(i + 1) % anchors])),
+        );
+        self.points = OnceLock::new();
+    }
 
-        for i in 0..anchor_points_length {
-            anchor_pairs.push((
-                anchor_points[i],
-                anchor_points[(i + 1) % anchor_points_length],
-            ));
-        }
-
-        let points: Vec<Vec<ColorPoint>> = anchor_pairs
-            .iter()
-            .clone()
-            .enumerate()
-            .map(|(idx, pair)| {
-                let p1_position = pair.0.position();
-                let p2_position = pair.1.position();
-                vectors_on_line(
-                    p1_position,
-                    p2_position,
-                    Some(num_points),
-                    idx % 2 == 0,
-                    Some(fx),
-                    Some(fy),
-                    Some(fz),
-                )
-                .into_iter()
-                .map(|point| {
-                    ColorPoint::new(ColorPointCollection {
-                        xyz: Some(point),
-                        color: None,
-                        inverted_lightness,
-                    })
-                })
-                .collect()
-            })
-            .collect();
-        (anchor_pairs, points)
+    /// Whether an edit left the samples stale, to be sampled again on the
+    /// next read
+    pub fn needs_update(&self) -> bool {
+        self.points.get().is_none()
     }
 
-    pub fn update_anchor_pairs(&mut self) {
-        let (anchor_pairs, points) = Self::_update_anchor_pairs(
-            self.connect_last_and_first_anchor,
-            self.anchor_points.clone(),
-            self.inverted_lightness,
-            self.num_points,
-            self.position_function_x,
-            self.position_function_y,
-            self.position_function_z,
-        );
-        self.anchor_pairs = anchor_pairs;
-        self.points = points;
+    /// Samples the palette now rather than on the next read, e.g. before
+    /// sharing it with a render thread
+    pub fn recompute(&self) {
+        self.sampled_points();
     }
 
+    /// Adds an anchor at `insert_at_index`, or at the end, and returns its
+    /// stable id. Fails when the palette already has as many anchors, or
+    /// would output more points, than it allows.
     pub fn add_anchor_point(
         &mut self,
         initial: ColorPointCollection,
         insert_at_index: Option<usize>,
-    ) -> ColorPoint {
-        let new_anchor = ColorPoint::new(initial);
-        if let Some(index) = insert_at_index {
-            self.anchor_points.insert(index, new_anchor);
-        } else {
-            self.anchor_points.push(new_anchor)
-        };
+    ) -> Result<u32, PolineErrors> {
+        self.check_counts(self.num_points - 2, self.anchor_points.len() + 1)?;
+        let index = insert_at_index.unwrap_or(self.anchor_points.len());
+        if index > self.anchor_points.len() {
+            return Err(PolineErrors::IndexOutOfBounds);
+        }
+        let mut new_anchor = ColorPoint::new(initial);
+        self.snap_anchor(&mut new_anchor);
+        let id = self.allocate_anchor_id();
+        self.anchor_points.insert(index, new_anchor);
+        self.anchor_ids.insert(index, id);
         self.update_anchor_pairs();
-        new_anchor
+        Ok(id)
     }
 
-    pub fn remove_anchor_point_at_index(&mut self, index: usize) {
+    /// Fails with `NotEnoughAnchors` rather than leaving fewer than two
+    pub fn remove_anchor_point_at_index(&mut self, index: usize) -> Result<(), PolineErrors> {
+        if index >= self.anchor_points.len() {
+            return Err(PolineErrors::IndexOutOfBounds);
+        }
+        if self.anchor_points.len() <= 2 {
+            return Err(PolineErrors::NotEnoughAnchors);
+        }
         self.anchor_points.remove(index);
+        self.anchor_ids.remove(index);
         self.update_anchor_pairs();
+        Ok(())
     }
 
-    pub fn remove_anchor_point(&mut self, point: ColorPoint) {
-        let index = self.anchor_points.iter().position(|&p| p == point);
-        if let Some(index) = index {
-            self.remove_anchor_point_at_index(index);
-        } else {
-            panic!("Point not found")
-        }
+    pub fn remove_anchor_point(&mut self, point: ColorPoint) -> Result<(), PolineErrors> {
+        let index = self.anchor_position(point)?;
+        self.remove_anchor_point_at_index(index)
     }
 
     pub fn update_anchor_point_at_index(
         &mut self,
         index: usize,
         initial: ColorPointCollection,
-    ) -> ColorPoint {
-        let mut point = self.anchor_points[index];
+    ) -> Result<ColorPoint, PolineErrors> {
+        let mut point = *self
+            .anchor_points
+            .get(index)
+            .ok_or(PolineErrors::IndexOutOfBounds)?;
         if let Some(xyz) = initial.xyz {
             point.set_position(xyz);
         };
         if let Some(color) = initial.color {
             point.set_hsl(color);
         };
-        self.update_anchor_pairs();
+        self.snap_anchor(&mut point);
+        self.anchor_points[index] = point;
+        self.update_segments_touching(index);
 
-        point
+        Ok(point)
     }
 
-    pub fn update_anchor_point(&mut self, point: ColorPoint, initial: ColorPointCollection) {
-        let index = self.anchor_points.iter().position(|&p| p == point);
-        if let Some(index) = index {
-            self.update_anchor_point_at_index(index, initial);
-        } else {
-            panic!("Point not found")
-        }
+    pub fn update_anchor_point(
+        &mut self,
+        point: ColorPoint,
+        initial: ColorPointCollection,
+    ) -> Result<ColorPoint, PolineErrors> {
+        let index = self.anchor_position(point)?;
+        self.update_anchor_point_at_index(index, initial)
     }
 
     pub fn get_closest_anchor_point(
@@ -260,7 +573,7 @@ impl Poline {
             .anchor_points
             .iter()
             .map(|anchor| {
-                R32::try_from(distance(optional_vector3(anchor.position()), xyz, false)).unwrap()
+                R32::from(distance(optional_vector3(anchor.position()), xyz, false))
             })
             .collect();
         let min_distance: &R32 = distances.iter().min().unwrap();
@@ -279,72 +592,22 @@ impl Poline {
         self.update_anchor_pairs();
     }
 
-    pub fn colors(&self) -> JsValue {
-        let colors: Vec<Vector3> = self
-            .flattened_points()
-            .iter()
-            .map(|point| point.color)
-            .collect();
-
-        if self.connect_last_and_first_anchor {
-            serde_wasm_bindgen::to_value(&colors.split_last().unwrap().1.to_vec()).unwrap()
-        } else {
-            serde_wasm_bindgen::to_value(&colors).unwrap()
-        }
-    }
-
-    pub fn anchor_points(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(&self.anchor_points).unwrap()
-    }
-
-    pub fn colors_css(&self) -> JsValue {
-        let colors: Vec<String> = self
-            .flattened_points()
-            .iter()
-            .map(|point| point.hsl_css())
-            .collect();
-
-        if self.connect_last_and_first_anchor {
-            serde_wasm_bindgen::to_value(&colors.split_last().unwrap().1.to_vec()).unwrap()
-        } else {
-            serde_wasm_bindgen::to_value(&colors).unwrap()
-        }
-    }
-
-    pub fn flattened_points_web(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(
-            &self
-                .points
-                .clone()
-                .into_iter()
-                .flatten()
-                .enumerate()
-                .filter(|(idx, _)| {
-                    if idx != &0 {
-                        idx % self.num_points == 0
-                    } else {
-                        true
-                    }
-                })
-                .map(|(_, elem)| elem)
-                .collect::<Vec<ColorPoint>>(),
-        )
-        .unwrap()
-    }
-
     pub fn set_position_fn_x(&mut self, scale_num: usize) {
         let scale = number_as_enum(scale_num);
         self.position_function_x = scale;
+        self.update_anchor_pairs();
     }
 
     pub fn set_position_fn_y(&mut self, scale_num: usize) {
         let scale = number_as_enum(scale_num);
-        self.position_function_z = scale;
+        self.position_function_y = scale;
+        self.update_anchor_pairs();
     }
 
     pub fn set_position_fn_z(&mut self, scale_num: usize) {
         let scale = number_as_enum(scale_num);
         self.position_function_z = scale;
+        self.update_anchor_pairs();
     }
 
     pub fn set_position_fn(&mut self, scale_num: usize) {
@@ -352,24 +615,650 @@ impl Poline {
         self.position_function_x = scale;
         self.position_function_y = scale;
         self.position_function_z = scale;
+        self.update_anchor_pairs();
+    }
+
+    pub fn set_reverse_x(&mut self, reverse: bool) {
+        self.reverse_x = reverse;
+        self.update_anchor_pairs();
+    }
+
+    pub fn set_reverse_y(&mut self, reverse: bool) {
+        self.reverse_y = reverse;
+        self.update_anchor_pairs();
+    }
+
+    pub fn set_reverse_z(&mut self, reverse: bool) {
+        self.reverse_z = reverse;
+        self.update_anchor_pairs();
+    }
+
+    pub fn color_model(&self) -> ColorModel {
+        self.color_model
+    }
+
+    pub fn inverted_lightness(&self) -> bool {
+        self.inverted_lightness
+    }
+
+    /// Switches the coordinate mapping, keeping the anchors' colors and
+    /// moving their positions to match the new layout
+    pub fn set_color_model(&mut self, model: ColorModel) {
+        self.color_model = model;
+        self.anchor_points
+            .iter_mut()
+            .for_each(|point| point.set_model(model));
+        self.update_anchor_pairs();
+    }
+
+    pub fn set_monotonic_lightness(&mut self, monotonic_lightness: bool) {
+        self.monotonic_lightness = monotonic_lightness;
+        self.update_anchor_pairs();
+    }
+
+    pub fn set_smooth_seam(&mut self, smooth_seam: bool) {
+        self.smooth_seam = smooth_seam;
+        self.update_anchor_pairs();
+    }
+
+    /// Duplicates are only dropped when flattening, the samples stay as
+    /// they are
+    pub fn set_include_anchor_duplicates(&mut self, include_anchor_duplicates: bool) {
+        self.include_anchor_duplicates = include_anchor_duplicates;
+    }
+
+    pub fn set_reverse(&mut self, reverse_x: bool, reverse_y: bool, reverse_z: bool) {
+        self.reverse_x = reverse_x;
+        self.reverse_y = reverse_y;
+        self.reverse_z = reverse_z;
+        self.update_anchor_pairs();
+    }
+
+    /// Points between each pair of anchors, or in the whole palette with
+    /// [`PointCountMode::TotalOutput`], as in `PolineOptions::num_points`.
+    /// Fails when the palette would output more points than it allows.
+    pub fn set_num_points(&mut self, num_points: usize) -> Result<(), PolineErrors> {
+        self.check_counts(num_points, self.anchor_points.len())?;
+        self.num_points = num_points + 2;
+        self.update_anchor_pairs();
+        Ok(())
+    }
+
+    pub fn set_closed_loop(&mut self, closed_loop: bool) {
+        self.connect_last_and_first_anchor = closed_loop;
+        self.update_anchor_pairs();
+    }
+
+    /// Keeps the anchors' colors and moves their positions to match
+    pub fn set_inverted_lightness(&mut self, inverted_lightness: bool) {
+        self.inverted_lightness = inverted_lightness;
+        self.anchor_points
+            .iter_mut()
+            .for_each(|point| point.set_inverted_lightness(inverted_lightness));
+        self.update_anchor_pairs();
     }
 }
 
 impl Poline {
+    fn anchor_position(&self, point: ColorPoint) -> Result<usize, PolineErrors> {
+        self.anchor_points
+            .iter()
+            .position(|&p| p == point)
+            .ok_or(PolineErrors::PointNotFound)
+    }
+
+    /// The options that rebuild this palette, with the current anchors, e.g.
+    /// to persist it or show its settings. `Poline::from(poline.options())`
+    /// samples the same colors.
+    pub fn options(&self) -> PolineOptions {
+        PolineOptions {
+            version: SCHEMA_VERSION,
+            anchor_colors: Some(self.anchor_points.iter().map(|point| point.color).collect()),
+            anchor_strategy: AnchorStrategy::RandomPair,
+            random_lightness_range: None,
+            random_saturation_range: None,
+            seed: self.provenance.as_ref().and_then(|provenance| provenance.seed),
+            num_points: self.num_points - 2,
+            point_count_mode: self.point_count_mode,
+            position_function: self.position_function_x,
+            position_function_x: Some(self.position_function_x),
+            position_function_y: Some(self.position_function_y),
+            position_function_z: Some(self.position_function_z),
+            reverse_x: self.reverse_x,
+            reverse_y: self.reverse_y,
+            reverse_z: self.reverse_z,
+            inverted_lightness: self.inverted_lightness,
+            closed_loop: self.connect_last_and_first_anchor,
+            color_model: self.color_model,
+            monotonic_lightness: self.monotonic_lightness,
+            smooth_seam: self.smooth_seam,
+            include_anchor_duplicates: self.include_anchor_duplicates,
+            interpolation: self.interpolation,
+            hue_interpolation: self.hue_interpolation,
+            segment_hue_interpolations: self.segment_hue_interpolations_by_index(),
+            max_anchors: self.max_anchors,
+            max_total_points: self.max_total_points,
+            snapping: self.snapping,
+        }
+    }
+
+    /// A new palette from the same anchors and options, after `f` has
+    /// changed the options
+    ///
+    /// ```
+    /// # use poline_core::Poline;
+    /// # let poline = Poline::default();
+    /// let looped = poline.with_options(|options| options.closed_loop = true);
+    /// assert!(looped.options().closed_loop);
+    /// ```
+    pub fn with_options(&self, f: impl FnOnce(&mut PolineOptions)) -> Poline {
+        let mut options = self.options();
+        f(&mut options);
+        Poline::from(options)
+    }
+
+    /// The sampled points in order. Unless `include_anchor_duplicates` is
+    /// set, a segment's first point is dropped when it sits where the
+    /// previous segment ended.
     pub fn flattened_points(&self) -> Vec<ColorPoint> {
-        self.points
-            .clone()
+        let positions = self.sampled_points().positions();
+        let colors = self.sampled_points().colors();
+        self.flattened_indices()
             .into_iter()
-            .flatten()
+            .map(|(_, idx)| {
+                ColorPoint::from_parts(
+                    positions[idx],
+                    colors[idx],
+                    self.inverted_lightness,
+                    self.color_model,
+                )
+            })
+            .collect()
+    }
+
+    /// The segment and buffer index of every flattened point
+    pub(crate) fn flattened_indices(&self) -> Vec<(usize, usize)> {
+        iter::FlatIndices::new(self, false).collect()
+    }
+
+    /// The anchors the palette is interpolated between
+    pub fn anchors(&self) -> &[ColorPoint] {
+        &self.anchor_points
+    }
+
+    /// The sampled positions and HSL colors of each segment, the joint
+    /// between two segments appears in both
+    pub fn segments(&self) -> impl Iterator<Item = (&[Vector3], &[Vector3])> + '_ {
+        (0..self.sampled_points().segment_count()).map(|index| {
+            (
+                self.sampled_points().segment_positions(index),
+                self.sampled_points().segment_colors(index),
+            )
+        })
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.anchor_pairs.len()
+    }
+
+    /// Points sampled on each segment, including both of its anchors. With
+    /// [`PointCountMode::TotalOutput`] the first segments may take one
+    /// point more than the rest, this is the larger count.
+    pub fn points_per_segment(&self) -> usize {
+        self.segment_sample_count(0)
+    }
+
+    /// The easing of the x, y and z axes
+    pub fn position_functions(&self) -> (PositionScale, PositionScale, PositionScale) {
+        (
+            self.position_function_x,
+            self.position_function_y,
+            self.position_function_z,
+        )
+    }
+
+    /// Every sampled point in flat position and color buffers, including
+    /// the duplicated joints between segments
+    pub fn sampled_points(&self) -> &SampledPoints {
+        if let Some(points) = self.points.get() {
+            trace::count!(monotonic_counter.poline_sample_cache_hits = 1_u64);
+            return points;
+        }
+        self.points.get_or_init(|| {
+            trace::span!("sample_palette", segments = self.anchor_pairs.len());
+            let mut points = SampledPoints::from_segments(self.sample_all_segments());
+            if self.monotonic_lightness {
+                self.enforce_monotonic_lightness(&mut points);
+            }
+            trace::count!(
+                monotonic_counter.poline_segments_sampled = self.anchor_pairs.len() as u64,
+                monotonic_counter.poline_points_sampled = points.len() as u64,
+            );
+            points
+        })
+    }
+
+    /// The palette colors: the flattened points without the first anchor
+    /// repeated at the end of a closed loop
+    pub(crate) fn output_points(&self) -> Vec<ColorPoint> {
+        let mut points = self.flattened_points();
+        let closing_duplicate = match (points.first(), points.last()) {
+            (Some(first), Some(last)) if points.len() > 1 => {
+                same_position(first.position(), last.position())
+            }
+            _ => false,
+        };
+        if self.connect_last_and_first_anchor
+            && !self.include_anchor_duplicates
+            && closing_duplicate
+        {
+            points.pop();
+        }
+        points
+    }
+
+    /// Re-targets the anchors designed under the `from` white point so they
+    /// appear the same under `to`, e.g. D65 screen colors for a D50 proof
+    pub fn adapt_white_point(&mut self, from: WhitePoint, to: WhitePoint) {
+        self.anchor_points.iter_mut().for_each(|point| {
+            let rgb = adapt_rgb(color_space::hsl_to_rgb(point.hsl()), from, to);
+            point.set_hsl(color_space::rgb_to_hsl(rgb));
+        });
+        self.update_anchor_pairs();
+    }
+
+    /// Mixes the palette colors at `i` and `j` in `space`, `None` when
+    /// either index is out of range
+    pub fn mix_swatches(&self, i: usize, j: usize, t: f32, space: MixSpace) -> Option<Vector3> {
+        let points = self.output_points();
+        let a = points.get(i)?.color;
+        let b = points.get(j)?.color;
+        Some(mix(a, b, t, space))
+    }
+
+    /// Blends every swatch with the corresponding swatch of `other`, matched
+    /// by relative position when the palettes differ in length
+    pub fn blend_with(&self, other: &Poline, mode: BlendMode) -> Vec<Vector3> {
+        let base = self.output_points();
+        let top = other.output_points();
+        if top.is_empty() {
+            return base.iter().map(|point| point.color).collect();
+        }
+        let last = base.len().saturating_sub(1).max(1) as f32;
+        base.iter()
             .enumerate()
-            .filter(|(idx, _)| {
-                if idx != &0 {
-                    idx % self.num_points == 0
-                } else {
-                    true
-                }
+            .map(|(i, point)| {
+                let j = ((i as f32 / last) * (top.len() - 1) as f32).round() as usize;
+                blend(point.color, top[j].color, mode)
+            })
+            .collect()
+    }
+
+    /// A transparency series for the palette color at `color_index`, `None`
+    /// when the index is out of range
+    pub fn alpha_ramp(&self, color_index: usize, steps: usize) -> Option<Vec<AlphaStep>> {
+        let point = self.output_points().get(color_index).copied()?;
+        Some(alpha_ramp(point.color, steps))
+    }
+
+    /// WCAG contrast and color vision deficiency checks of the palette
+    /// against an HSL `background`
+    pub fn accessibility_report(&self, background: Vector3) -> A11yReport {
+        A11yReport::new(&self.colors_vec(), background)
+    }
+
+    /// The HSL colors of the palette
+    pub fn colors_vec(&self) -> Vec<Vector3> {
+        self.iter_colors().collect()
+    }
+
+    /// The palette colors as CSS `hsl()` strings
+    pub fn css_strings(&self) -> Vec<String> {
+        let format = self.float_format(2);
+        self.output_points()
+            .iter()
+            .map(|point| float_format::css_hsl(point.color, format))
+            .collect()
+    }
+
+    /// The palette colors as sRGB with channels in 0..1
+    pub(crate) fn colors_rgb(&self) -> Vec<Vector3> {
+        self.output_points()
+            .iter()
+            .map(|point| color_space::hsl_to_rgb(point.color))
+            .collect()
+    }
+
+    /// The palette colors encoded for a Rec.2020 (BT.2020 transfer) pipeline
+    pub fn colors_rec2020(&self) -> Vec<Vector3> {
+        self.colors_rgb().into_iter().map(rgb_to_rec2020).collect()
+    }
+
+    /// The palette colors as scRGB, scaled by `brightness` (1.0 is SDR white)
+    pub fn colors_scrgb(&self, brightness: f32) -> Vec<Vector3> {
+        self.colors_rgb()
+            .into_iter()
+            .map(|rgb| rgb_to_scrgb(rgb, brightness))
+            .collect()
+    }
+
+    /// The Rec.2020 palette colors packed as opaque RGB10A2 values
+    pub fn colors_rec2020_10bit(&self) -> Vec<u32> {
+        self.colors_rec2020()
+            .into_iter()
+            .map(|rgb| pack_rgb10a2(rgb, 1.0))
+            .collect()
+    }
+
+    /// The palette colors as opaque RGBA8, 4 bytes per color
+    pub fn colors_rgba8(&self) -> Vec<u8> {
+        self.colors_rgb()
+            .into_iter()
+            .flat_map(|rgb| {
+                let [r, g, b] = rgb_to_rgb8(rgb);
+                [r, g, b, 255]
             })
-            .map(|(_, elem)| elem)
             .collect()
     }
+
+    /// The palette colors as opaque RGBA half floats, 4 values per color
+    #[cfg(feature = "half")]
+    pub fn colors_f16(&self) -> Vec<half::f16> {
+        self.colors_rgb()
+            .into_iter()
+            .flat_map(|Vector3(r, g, b)| [r, g, b, 1.0].map(half::f16::from_f32))
+            .collect()
+    }
+
+    /// The palette colors as HCT (hue, chroma, tone) for Material tooling
+    #[cfg(feature = "hct")]
+    pub fn colors_hct(&self) -> Vec<Vector3> {
+        self.colors_rgb().into_iter().map(hct::rgb_to_hct).collect()
+    }
+
+    /// The palette colors as 8-bit device values in an ICC output profile
+    #[cfg(feature = "icc")]
+    pub fn colors_in_profile(&self, profile: &Profile) -> Result<Vec<[u8; 3]>, PolineErrors> {
+        rgb_in_profile(&self.colors_rgb(), profile)
+    }
+}
+
+/// Whether two sampled positions are the same point, allowing for the
+/// rounding of separately computed segment ends
+fn same_position(a: Vector3, b: Vector3) -> bool {
+    (a.0 - b.0).abs() < 1e-5 && (a.1 - b.1).abs() < 1e-5 && (a.2 - b.2).abs() < 1e-5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_poline() -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.8, 0.3), Vector3(200.0, 0.6, 0.7)]),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn position_setters_ease_their_own_axis() {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.8, 0.3), Vector3(200.0, 0.6, 0.8)]),
+            position_function: PositionScale::Linear,
+            ..Default::default()
+        });
+        let before = poline.sampled_points().clone();
+        poline.set_position_fn_y(2);
+        assert!(matches!(poline.position_function_y, PositionScale::Cubic));
+        assert!(matches!(poline.position_function_z, PositionScale::Linear));
+        assert_ne!(poline.sampled_points(), &before);
+    }
+
+    #[test]
+    fn open_palettes_end_at_the_last_anchor() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(20.0, 0.8, 0.3),
+                Vector3(120.0, 0.6, 0.5),
+                Vector3(200.0, 0.6, 0.7),
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(poline.anchor_pairs.len(), 2);
+        assert_eq!(poline.anchor_pairs[1], (poline.anchor_points[1], poline.anchor_points[2]));
+    }
+
+    #[test]
+    fn joints_are_deduplicated_by_position() {
+        let options = PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(20.0, 0.8, 0.3),
+                Vector3(120.0, 0.6, 0.7),
+                Vector3(220.0, 0.6, 0.5),
+            ]),
+            num_points: 3,
+            closed_loop: true,
+            inverted_lightness: true,
+            ..Default::default()
+        };
+        let poline = Poline::from(options.clone());
+        let points = poline.flattened_points();
+        assert_eq!(points.len(), 13);
+        assert!(points
+            .windows(2)
+            .all(|w| !same_position(w[0].position(), w[1].position())));
+        assert_eq!(poline.output_points().len(), 12);
+
+        let raw = Poline::from(PolineOptions {
+            include_anchor_duplicates: true,
+            ..options
+        });
+        assert_eq!(raw.flattened_points().len(), 15);
+        assert_eq!(raw.output_points().len(), 15);
+    }
+
+    #[test]
+    fn runtime_setters_match_construction() {
+        let mut poline = test_poline();
+        poline.set_num_points(7).unwrap();
+        poline.set_closed_loop(true);
+        poline.set_inverted_lightness(true);
+        let expected = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.8, 0.3), Vector3(200.0, 0.6, 0.7)]),
+            num_points: 7,
+            closed_loop: true,
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        assert_eq!(poline.len(), expected.len());
+        for (a, b) in poline.iter_colors().zip(expected.iter_colors()) {
+            assert!((a.0 - b.0).abs() < 1e-3 && (a.2 - b.2).abs() < 1e-4, "{a:?} {b:?}");
+        }
+    }
+
+    #[test]
+    fn edits_fail_instead_of_panicking() {
+        let mut poline = test_poline();
+        let update = ColorPointCollection {
+            xyz: None,
+            color: Some(Vector3(90.0, 0.5, 0.5)),
+            inverted_lightness: false,
+            model: Default::default(),
+        };
+        assert!(matches!(
+            poline.update_anchor_point_at_index(2, update),
+            Err(PolineErrors::IndexOutOfBounds)
+        ));
+        assert!(matches!(
+            poline.remove_anchor_point_at_index(2),
+            Err(PolineErrors::IndexOutOfBounds)
+        ));
+        assert!(matches!(
+            poline.remove_anchor_point_at_index(0),
+            Err(PolineErrors::NotEnoughAnchors)
+        ));
+        let stray = ColorPoint::new(update);
+        assert!(matches!(
+            poline.remove_anchor_point(stray),
+            Err(PolineErrors::PointNotFound)
+        ));
+        let first = poline.anchors()[0];
+        assert!(poline.update_anchor_point(first, update).is_ok());
+
+        let one_anchor = Poline::try_from_options(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.8, 0.3)]),
+            ..Default::default()
+        });
+        assert!(matches!(one_anchor, Err(PolineErrors::NotEnoughAnchors)));
+    }
+
+    #[test]
+    fn edits_resample_on_the_next_read() {
+        let mut poline = test_poline();
+        assert!(poline.needs_update());
+        poline.set_position_fn_y(7);
+        poline.set_closed_loop(true);
+        poline.shift_hue(30.0);
+        assert!(poline.needs_update());
+        let colors = poline.colors_vec();
+        assert!(!poline.needs_update());
+        assert_eq!(Poline::from(poline.options()).colors_vec(), colors);
+
+        poline.set_reverse_x(true);
+        poline.recompute();
+        assert!(!poline.needs_update());
+        assert_ne!(poline.colors_vec(), colors);
+    }
+
+    #[test]
+    fn options_rebuild_the_palette() {
+        let mut poline = test_poline();
+        poline.set_position_fn_y(7);
+        poline.set_closed_loop(true);
+        poline.shift_hue(30.0);
+        let options = poline.options();
+        assert_eq!(options.anchor_colors.as_ref().map(Vec::len), Some(2));
+        assert_eq!(options.position_function_y, Some(PositionScale::Arc));
+        assert_eq!(Poline::from(options).colors_vec(), poline.colors_vec());
+    }
+
+    #[test]
+    fn anchor_colors_win_over_the_strategy() {
+        let anchors = vec![Vector3(20.0, 0.8, 0.3), Vector3(200.0, 0.6, 0.7)];
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(anchors.clone()),
+            anchor_strategy: AnchorStrategy::Explicit(vec![
+                Vector3(0.0, 0.5, 0.5),
+                Vector3(90.0, 0.5, 0.5),
+                Vector3(180.0, 0.5, 0.5),
+            ]),
+            ..Default::default()
+        });
+        let colors: Vec<Vector3> = poline.anchors().iter().map(|point| point.color).collect();
+        assert_eq!(colors, anchors);
+    }
+
+    #[test]
+    fn random_anchors_stay_in_range() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: None,
+            random_lightness_range: Some(Vector2(0.1, 0.2)),
+            random_saturation_range: Some(Vector2(0.5, 0.6)),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        for anchor in poline.anchors() {
+            let Vector3(_, s, l) = anchor.color;
+            assert!((0.5..=0.6).contains(&s), "{s}");
+            assert!((0.1..=0.2).contains(&l), "{l}");
+        }
+    }
+
+    #[test]
+    fn mixes_swatches_in_the_given_space() {
+        let poline = test_poline();
+        let colors = poline.colors_vec();
+        let last = colors.len() - 1;
+        for space in [MixSpace::Hsl, MixSpace::Oklab, MixSpace::LinearRgb] {
+            let mixed = poline.mix_swatches(0, last, 0.5, space);
+            assert_eq!(mixed, Some(mix(colors[0], colors[last], 0.5, space)));
+        }
+        assert_eq!(poline.mix_swatches(0, last + 1, 0.5, MixSpace::Oklab), None);
+    }
+
+    #[test]
+    fn blending_with_white_screen_is_white() {
+        let poline = test_poline();
+        let white = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 1.0), Vector3(0.0, 0.0, 1.0)]),
+            ..Default::default()
+        });
+        let blended = poline.blend_with(&white, BlendMode::Screen);
+        assert_eq!(blended.len(), poline.output_points().len());
+        assert!(blended.iter().all(|color| (color.2 - 1.0).abs() < 1e-4));
+    }
+
+    #[test]
+    fn with_options_keeps_the_anchors() {
+        let poline = test_poline();
+        assert_eq!(poline.with_options(|_| {}), poline);
+
+        let looped = poline.with_options(|options| options.closed_loop = true);
+        assert_ne!(looped, poline);
+        assert_eq!(looped.anchor_points, poline.anchor_points);
+        assert_eq!(looped.anchor_pairs.len(), 2);
+    }
+
+    #[test]
+    fn accessors_describe_the_structure() {
+        let poline = test_poline();
+        assert_eq!(poline.anchors().len(), 2);
+        assert_eq!(poline.segment_count(), 1);
+        let (positions, colors) = poline.segments().next().unwrap();
+        assert_eq!(positions.len(), poline.points_per_segment());
+        assert_eq!(positions[0], poline.anchors()[0].position());
+        assert_eq!(colors.len(), positions.len());
+        assert_eq!(
+            poline.position_functions(),
+            (PositionScale::Sinusoidal, PositionScale::Sinusoidal, PositionScale::Sinusoidal)
+        );
+    }
+
+    #[test]
+    fn packed_buffers_hold_four_channels() {
+        let poline = test_poline();
+        let count = poline.output_points().len();
+        let rgba8 = poline.colors_rgba8();
+        assert_eq!(rgba8.len(), count * 4);
+        assert_eq!(rgba8[..3], rgb_to_rgb8(poline.colors_rgb()[0]));
+        assert!(rgba8.chunks(4).all(|texel| texel[3] == 255));
+
+        #[cfg(feature = "half")]
+        {
+            let f16 = poline.colors_f16();
+            assert_eq!(f16.len(), count * 4);
+            assert_eq!(f16[3], half::f16::ONE);
+        }
+    }
+
+    #[test]
+    fn monotonic_lightness_only_increases() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(20.0, 0.8, 0.2),
+                Vector3(120.0, 0.8, 0.8),
+                Vector3(220.0, 0.8, 0.5),
+            ]),
+            inverted_lightness: true,
+            monotonic_lightness: true,
+            ..Default::default()
+        });
+        assert!(poline.lightness_monotonicity() < 1.0);
+        let lightness: Vec<f32> = poline
+            .output_points()
+            .iter()
+            .map(|point| hsl_to_oklab(point.color).0)
+            .collect();
+        assert!(lightness.windows(2).all(|w| w[1] >= w[0] - 1e-3));
+    }
 }
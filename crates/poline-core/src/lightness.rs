@@ -0,0 +1,130 @@
+use crate::{
+    color_space::{hsl_to_oklab, oklab_to_hsl, oklab_to_linear_rgb},
+    storage::SampledPoints,
+    types::Vector3,
+    Poline,
+};
+
+/// Converts to HSL like `oklab_to_hsl`, but brings out of gamut colors in
+/// by reducing their chroma rather than clamping, which would change their
+/// lightness again
+fn oklab_to_hsl_keeping_lightness(lab: Vector3) -> Vector3 {
+    let in_gamut = |factor: f32| {
+        let Vector3(r, g, b) = oklab_to_linear_rgb(Vector3(lab.0, lab.1 * factor, lab.2 * factor));
+        [r, g, b].iter().all(|c| (-1e-4..=1.0 + 1e-4).contains(c))
+    };
+    if in_gamut(1.0) {
+        return oklab_to_hsl(lab);
+    }
+    let (mut low, mut high) = (0.0_f32, 1.0_f32);
+    for _ in 0..16 {
+        let factor = (low + high) / 2.0;
+        if in_gamut(factor) {
+            low = factor;
+        } else {
+            high = factor;
+        }
+    }
+    oklab_to_hsl(Vector3(lab.0, lab.1 * low, lab.2 * low))
+}
+
+/// Replaces `values` with the closest non-decreasing sequence in the least
+/// squares sense (pool adjacent violators)
+fn isotonic_increasing(values: &mut [f32]) {
+    // Each block is (mean, length)
+    let mut blocks: Vec<(f32, usize)> = Vec::with_capacity(values.len());
+    for &value in values.iter() {
+        blocks.push((value, 1));
+        while blocks.len() > 1 && blocks[blocks.len() - 2].0 > blocks[blocks.len() - 1].0 {
+            let (mean_b, len_b) = blocks.pop().unwrap();
+            let (mean_a, len_a) = blocks.pop().unwrap();
+            let len = len_a + len_b;
+            blocks.push((
+                (mean_a * len_a as f32 + mean_b * len_b as f32) / len as f32,
+                len,
+            ));
+        }
+    }
+    let mut i = 0;
+    for (mean, len) in blocks {
+        values[i..i + len].fill(mean);
+        i += len;
+    }
+}
+
+impl Poline {
+    /// How monotonic the anchors' OKLab lightness is along the palette, from
+    /// 1.0 (strictly one direction) down towards 0.0 (lightness goes back and
+    /// forth). Anything below 1.0 means the monotonic lightness option has to
+    /// move the palette away from the anchor colors.
+    pub fn lightness_monotonicity(&self) -> f32 {
+        let mut lightness: Vec<f32> = self
+            .anchor_points
+            .iter()
+            .map(|point| hsl_to_oklab(point.color).0)
+            .collect();
+        if self.connect_last_and_first_anchor {
+            lightness.push(lightness[0]);
+        }
+        let travelled: f32 = lightness.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+        if travelled == 0.0 {
+            return 1.0;
+        }
+        (lightness[lightness.len() - 1] - lightness[0]).abs() / travelled
+    }
+
+    /// Rewrites the sampled points so OKLab lightness only ever moves in the
+    /// direction from the first to the last point
+    pub(crate) fn enforce_monotonic_lightness(&self, points: &mut SampledPoints) {
+        let labs: Vec<Vector3> = points
+            .colors()
+            .iter()
+            .map(|&color| hsl_to_oklab(color))
+            .collect();
+        let (Some(first), Some(last)) = (labs.first(), labs.last()) else {
+            return;
+        };
+        let increasing = last.0 >= first.0;
+
+        let mut lightness: Vec<f32> = labs
+            .iter()
+            .map(|lab| if increasing { lab.0 } else { -lab.0 })
+            .collect();
+        isotonic_increasing(&mut lightness);
+
+        let adjusted = labs.iter().zip(lightness).map(|(lab, l)| {
+            let l = if increasing { l } else { -l };
+            Vector3(l, lab.1, lab.2)
+        });
+        let (positions, colors) = points.positions_and_colors_mut();
+        for (color, lab) in colors.iter_mut().zip(adjusted) {
+            *color = oklab_to_hsl_keeping_lightness(lab);
+        }
+        let adjusted_positions = self
+            .color_model
+            .hsl_to_points(colors, self.inverted_lightness);
+        positions.copy_from_slice(&adjusted_positions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pools_violations() {
+        let mut values = [0.1, 0.5, 0.3, 0.7];
+        isotonic_increasing(&mut values);
+        assert_eq!(values, [0.1, 0.4, 0.4, 0.7]);
+    }
+
+    #[test]
+    fn gamut_mapping_keeps_lightness() {
+        // A saturated blue pushed to a lightness it has no room for in sRGB
+        let lab = Vector3(0.9, -0.03, -0.3);
+        let kept = hsl_to_oklab(oklab_to_hsl_keeping_lightness(lab)).0;
+        let clamped = hsl_to_oklab(oklab_to_hsl(lab)).0;
+        assert!((kept - 0.9).abs() < 1e-3, "{kept}");
+        assert!((clamped - 0.9).abs() > 1e-2, "{clamped}");
+    }
+}
@@ -0,0 +1,231 @@
+//! Limits on the anchor and point counts, so that pathological inputs fail
+//! with an error instead of panicking or sampling millions of colors.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{AnchorStrategy, PointCountMode, Poline, PolineErrors, PolineOptions};
+
+/// Anchors allowed when `PolineOptions::max_anchors` is not set
+pub const MAX_ANCHORS: usize = 1024;
+/// Output colors allowed when `PolineOptions::max_total_points` is not set
+pub const MAX_TOTAL_POINTS: usize = 1 << 20;
+
+/// The colors a palette outputs, as `num_points` is given in the options
+fn output_len(num_points: usize, anchors: usize, closed: bool, mode: PointCountMode) -> usize {
+    let segments = if closed {
+        anchors
+    } else {
+        anchors.saturating_sub(1)
+    };
+    match mode {
+        PointCountMode::PerSegment => segments
+            .saturating_mul(num_points.saturating_add(1))
+            .saturating_add(usize::from(!closed)),
+        PointCountMode::TotalOutput => num_points.max(anchors),
+    }
+}
+
+/// Fails with `NotEnoughAnchors`, `TooManyAnchors` or `TooManyPoints` when a
+/// palette of that shape is out of bounds
+fn check_counts(
+    num_points: usize,
+    anchors: usize,
+    closed: bool,
+    mode: PointCountMode,
+    max_anchors: usize,
+    max_total_points: usize,
+) -> Result<(), PolineErrors> {
+    if anchors < 2 {
+        return Err(PolineErrors::NotEnoughAnchors);
+    }
+    if anchors > max_anchors {
+        return Err(PolineErrors::TooManyAnchors);
+    }
+    if output_len(num_points, anchors, closed, mode) > max_total_points {
+        return Err(PolineErrors::TooManyPoints);
+    }
+    Ok(())
+}
+
+impl AnchorStrategy {
+    /// How many anchors the strategy generates
+    pub fn anchor_count(&self) -> usize {
+        match self {
+            AnchorStrategy::RandomPair => 2,
+            AnchorStrategy::RandomTriple => 3,
+            AnchorStrategy::Harmony(kind) => kind.hue_offsets().len(),
+            // As `reduce_anchors` keeps every pixel when there are no more
+            // than `count`, or `count` is 0
+            AnchorStrategy::FromImage { pixels, count } if *count == 0 => pixels.len(),
+            AnchorStrategy::FromImage { pixels, count } => pixels.len().min(*count),
+            AnchorStrategy::Explicit(colors) => colors.len(),
+        }
+    }
+}
+
+impl PolineOptions {
+    /// Checks the anchors and point count against the limits, counting the
+    /// anchors the strategy will generate when `anchor_colors` is not set
+    pub fn validate(&self) -> Result<(), PolineErrors> {
+        let anchors = self
+            .anchor_colors
+            .as_ref()
+            .map_or_else(|| self.anchor_strategy.anchor_count(), Vec::len);
+        self.check_anchor_count(anchors)
+    }
+
+    /// Checks the point count and `anchors` anchors against the limits
+    pub(crate) fn check_anchor_count(&self, anchors: usize) -> Result<(), PolineErrors> {
+        check_counts(
+            self.num_points,
+            anchors,
+            self.closed_loop,
+            self.point_count_mode,
+            self.max_anchors.unwrap_or(MAX_ANCHORS),
+            self.max_total_points.unwrap_or(MAX_TOTAL_POINTS),
+        )
+    }
+}
+
+impl Poline {
+    /// Fails when the palette would be out of bounds with `anchors` anchors
+    /// and `num_points` as given in the options
+    pub(crate) fn check_counts(
+        &self,
+        num_points: usize,
+        anchors: usize,
+    ) -> Result<(), PolineErrors> {
+        check_counts(
+            num_points,
+            anchors,
+            self.connect_last_and_first_anchor,
+            self.point_count_mode,
+            self.max_anchors(),
+            self.max_total_points(),
+        )
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    pub fn max_anchors(&self) -> usize {
+        self.max_anchors.unwrap_or(MAX_ANCHORS)
+    }
+
+    pub fn max_total_points(&self) -> usize {
+        self.max_total_points.unwrap_or(MAX_TOTAL_POINTS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::Vector3, ColorPointCollection};
+
+    fn anchors(count: usize) -> Option<Vec<Vector3>> {
+        Some(
+            (0..count)
+                .map(|i| Vector3(i as f32 * 30.0, 0.5, 0.5))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn options_out_of_bounds_are_rejected() {
+        let options = |anchor_colors, num_points| PolineOptions {
+            anchor_colors,
+            num_points,
+            max_anchors: Some(8),
+            max_total_points: Some(100),
+            ..Default::default()
+        };
+        assert!(options(anchors(3), 4).validate().is_ok());
+        assert!(options(None, 4).validate().is_ok());
+        assert!(matches!(
+            options(anchors(1), 4).validate(),
+            Err(PolineErrors::NotEnoughAnchors)
+        ));
+        assert!(matches!(
+            options(anchors(9), 4).validate(),
+            Err(PolineErrors::TooManyAnchors)
+        ));
+        assert!(matches!(
+            options(anchors(3), 50).validate(),
+            Err(PolineErrors::TooManyPoints)
+        ));
+        assert!(PolineOptions {
+            num_points: usize::MAX,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn generated_anchors_are_counted() {
+        use std::mem::discriminant;
+
+        let strategy = |anchor_strategy| PolineOptions {
+            anchor_strategy,
+            max_anchors: Some(8),
+            ..Default::default()
+        };
+        let pixels = vec![Vector3(0.0, 1.0, 0.5), Vector3(240.0, 1.0, 0.5)];
+        for (anchor_strategy, expected) in [
+            (AnchorStrategy::Explicit(vec![]), PolineErrors::NotEnoughAnchors),
+            (AnchorStrategy::Explicit(anchors(1).unwrap()), PolineErrors::NotEnoughAnchors),
+            (AnchorStrategy::Explicit(anchors(9).unwrap()), PolineErrors::TooManyAnchors),
+            (
+                AnchorStrategy::FromImage { pixels: pixels.clone(), count: 1 },
+                PolineErrors::NotEnoughAnchors,
+            ),
+            (
+                AnchorStrategy::FromImage { pixels: pixels.repeat(5), count: 0 },
+                PolineErrors::TooManyAnchors,
+            ),
+        ] {
+            let options = strategy(anchor_strategy);
+            let same = |error: PolineErrors| discriminant(&error) == discriminant(&expected);
+            assert!(same(options.validate().unwrap_err()));
+            assert!(same(Poline::try_from_options(options).unwrap_err()));
+        }
+        let options = strategy(AnchorStrategy::FromImage { pixels, count: 2 });
+        assert!(Poline::try_from_options(options).is_ok());
+    }
+
+    #[test]
+    fn setters_keep_the_palette_in_bounds() {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: anchors(2),
+            max_anchors: Some(3),
+            max_total_points: Some(50),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let anchor = ColorPointCollection {
+            xyz: None,
+            color: Some(Vector3(200.0, 0.5, 0.5)),
+            inverted_lightness: true,
+            model: Default::default(),
+        };
+        assert!(poline.add_anchor_point(anchor, None).is_ok());
+        assert!(matches!(
+            poline.add_anchor_point(anchor, None),
+            Err(PolineErrors::TooManyAnchors)
+        ));
+        assert!(matches!(
+            poline.set_num_points(30),
+            Err(PolineErrors::TooManyPoints)
+        ));
+        assert_eq!(poline.output_points().len(), 11);
+
+        assert!(poline.remove_anchor_point_at_index(5).is_err());
+        poline.remove_anchor_point_at_index(0).unwrap();
+        assert!(matches!(
+            poline.remove_anchor_point_at_index(0),
+            Err(PolineErrors::NotEnoughAnchors)
+        ));
+        assert_eq!(poline.anchors().len(), 2);
+    }
+}
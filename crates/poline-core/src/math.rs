@@ -0,0 +1,58 @@
+//! The geometry poline samples palettes with: mapping HSL colors to and
+//! from the (x, y, z) unit cube of each [`ColorModel`](crate::ColorModel),
+//! easing between two positions and the hue-aware distance between them.
+//!
+//! Positions are centered on (0.5, 0.5), hues go around that center and
+//! colors are `Vector3(hue, saturation, lightness)` with the hue in degrees.
+//! Like poline.js, [`hsl_to_point`] only places the lightness with
+//! `inverted_lightness`, otherwise every color sits on the outer edge.
+
+pub use crate::positions::position_from_scale;
+pub use crate::utils::{
+    distance, hsl_to_point, hsl_to_point_lightness_z, hsl_to_point_oklch, optional_vector3,
+    point_to_hsl, point_to_hsl_lightness_z, point_to_hsl_oklch, vector_on_line, vectors_on_line,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PartialVector3, PositionScale, Vector3};
+
+    fn close(a: Vector3, b: Vector3) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-4 && (a.2 - b.2).abs() < 1e-4
+    }
+
+    #[test]
+    fn cylinders_round_trip() {
+        let hsl = Vector3(210.0, 0.6, 0.35);
+        let point = hsl_to_point(hsl, true);
+        assert!(close(point_to_hsl(point, true), hsl), "{point:?}");
+        for inverted in [false, true] {
+            let point = hsl_to_point_lightness_z(hsl, inverted);
+            assert!(close(point_to_hsl_lightness_z(point, inverted), hsl), "{point:?}");
+        }
+    }
+
+    #[test]
+    fn lines_include_both_ends() {
+        let p1 = Vector3(0.0, 0.25, 1.0);
+        let p2 = Vector3(1.0, 0.75, 0.0);
+        let line = vectors_on_line(p1, p2, Some(5), [false; 3], None, None, None);
+        assert_eq!(line.len(), 5);
+        assert_eq!((line[0], line[4]), (p1, p2));
+        assert_eq!(line[2], Vector3(0.5, 0.5, 0.5));
+        let eased = Some(PositionScale::Quadratic);
+        assert_eq!(
+            vectors_on_line(p1, p2, Some(1), [false; 3], eased, eased, eased),
+            [p1]
+        );
+    }
+
+    #[test]
+    fn hue_distance_wraps_around() {
+        let red = PartialVector3(Some(350.0), None, None);
+        let orange = PartialVector3(Some(20.0), None, None);
+        assert!((distance(red, orange, true) - 30.0 / 360.0).abs() < 1e-6);
+        assert_eq!(distance(red, orange, false), 330.0);
+    }
+}
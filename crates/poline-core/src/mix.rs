@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color_space::{
+        hsl_to_oklab, hsl_to_rgb, linear_to_srgb, oklab_to_hsl, rgb_to_hsl, srgb_to_linear,
+    },
+    types::Vector3,
+};
+
+/// The color space two colors are interpolated in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MixSpace {
+    /// Hue takes the shorter way around the wheel
+    Hsl,
+    #[default]
+    Oklab,
+    LinearRgb,
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_vector3(a: Vector3, b: Vector3, t: f32) -> Vector3 {
+    Vector3(lerp(a.0, b.0, t), lerp(a.1, b.1, t), lerp(a.2, b.2, t))
+}
+
+///
+/// Mixes two HSL colors, `t` = 0 returns `a` and `t` = 1 returns `b`
+///
+/// mix([0, 1, 0.5], [120, 1, 0.5], 0.5, MixSpace::Hsl); // [60, 1, 0.5]
+///
+pub fn mix(a: Vector3, b: Vector3, t: f32, space: MixSpace) -> Vector3 {
+    let t = t.clamp(0.0, 1.0);
+    match space {
+        MixSpace::Hsl => {
+            let mut delta = (b.0 - a.0).rem_euclid(360.0);
+            if delta > 180.0 {
+                delta -= 360.0;
+            }
+            Vector3(
+                (a.0 + delta * t).rem_euclid(360.0),
+                lerp(a.1, b.1, t),
+                lerp(a.2, b.2, t),
+            )
+        }
+        MixSpace::Oklab => oklab_to_hsl(lerp_vector3(hsl_to_oklab(a), hsl_to_oklab(b), t)),
+        MixSpace::LinearRgb => {
+            let a = srgb_to_linear(hsl_to_rgb(a));
+            let b = srgb_to_linear(hsl_to_rgb(b));
+            rgb_to_hsl(linear_to_srgb(lerp_vector3(a, b, t)))
+        }
+    }
+}
+
+///
+/// The straight line distance between two HSL colors in `space`. For `Hsl`
+/// the colors are placed in the cylinder with saturation as the radius.
+///
+pub fn color_distance(a: Vector3, b: Vector3, space: MixSpace) -> f32 {
+    let (a, b) = match space {
+        MixSpace::Hsl => {
+            let cartesian = |Vector3(h, s, l): Vector3| {
+                let (sin, cos) = h.to_radians().sin_cos();
+                Vector3(s * cos, s * sin, l)
+            };
+            (cartesian(a), cartesian(b))
+        }
+        MixSpace::Oklab => (hsl_to_oklab(a), hsl_to_oklab(b)),
+        MixSpace::LinearRgb => (srgb_to_linear(hsl_to_rgb(a)), srgb_to_linear(hsl_to_rgb(b))),
+    };
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixes_hue_the_short_way() {
+        let mixed = mix(
+            Vector3(350.0, 1.0, 0.5),
+            Vector3(30.0, 0.0, 0.5),
+            0.5,
+            MixSpace::Hsl,
+        );
+        assert!((mixed.0 - 10.0).abs() < 1e-3);
+        assert!((mixed.1 - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mix_endpoints_are_inputs() {
+        let a = Vector3(200.0, 0.5, 0.4);
+        let b = Vector3(20.0, 0.8, 0.6);
+        for space in [MixSpace::Oklab, MixSpace::LinearRgb] {
+            let start = mix(a, b, 0.0, space);
+            assert!((start.0 - a.0).abs() < 0.5 && (start.2 - a.2).abs() < 1e-2);
+        }
+    }
+}
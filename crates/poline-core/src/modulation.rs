@@ -0,0 +1,196 @@
+//! Binding external signals, like audio levels, to palette parameters.
+//!
+//! Each binding maps a normalized 0..1 signal onto a parameter range and
+//! smooths it with separate attack and release times, so a palette follows
+//! the beat without flickering.
+//!
+//! ```
+//! # use poline_core::{Modulation, ModulationTarget, Poline, Vector2};
+//! # let (poline, level, dt) = (Poline::default(), 0.8, 1.0 / 60.0);
+//! let mut modulation = Modulation::new();
+//! let bass = modulation.bind(ModulationTarget::HueOffset, Vector2(0.0, 40.0), 0.05, 0.5);
+//! // every frame
+//! modulation.update(bass, level, dt);
+//! let frame = modulation.apply(&poline);
+//! # assert_ne!(frame.anchors()[0].color, poline.anchors()[0].color);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{types::Vector2, types::Vector3, Poline};
+
+/// The palette parameters a signal can drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModulationTarget {
+    /// Degrees added to every anchor's hue, bindings add up
+    HueOffset,
+    /// Factor on every anchor's saturation, bindings multiply
+    SaturationScale,
+    /// Factor on every anchor's distance from the center of the color
+    /// wheel, bindings multiply
+    AnchorRadius,
+}
+
+/// One-pole smoothing with separate rise and fall times
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Smoother {
+    /// Seconds to cover about 63% of a rise
+    pub attack: f32,
+    /// Seconds to cover about 63% of a fall
+    pub release: f32,
+    value: f32,
+}
+
+impl Smoother {
+    pub fn new(attack: f32, release: f32) -> Self {
+        Self {
+            attack,
+            release,
+            value: 0.0,
+        }
+    }
+
+    /// Moves towards `input` by `dt` seconds worth and returns the result
+    pub fn update(&mut self, input: f32, dt: f32) -> f32 {
+        let time = if input > self.value {
+            self.attack
+        } else {
+            self.release
+        };
+        if time <= 0.0 {
+            self.value = input;
+        } else {
+            self.value += (input - self.value) * (1.0 - (-dt / time).exp());
+        }
+        self.value
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Binding {
+    target: ModulationTarget,
+    /// Parameter values at signal 0 and signal 1
+    range: Vector2,
+    smoother: Smoother,
+}
+
+impl Binding {
+    fn value(&self) -> f32 {
+        self.range.0 + self.smoother.value() * (self.range.1 - self.range.0)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Modulation {
+    bindings: Vec<Binding>,
+}
+
+impl Modulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds a new signal to `target`, mapping 0..1 onto `range` with the
+    /// given smoothing. Returns the index to `update` the signal with.
+    pub fn bind(
+        &mut self,
+        target: ModulationTarget,
+        range: Vector2,
+        attack: f32,
+        release: f32,
+    ) -> usize {
+        self.bindings.push(Binding {
+            target,
+            range,
+            smoother: Smoother::new(attack, release),
+        });
+        self.bindings.len() - 1
+    }
+
+    /// Feeds the latest reading of the signal bound at `binding`, clamped to
+    /// 0..1, `dt` seconds after the previous one
+    pub fn update(&mut self, binding: usize, signal: f32, dt: f32) {
+        if let Some(binding) = self.bindings.get_mut(binding) {
+            binding.smoother.update(signal.clamp(0.0, 1.0), dt);
+        }
+    }
+
+    /// The current, smoothed value of the parameter bound at `binding`
+    pub fn value(&self, binding: usize) -> Option<f32> {
+        self.bindings.get(binding).map(Binding::value)
+    }
+
+    /// A copy of `base` with every binding applied
+    pub fn apply(&self, base: &Poline) -> Poline {
+        let (mut hue, mut saturation, mut radius) = (0.0, 1.0, 1.0);
+        for binding in &self.bindings {
+            match binding.target {
+                ModulationTarget::HueOffset => hue += binding.value(),
+                ModulationTarget::SaturationScale => saturation *= binding.value(),
+                ModulationTarget::AnchorRadius => radius *= binding.value(),
+            }
+        }
+
+        let mut frame = base.clone();
+        for point in &mut frame.anchor_points {
+            if radius != 1.0 {
+                let Vector3(x, y, z) = point.position();
+                point.set_position(Vector3(
+                    (0.5 + (x - 0.5) * radius).clamp(0.0, 1.0),
+                    (0.5 + (y - 0.5) * radius).clamp(0.0, 1.0),
+                    z,
+                ));
+            }
+            let Vector3(h, s, l) = point.color;
+            point.set_hsl(Vector3(
+                (h + hue).rem_euclid(360.0),
+                (s * saturation).clamp(0.0, 1.0),
+                l,
+            ));
+        }
+        frame.update_anchor_pairs();
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolineOptions;
+
+    #[test]
+    fn smoothing_rises_fast_and_falls_slowly() {
+        let mut smoother = Smoother::new(0.01, 1.0);
+        assert!(smoother.update(1.0, 0.05) > 0.99);
+        let after_fall = smoother.update(0.0, 0.05);
+        assert!(after_fall > 0.9 && after_fall < 1.0);
+    }
+
+    #[test]
+    fn signals_drive_the_palette() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.5, 0.3), Vector3(200.0, 0.5, 0.7)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let mut modulation = Modulation::new();
+        let hue = modulation.bind(ModulationTarget::HueOffset, Vector2(0.0, 40.0), 0.0, 0.0);
+        let saturation = modulation.bind(
+            ModulationTarget::SaturationScale,
+            Vector2(1.0, 0.5),
+            0.0,
+            0.0,
+        );
+        assert_eq!(modulation.apply(&poline), poline);
+
+        modulation.update(hue, 0.5, 0.016);
+        modulation.update(saturation, 2.0, 0.016);
+        assert_eq!(modulation.value(hue), Some(20.0));
+        let Vector3(h, s, _) = modulation.apply(&poline).anchors()[0].color;
+        assert!((h - 40.0).abs() < 1e-3 && (s - 0.25).abs() < 1e-3);
+    }
+}
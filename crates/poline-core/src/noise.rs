@@ -0,0 +1,167 @@
+//! Gently evolving palettes for ambient backgrounds. Every anchor drifts
+//! around its resting position along seeded simplex noise, so the same seed
+//! and time always give the same frame and consecutive frames change
+//! smoothly.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{types::Vector3, Poline};
+
+/// How far and how fast anchors drift in [`Poline::animate_noise`]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NoiseConfig {
+    pub seed: u32,
+    /// Largest offset of an anchor on each axis of the unit cube
+    pub amplitude: f32,
+    /// Noise features passed per unit of time, lower is calmer
+    pub speed: f32,
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            amplitude: 0.05,
+            speed: 0.1,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl NoiseConfig {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(seed: u32, amplitude: f32, speed: f32) -> NoiseConfig {
+        NoiseConfig {
+            seed,
+            amplitude,
+            speed,
+        }
+    }
+}
+
+/// 2D simplex noise over a seeded permutation table
+struct Simplex {
+    permutation: [u8; 512],
+}
+
+const GRADIENTS: [(f32, f32); 8] = [
+    (1.0, 1.0),
+    (-1.0, 1.0),
+    (1.0, -1.0),
+    (-1.0, -1.0),
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, 1.0),
+    (0.0, -1.0),
+];
+
+impl Simplex {
+    fn new(seed: u32) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        // xorshift32 driven Fisher-Yates, zero is not a valid state
+        let mut state = seed.wrapping_mul(0x9e37_79b9) | 1;
+        for i in (1..table.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            table.swap(i, state as usize % (i + 1));
+        }
+        Self {
+            permutation: std::array::from_fn(|i| table[i % 256]),
+        }
+    }
+
+    /// Noise in -1..1
+    fn noise(&self, x: f32, y: f32) -> f32 {
+        let skew = (3.0_f32.sqrt() - 1.0) / 2.0;
+        let unskew = (3.0 - 3.0_f32.sqrt()) / 6.0;
+
+        let s = (x + y) * skew;
+        let (i, j) = ((x + s).floor(), (y + s).floor());
+        let t = (i + j) * unskew;
+        let (x0, y0) = (x - (i - t), y - (j - t));
+        let (i1, j1) = if x0 > y0 { (1.0, 0.0) } else { (0.0, 1.0) };
+        let corners = [
+            (x0, y0, 0.0, 0.0),
+            (x0 - i1 + unskew, y0 - j1 + unskew, i1, j1),
+            (x0 - 1.0 + 2.0 * unskew, y0 - 1.0 + 2.0 * unskew, 1.0, 1.0),
+        ];
+
+        let (ii, jj) = (i.rem_euclid(256.0) as usize, j.rem_euclid(256.0) as usize);
+        let total: f32 = corners
+            .iter()
+            .map(|&(dx, dy, oi, oj)| {
+                let falloff = 0.5 - dx * dx - dy * dy;
+                if falloff <= 0.0 {
+                    return 0.0;
+                }
+                let hash = self.permutation
+                    [ii + oi as usize + self.permutation[jj + oj as usize] as usize];
+                let (gx, gy) = GRADIENTS[hash as usize % GRADIENTS.len()];
+                falloff.powi(4) * (gx * dx + gy * dy)
+            })
+            .sum();
+        // Scales the theoretical maximum of the sum to 1
+        (70.0 * total).clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    /// The palette at `time` with every anchor moved along seeded noise,
+    /// at most `config.amplitude` per axis and kept inside the unit cube.
+    /// The palette itself is left alone, call this with the resting palette
+    /// for every frame.
+    pub fn animate_noise(&self, config: &NoiseConfig, time: f32) -> Poline {
+        let simplex = Simplex::new(config.seed);
+        let mut frame = self.clone();
+        for (index, point) in frame.anchor_points.iter_mut().enumerate() {
+            let offset = |axis: usize| {
+                let lane = index as f32 * 7.31 + axis as f32 * 101.7;
+                config.amplitude * simplex.noise(time * config.speed, lane)
+            };
+            let Vector3(x, y, z) = point.position();
+            point.set_position(Vector3(
+                (x + offset(0)).clamp(0.0, 1.0),
+                (y + offset(1)).clamp(0.0, 1.0),
+                (z + offset(2)).clamp(0.0, 1.0),
+            ));
+        }
+        frame.update_anchor_pairs();
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolineOptions;
+
+    #[test]
+    fn noise_is_seeded_bounded_and_smooth() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.6, 0.3), Vector3(200.0, 0.6, 0.7)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let config = NoiseConfig::new(7, 0.05, 0.5);
+        let a = poline.animate_noise(&config, 3.0);
+        assert_eq!(a, poline.animate_noise(&config, 3.0));
+        assert_ne!(
+            a,
+            poline.animate_noise(&NoiseConfig { seed: 8, ..config }, 3.0)
+        );
+
+        let next = poline.animate_noise(&config, 3.01);
+        for (i, anchor) in a.anchors().iter().enumerate() {
+            let rest = poline.anchors()[i].position();
+            let moved = anchor.position();
+            assert!((moved.0 - rest.0).abs() <= 0.05 + 1e-6);
+            assert!((moved.2 - rest.2).abs() <= 0.05 + 1e-6);
+            assert!((next.anchors()[i].x - moved.0).abs() < 0.01);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Batches of edits applied as one. An editor collects the changes of a
+//! gesture as [`PolineOp`]s and hands them over in a single call, so the
+//! palette is sampled once and the JS bindings cross into wasm once.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ColorPointCollection, Poline, PolineErrors, PositionScale, Vector3};
+
+/// One edit of a batch, tagged by `op` in JSON, e.g.
+/// `{ "op": "shiftHue", "degrees": 30 }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PolineOp {
+    /// Adds an anchor at `index`, or at the end, from a position or color
+    AddAnchor {
+        #[serde(default)]
+        index: Option<usize>,
+        #[serde(default)]
+        xyz: Option<Vector3>,
+        #[serde(default)]
+        color: Option<Vector3>,
+    },
+    RemoveAnchor { index: usize },
+    /// Moves the anchor at `index` to a new position or color
+    UpdateAnchor {
+        index: usize,
+        #[serde(default)]
+        xyz: Option<Vector3>,
+        #[serde(default)]
+        color: Option<Vector3>,
+    },
+    ShiftHue { degrees: f32 },
+    /// Position function numbers per axis, axes left out keep theirs
+    SetEasing {
+        #[serde(default)]
+        x: Option<usize>,
+        #[serde(default)]
+        y: Option<usize>,
+        #[serde(default)]
+        z: Option<usize>,
+    },
+}
+
+impl Poline {
+    /// Applies `ops` in order and samples the palette once. Either every
+    /// op applies or, when one fails, the palette is left as it was.
+    pub fn apply_ops(&mut self, ops: &[PolineOp]) -> Result<(), PolineErrors> {
+        let mut edited = self.clone();
+        for op in ops {
+            edited.apply_op(op)?;
+        }
+        edited.recompute();
+        *self = edited;
+        Ok(())
+    }
+
+    fn apply_op(&mut self, op: &PolineOp) -> Result<(), PolineErrors> {
+        match *op {
+            PolineOp::AddAnchor { index, xyz, color } => {
+                if xyz.is_none() && color.is_none() {
+                    return Err(PolineErrors::InvalidOption);
+                }
+                let initial = ColorPointCollection {
+                    xyz,
+                    color,
+                    inverted_lightness: self.inverted_lightness,
+                    model: self.color_model,
+                };
+                self.add_anchor_point(initial, index)?;
+            }
+            PolineOp::RemoveAnchor { index } => self.remove_anchor_point_at_index(index)?,
+            PolineOp::UpdateAnchor { index, xyz, color } => {
+                let initial = ColorPointCollection {
+                    xyz,
+                    color,
+                    inverted_lightness: self.inverted_lightness,
+                    model: self.color_model,
+                };
+                self.update_anchor_point_at_index(index, initial)?;
+            }
+            PolineOp::ShiftHue { degrees } => self.shift_hue(degrees),
+            PolineOp::SetEasing { x, y, z } => {
+                let scale = |id| PositionScale::from_id(id).ok_or(PolineErrors::InvalidOption);
+                if let Some(x) = x {
+                    self.position_function_x = scale(x)?;
+                }
+                if let Some(y) = y {
+                    self.position_function_y = scale(y)?;
+                }
+                if let Some(z) = z {
+                    self.position_function_z = scale(z)?;
+                }
+                self.update_anchor_pairs();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_a_batch_at_once() {
+        let ops: Vec<PolineOp> = serde_json::from_str(
+            r#"[
+                { "op": "addAnchor", "color": [120, 0.5, 0.5] },
+                { "op": "updateAnchor", "index": 0, "xyz": [0.2, 0.3, 0.4] },
+                { "op": "shiftHue", "degrees": 30 },
+                { "op": "setEasing", "y": 2 }
+            ]"#,
+        )
+        .unwrap();
+        let mut batched = Poline::default();
+        let mut stepped = batched.clone();
+        batched.apply_ops(&ops).unwrap();
+        assert!(!batched.needs_update());
+
+        let initial = |xyz, color| ColorPointCollection {
+            xyz,
+            color,
+            inverted_lightness: stepped.inverted_lightness(),
+            model: stepped.color_model(),
+        };
+        let (add, update) = (
+            initial(None, Some(Vector3(120.0, 0.5, 0.5))),
+            initial(Some(Vector3(0.2, 0.3, 0.4)), None),
+        );
+        stepped.add_anchor_point(add, None).unwrap();
+        stepped.update_anchor_point_at_index(0, update).unwrap();
+        stepped.shift_hue(30.0);
+        stepped.set_position_fn_y(2);
+        assert_eq!(batched.colors_vec(), stepped.colors_vec());
+        assert_eq!(batched.position_function_y, PositionScale::Cubic);
+    }
+
+    #[test]
+    fn leaves_the_palette_alone_when_an_op_fails() {
+        let mut poline = Poline::default();
+        let before = poline.colors_vec();
+        let ops = [
+            PolineOp::ShiftHue { degrees: 90.0 },
+            PolineOp::RemoveAnchor { index: 9 },
+        ];
+        assert!(matches!(poline.apply_ops(&ops), Err(PolineErrors::IndexOutOfBounds)));
+        assert_eq!(poline.colors_vec(), before);
+
+        let easing = [PolineOp::SetEasing {
+            x: None,
+            y: Some(PositionScale::ALL.len()),
+            z: None,
+        }];
+        assert!(matches!(poline.apply_ops(&easing), Err(PolineErrors::InvalidOption)));
+        assert_eq!(poline.colors_vec(), before);
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    mix::{color_distance, MixSpace},
+    Poline,
+};
+
+/// How much longer the tour gets when `order[i..=j]` is reversed: only the
+/// edges into `order[i]` and out of `order[j]` change. `i` is at least 1,
+/// the edge out of the last point wraps back to the first when `closed`.
+fn reversal_delta(
+    order: &[usize],
+    distances: &[Vec<f32>],
+    closed: bool,
+    i: usize,
+    j: usize,
+) -> f32 {
+    let (before, first, last) = (order[i - 1], order[i], order[j]);
+    let after = match order.get(j + 1) {
+        Some(&after) => Some(after),
+        None if closed => Some(order[0]),
+        None => None,
+    };
+    let outer = after.map_or(0.0, |after| distances[first][after] - distances[last][after]);
+    distances[before][last] - distances[before][first] + outer
+}
+
+/// A short order through all points starting at point 0: nearest neighbour
+/// construction improved with 2-opt moves until none helps
+fn shortest_order(distances: &[Vec<f32>], closed: bool) -> Vec<usize> {
+    let n = distances.len();
+    let mut order = vec![0];
+    let mut remaining: Vec<usize> = (1..n).collect();
+    while !remaining.is_empty() {
+        let last = order[order.len() - 1];
+        let (position, _) = remaining
+            .iter()
+            .enumerate()
+            .min_by(|(_, &a), (_, &b)| distances[last][a].total_cmp(&distances[last][b]))
+            .unwrap();
+        order.push(remaining.swap_remove(position));
+    }
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 1..n {
+            for j in i + 1..n {
+                if reversal_delta(&order, distances, closed, i, j) < -1e-6 {
+                    order[i..=j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+    order
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    /// Reorders the anchors so the path through them is as short as
+    /// possible in OKLab, keeping the first anchor first. Anchors from
+    /// unordered sources, like colors picked from an image, then give a
+    /// smooth palette instead of one jumping back and forth.
+    pub fn optimize_anchor_order(&mut self) {
+        let colors: Vec<_> = self.anchor_points.iter().map(|point| point.color).collect();
+        let distances: Vec<Vec<f32>> = colors
+            .iter()
+            .map(|&a| {
+                colors
+                    .iter()
+                    .map(|&b| color_distance(a, b, MixSpace::Oklab))
+                    .collect()
+            })
+            .collect();
+        let order = shortest_order(&distances, self.connect_last_and_first_anchor);
+        self.anchor_points = order.iter().map(|&i| self.anchor_points[i]).collect();
+        self.anchor_ids = order.iter().map(|&i| self.anchor_ids[i]).collect();
+        self.update_anchor_pairs();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::Vector3, PolineOptions};
+
+    fn tour_length(order: &[usize], distances: &[Vec<f32>], closed: bool) -> f32 {
+        let open: f32 = order.windows(2).map(|w| distances[w[0]][w[1]]).sum();
+        match (closed, order.first(), order.last()) {
+            (true, Some(&first), Some(&last)) => open + distances[last][first],
+            _ => open,
+        }
+    }
+
+    #[test]
+    fn reversal_delta_matches_the_tour_length() {
+        let points = [0.0_f32, 0.9, 0.2, 0.7, 0.4, 1.0];
+        let distances: Vec<Vec<f32>> = points
+            .iter()
+            .map(|a| points.iter().map(|b| (a - b).abs()).collect())
+            .collect();
+        let order: Vec<usize> = (0..points.len()).collect();
+        for closed in [false, true] {
+            let length = tour_length(&order, &distances, closed);
+            for i in 1..points.len() {
+                for j in i + 1..points.len() {
+                    let mut reversed = order.clone();
+                    reversed[i..=j].reverse();
+                    let expected = tour_length(&reversed, &distances, closed) - length;
+                    let delta = reversal_delta(&order, &distances, closed, i, j);
+                    assert!((delta - expected).abs() < 1e-5, "{i}..={j} {closed}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn orders_anchors_along_the_shortest_path() {
+        let gray = |l: f32| Vector3(0.0, 0.0, l);
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                gray(0.0),
+                gray(0.75),
+                gray(0.25),
+                gray(1.0),
+                gray(0.5),
+            ]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let ids_by_lightness = [0, 2, 4, 1, 3];
+        poline.optimize_anchor_order();
+        let lightness: Vec<f32> = poline.anchors().iter().map(|point| point.color.2).collect();
+        for (l, expected) in lightness.iter().zip([0.0, 0.25, 0.5, 0.75, 1.0]) {
+            assert!((l - expected).abs() < 1e-3, "{lightness:?}");
+        }
+        assert_eq!(poline.anchor_ids(), ids_by_lightness);
+    }
+}
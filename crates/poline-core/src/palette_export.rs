@@ -0,0 +1,444 @@
+//! Palette files for pixel art tools, e.g. as shared on Lospec, and for the
+//! open source design tools Krita and Inkscape, and swatches for Procreate.
+//! Formats that hold a limited number of colors take a [`LimitPolicy`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    color_space::{rgb_to_hsl, rgb_to_rgb8},
+    export::escape_xml,
+    types::Vector3,
+    utils::crc32,
+    Poline,
+};
+
+/// Paint.NET palettes hold at most this many colors
+pub const PAINT_NET_MAX_COLORS: usize = 96;
+
+/// Procreate swatch sets hold at most this many colors
+pub const PROCREATE_MAX_COLORS: usize = 30;
+
+/// What an export does with more colors than its format holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitPolicy {
+    /// Keep the first colors and drop the rest
+    #[default]
+    Truncate,
+    /// Resample the whole gradient down to the limit
+    Quantize,
+    /// Spread the colors over as many files as it takes
+    Paginate,
+}
+
+/// The files an export with a color limit wrote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitedExport<T> {
+    /// One file, or one per page with [`LimitPolicy::Paginate`]
+    pub pages: Vec<T>,
+    /// Colors in the palette
+    pub colors: usize,
+    /// Colors written over all pages
+    pub written: usize,
+    /// Colors a page of the format holds
+    pub limit: usize,
+    pub policy: LimitPolicy,
+}
+
+impl<T> LimitedExport<T> {
+    /// Whether every color of the palette was written as it is
+    pub fn is_complete(&self) -> bool {
+        self.colors <= self.limit || self.policy == LimitPolicy::Paginate
+    }
+
+    fn map<U>(self, f: impl FnMut(T) -> U) -> LimitedExport<U> {
+        LimitedExport {
+            pages: self.pages.into_iter().map(f).collect(),
+            colors: self.colors,
+            written: self.written,
+            limit: self.limit,
+            policy: self.policy,
+        }
+    }
+}
+
+/// Hue, saturation and brightness in 0..1
+fn rgb8_to_hsb(rgb: [u8; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(|c| c as f32 / 255.0);
+    let hue = rgb_to_hsl(Vector3(r, g, b)).0 / 360.0;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+    [hue, saturation, max]
+}
+
+/// Reduces the palette for pixel art exports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PixelPaletteOptions {
+    /// Resample the gradient to at most this many colors
+    pub max_colors: Option<usize>,
+    /// Quantize each channel to this many bits (1 to 8), e.g. 5 for the
+    /// 15-bit color of retro consoles. Neighbouring colors that become the
+    /// same are merged.
+    pub bits_per_channel: Option<u8>,
+}
+
+fn quantize(channel: u8, bits: u8) -> u8 {
+    let levels = (1_u32 << bits.clamp(1, 8)) - 1;
+    let level = (channel as u32 * levels + 127) / 255;
+    ((level * 255 + levels / 2) / levels) as u8
+}
+
+impl Poline {
+    /// The 8-bit sRGB colors of a pixel art export
+    pub fn pixel_palette(&self, options: PixelPaletteOptions) -> Vec<[u8; 3]> {
+        let rgb: Vec<Vector3> = match options.max_colors {
+            Some(max) if max < self.output_points().len() => self
+                .colormap()
+                .clamped()
+                .discrete(max)
+                .into_iter()
+                .map(|[r, g, b]| Vector3(r, g, b))
+                .collect(),
+            _ => self.colors_rgb(),
+        };
+        let mut colors: Vec<[u8; 3]> = rgb.into_iter().map(rgb_to_rgb8).collect();
+        if let Some(bits) = options.bits_per_channel {
+            colors = colors
+                .into_iter()
+                .map(|color| color.map(|channel| quantize(channel, bits)))
+                .collect();
+            colors.dedup();
+        }
+        colors
+    }
+
+    /// A Lospec style `.hex` file, one `rrggbb` color per line
+    pub fn to_hex_palette(&self, options: PixelPaletteOptions) -> String {
+        self.pixel_palette(options)
+            .into_iter()
+            .map(|[r, g, b]| format!("{r:02x}{g:02x}{b:02x}\n"))
+            .collect()
+    }
+
+    /// The colors of a pixel art export fitted into pages of at most
+    /// `limit` colors by `policy`
+    pub fn limited_pixel_palette(
+        &self,
+        options: PixelPaletteOptions,
+        limit: usize,
+        policy: LimitPolicy,
+    ) -> LimitedExport<Vec<[u8; 3]>> {
+        let limit = limit.max(1);
+        let mut colors = self.pixel_palette(options);
+        let count = colors.len();
+        let pages = if count <= limit {
+            vec![colors]
+        } else {
+            match policy {
+                LimitPolicy::Truncate => {
+                    colors.truncate(limit);
+                    vec![colors]
+                }
+                LimitPolicy::Quantize => vec![self.pixel_palette(PixelPaletteOptions {
+                    max_colors: Some(limit),
+                    ..options
+                })],
+                LimitPolicy::Paginate => colors.chunks(limit).map(<[_]>::to_vec).collect(),
+            }
+        };
+        LimitedExport {
+            written: pages.iter().map(Vec::len).sum(),
+            pages,
+            colors: count,
+            limit,
+            policy,
+        }
+    }
+
+    /// A Paint.NET `.txt` palette of `AARRGGBB` lines, cut off after
+    /// [`PAINT_NET_MAX_COLORS`]
+    pub fn to_paint_net_palette(&self, options: PixelPaletteOptions) -> String {
+        let mut export = self.to_paint_net_pages(options, LimitPolicy::Truncate);
+        export.pages.remove(0)
+    }
+
+    /// Paint.NET palettes of at most [`PAINT_NET_MAX_COLORS`] each
+    pub fn to_paint_net_pages(
+        &self,
+        options: PixelPaletteOptions,
+        policy: LimitPolicy,
+    ) -> LimitedExport<String> {
+        let export = self.limited_pixel_palette(options, PAINT_NET_MAX_COLORS, policy);
+        export.map(|colors| {
+            let mut palette = String::from(
+                "; paint.net Palette File\n; Generated by poline\n\
+                 ; Colors are written as AARRGGBB\n",
+            );
+            palette.push_str(&self.provenance_comment("; "));
+            for [r, g, b] in colors {
+                palette.push_str(&format!("FF{r:02X}{g:02X}{b:02X}\n"));
+            }
+            palette
+        })
+    }
+
+    /// Procreate `.swatches` files of at most [`PROCREATE_MAX_COLORS`]
+    /// each, pages after the first are named `name 2`, `name 3` and so on
+    pub fn to_procreate_swatches(
+        &self,
+        name: &str,
+        policy: LimitPolicy,
+    ) -> LimitedExport<Vec<u8>> {
+        let options = PixelPaletteOptions::default();
+        let export = self.limited_pixel_palette(options, PROCREATE_MAX_COLORS, policy);
+        let mut page = 0;
+        export.map(|colors| {
+            page += 1;
+            let swatches: Vec<_> = colors
+                .into_iter()
+                .map(|rgb| {
+                    let [hue, saturation, brightness] = rgb8_to_hsb(rgb);
+                    json!({
+                        "hue": hue,
+                        "saturation": saturation,
+                        "brightness": brightness,
+                        "alpha": 1,
+                        "colorSpace": 0,
+                    })
+                })
+                .collect();
+            let title = if page == 1 {
+                name.to_string()
+            } else {
+                format!("{name} {page}")
+            };
+            let json = json!([{ "name": title, "swatches": swatches }]).to_string();
+            write_stored_zip(&[("Swatches.json", json.as_bytes())])
+        })
+    }
+}
+
+const KRITA_MIMETYPE: &str = "application/x-krita-palette";
+
+/// Writes an uncompressed zip archive, the files keep their order so that a
+/// `mimetype` entry can come first as OpenDocument style formats require.
+fn write_stored_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for &(name, contents) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(contents);
+        let size = contents.len() as u32;
+        // Version 1.0, no flags, stored, 1980-01-01 00:00
+        let common = |out: &mut Vec<u8>| {
+            out.extend_from_slice(&10_u16.to_le_bytes());
+            out.extend_from_slice(&0_u16.to_le_bytes());
+            out.extend_from_slice(&0_u16.to_le_bytes());
+            out.extend_from_slice(&0_u16.to_le_bytes());
+            out.extend_from_slice(&0x21_u16.to_le_bytes());
+            out.extend_from_slice(&crc.to_le_bytes());
+            out.extend_from_slice(&size.to_le_bytes());
+            out.extend_from_slice(&size.to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0_u16.to_le_bytes());
+        };
+
+        archive.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+        common(&mut archive);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(contents);
+
+        directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+        directory.extend_from_slice(&10_u16.to_le_bytes());
+        common(&mut directory);
+        // No comment, disk 0, no attributes
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = archive.len() as u32;
+    let count = files.len() as u16;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&count.to_le_bytes());
+    archive.extend_from_slice(&count.to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0_u16.to_le_bytes());
+    archive
+}
+
+impl Poline {
+    /// The output colors with a display name each. Colors sitting on an
+    /// anchor are named after it, the rest after their position in the
+    /// palette.
+    fn named_colors(&self, name: &str) -> Vec<([u8; 3], String)> {
+        let anchors = self.anchor_points.len();
+        let last_segment = self.segment_count().saturating_sub(1);
+        let indices = self.flattened_indices();
+        let colors = self.colors_rgb();
+        colors
+            .into_iter()
+            .zip(indices)
+            .enumerate()
+            .map(|(position, (rgb, (segment, idx)))| {
+                let range = self.sampled_points().segment_range(segment);
+                let anchor = if idx == range.start {
+                    Some(segment)
+                } else if idx + 1 == range.end && segment == last_segment {
+                    Some((segment + 1) % anchors)
+                } else {
+                    None
+                };
+                let label = match anchor {
+                    Some(anchor) => format!("{name} anchor {}", anchor + 1),
+                    None => format!("{name} {}", position + 1),
+                };
+                (rgb_to_rgb8(rgb), label)
+            })
+            .collect()
+    }
+
+    /// A GIMP palette with a name per color, as Inkscape reads them from its
+    /// `palettes` folder
+    pub fn to_inkscape_gpl(&self, name: &str) -> String {
+        let colors = self.named_colors(name);
+        let mut palette = format!(
+            "GIMP Palette\nName: {name}\nColumns: {}\n#\n{}",
+            colors.len().min(16),
+            self.provenance_comment("# ")
+        );
+        for ([r, g, b], label) in colors {
+            palette.push_str(&format!("{r:>3} {g:>3} {b:>3}\t{label}\n"));
+        }
+        palette
+    }
+
+    /// A Krita `.kpl` palette, a zip archive holding the color set as XML
+    pub fn to_krita_palette(&self, name: &str) -> Vec<u8> {
+        let colors = self.named_colors(name);
+        let columns = colors.len().clamp(1, 16);
+        let format = self.float_format(6);
+        let mut colorset = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ColorSet version=\"2.0\" \
+             readonly=\"false\" columns=\"{columns}\" rows=\"{}\" name=\"{}\" comment=\"\">\n",
+            colors.len().div_ceil(columns),
+            escape_xml(name)
+        );
+        for (index, ([r, g, b], label)) in colors.into_iter().enumerate() {
+            colorset.push_str(&format!(
+                "  <ColorSetEntry name=\"{}\" id=\"{}\" spot=\"false\" bitdepth=\"U8\">\n    \
+                 <RGB space=\"sRGB-elle-V2-srgbtrc.icc\" r=\"{}\" g=\"{}\" b=\"{}\"/>\n    \
+                 <Position row=\"{}\" column=\"{}\"/>\n  </ColorSetEntry>\n",
+                escape_xml(&label),
+                index + 1,
+                format.format(r as f32 / 255.0),
+                format.format(g as f32 / 255.0),
+                format.format(b as f32 / 255.0),
+                index / columns,
+                index % columns,
+            ));
+        }
+        colorset.push_str("</ColorSet>\n");
+        let profiles = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Profiles/>\n";
+        write_stored_zip(&[
+            ("mimetype", KRITA_MIMETYPE.as_bytes()),
+            ("colorset.xml", colorset.as_bytes()),
+            ("profiles.xml", profiles.as_bytes()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolineOptions;
+
+    fn grays() -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            num_points: 8,
+            inverted_lightness: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn quantizes_to_even_levels() {
+        assert_eq!(quantize(0, 1), 0);
+        assert_eq!(quantize(200, 1), 255);
+        assert_eq!(quantize(100, 2), 85);
+        assert_eq!(quantize(123, 8), 123);
+    }
+
+    #[test]
+    fn writes_pixel_art_palettes() {
+        let hex = grays().to_hex_palette(PixelPaletteOptions::default());
+        assert_eq!(hex.lines().count(), 10);
+        assert_eq!(hex.lines().next(), Some("000000"));
+        assert_eq!(hex.lines().last(), Some("ffffff"));
+
+        let capped = PixelPaletteOptions {
+            max_colors: Some(4),
+            bits_per_channel: Some(1),
+        };
+        assert_eq!(grays().pixel_palette(capped), [[0, 0, 0], [255, 255, 255]]);
+
+        let paint_net = grays().to_paint_net_palette(PixelPaletteOptions::default());
+        assert!(paint_net.starts_with("; paint.net Palette File\n"));
+        assert!(paint_net.contains("\nFF000000\n"));
+        assert!(paint_net.ends_with("FFFFFFFF\n"));
+    }
+
+    #[test]
+    fn fits_colors_into_the_limit() {
+        let grays = grays();
+        let truncated = grays.limited_pixel_palette(Default::default(), 4, LimitPolicy::Truncate);
+        assert_eq!((truncated.pages.len(), truncated.written, truncated.colors), (1, 4, 10));
+        assert!(!truncated.is_complete());
+
+        let quantized = grays.limited_pixel_palette(Default::default(), 4, LimitPolicy::Quantize);
+        assert_eq!(quantized.pages[0].first(), Some(&[0, 0, 0]));
+        assert_eq!(quantized.pages[0].last(), Some(&[255, 255, 255]));
+        assert_eq!(quantized.written, 4);
+
+        let paginated = grays.limited_pixel_palette(Default::default(), 4, LimitPolicy::Paginate);
+        let sizes: Vec<usize> = paginated.pages.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [4, 4, 2]);
+        assert!(paginated.is_complete());
+
+        let swatches = grays.to_procreate_swatches("Grays", LimitPolicy::Paginate);
+        assert_eq!(swatches.pages.len(), 1);
+        assert!(swatches.is_complete());
+        assert!(swatches.pages[0].starts_with(b"PK"));
+        assert_eq!(rgb8_to_hsb([255, 0, 0]), [0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn inkscape_gpl_names_colors() {
+        let gpl = grays().to_inkscape_gpl("Grays");
+        assert!(gpl.starts_with("GIMP Palette\nName: Grays\n"));
+        assert!(gpl.contains("  0   0   0\tGrays anchor 1\n"));
+        assert!(gpl.contains("\tGrays 2\n"));
+        assert!(gpl.ends_with("255 255 255\tGrays anchor 2\n"));
+        assert_eq!(crate::import::read_gpl(&gpl).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn krita_palette_is_a_stored_zip() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        let kpl = grays().to_krita_palette("Grays");
+        assert_eq!(&kpl[..4], b"PK\x03\x04");
+        assert_eq!(&kpl[30..38], b"mimetype");
+        assert_eq!(
+            &kpl[38..38 + KRITA_MIMETYPE.len()],
+            KRITA_MIMETYPE.as_bytes()
+        );
+        let text = String::from_utf8_lossy(&kpl);
+        assert!(text.contains("<ColorSetEntry name=\"Grays anchor 2\""));
+        assert_eq!(&kpl[kpl.len() - 22..kpl.len() - 18], b"PK\x05\x06");
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    contrast::{contrast_ratio, WcagLevel},
+    types::Vector3,
+    Poline, PolineErrors, PolineOptions,
+};
+
+/// Contrast between one swatch of each of two palettes in a [`PolineSet`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossContrast {
+    pub first_index: usize,
+    pub second_index: usize,
+    pub contrast_ratio: f32,
+    pub wcag: WcagLevel,
+}
+
+/// Several named palettes generated together, like the light, dark and brand
+/// palettes of a design system. Names keep their insertion order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolineSet {
+    palettes: Vec<(String, Poline)>,
+}
+
+impl PolineSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a palette, returning the one it replaced under the same name
+    pub fn insert(&mut self, name: impl Into<String>, poline: Poline) -> Option<Poline> {
+        let name = name.into();
+        match self.get_mut(&name) {
+            Some(existing) => Some(std::mem::replace(existing, poline)),
+            None => {
+                self.palettes.push((name, poline));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Poline> {
+        let index = self.palettes.iter().position(|(n, _)| n == name)?;
+        Some(self.palettes.remove(index).1)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Poline> {
+        self.palettes
+            .iter()
+            .find_map(|(n, poline)| (n == name).then_some(poline))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Poline> {
+        self.palettes
+            .iter_mut()
+            .find_map(|(n, poline)| (n == name).then_some(poline))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.palettes.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Poline)> + '_ {
+        self.palettes
+            .iter()
+            .map(|(name, poline)| (name.as_str(), poline))
+    }
+
+    pub fn len(&self) -> usize {
+        self.palettes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.palettes.is_empty()
+    }
+
+    /// Rebuilds every palette from its own anchors after `f` has changed
+    /// its options, e.g. to give the whole set the same easing
+    pub fn set_shared_options(&mut self, f: impl Fn(&mut PolineOptions)) {
+        for (_, poline) in &mut self.palettes {
+            *poline = poline.with_options(&f);
+        }
+    }
+
+    pub fn shift_hue(&mut self, shift: f32) {
+        for (_, poline) in &mut self.palettes {
+            poline.shift_hue(shift);
+        }
+    }
+
+    /// Runs an exporter over every palette, keeping the names
+    ///
+    /// ```
+    /// # use poline_core::{Poline, PolineSet};
+    /// let mut set = PolineSet::new();
+    /// set.insert("brand", Poline::default());
+    /// let vega = set.export_all(Poline::to_vega_scheme_json);
+    /// assert_eq!(vega[0].0, "brand");
+    /// ```
+    pub fn export_all<T>(&self, export: impl Fn(&Poline) -> T) -> Vec<(String, T)> {
+        self.palettes
+            .iter()
+            .map(|(name, poline)| (name.clone(), export(poline)))
+            .collect()
+    }
+
+    /// A JSON array of `[name, options]` pairs, in insertion order
+    pub fn to_json(&self) -> String {
+        let palettes: Vec<(&str, PolineOptions)> = self
+            .palettes
+            .iter()
+            .map(|(name, poline)| (name.as_str(), poline.options()))
+            .collect();
+        serde_json::to_string(&palettes).expect("options always serialize")
+    }
+
+    /// Reads a set saved by [`PolineSet::to_json`]
+    pub fn from_json(json: &str) -> Result<Self, PolineErrors> {
+        let palettes: Vec<(String, PolineOptions)> =
+            serde_json::from_str(json).map_err(|_| PolineErrors::InvalidOption)?;
+        let mut set = Self::new();
+        for (name, options) in palettes {
+            set.insert(name, Poline::try_from_options(options.migrate()?)?);
+        }
+        Ok(set)
+    }
+
+    /// WCAG contrast between every swatch of `first` and every swatch of
+    /// `second`, `None` when either name is missing
+    pub fn cross_contrast(&self, first: &str, second: &str) -> Option<Vec<CrossContrast>> {
+        let colors = |poline: &Poline| -> Vec<Vector3> {
+            poline
+                .output_points()
+                .iter()
+                .map(|point| point.color)
+                .collect()
+        };
+        let a = colors(self.get(first)?);
+        let b = colors(self.get(second)?);
+        let mut pairs = Vec::with_capacity(a.len() * b.len());
+        for (first_index, &x) in a.iter().enumerate() {
+            for (second_index, &y) in b.iter().enumerate() {
+                let ratio = contrast_ratio(x, y);
+                pairs.push(CrossContrast {
+                    first_index,
+                    second_index,
+                    contrast_ratio: ratio,
+                    wcag: WcagLevel::from_ratio(ratio),
+                });
+            }
+        }
+        Some(pairs)
+    }
+
+    /// The lowest contrast pair between two palettes, e.g. to check that no
+    /// brand color disappears on the dark palette
+    pub fn min_cross_contrast(&self, first: &str, second: &str) -> Option<CrossContrast> {
+        self.cross_contrast(first, second)?
+            .into_iter()
+            .min_by(|a, b| a.contrast_ratio.total_cmp(&b.contrast_ratio))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PositionScale;
+
+    fn palette(lightness: f32) -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(20.0, 0.0, lightness),
+                Vector3(20.0, 0.0, lightness),
+            ]),
+            inverted_lightness: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn manages_named_palettes() {
+        let mut set = PolineSet::new();
+        assert!(set.insert("light", palette(0.95)).is_none());
+        assert!(set.insert("dark", palette(0.05)).is_none());
+        assert!(set.insert("light", palette(0.9)).is_some());
+        assert_eq!(set.names().collect::<Vec<_>>(), ["light", "dark"]);
+
+        set.set_shared_options(|options| {
+            options.position_function_x = Some(PositionScale::Linear);
+        });
+        set.shift_hue(10.0);
+        for (_, poline) in set.iter() {
+            assert_eq!(poline.position_functions().0, PositionScale::Linear);
+        }
+        assert_eq!(set.export_all(|poline| poline.anchors().len()).len(), 2);
+
+        let back = PolineSet::from_json(&set.to_json()).unwrap();
+        assert_eq!(back.names().collect::<Vec<_>>(), ["light", "dark"]);
+        assert_eq!(back.get("dark"), set.get("dark"));
+        let huge = set.to_json().replacen("\"numPoints\":4", "\"numPoints\":100000000", 1);
+        assert!(matches!(PolineSet::from_json(&huge), Err(PolineErrors::TooManyPoints)));
+        assert!(set.remove("dark").is_some());
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn checks_contrast_across_palettes() {
+        let mut set = PolineSet::new();
+        set.insert("light", palette(0.95));
+        set.insert("dark", palette(0.05));
+        let lowest = set.min_cross_contrast("light", "dark").unwrap();
+        assert!(lowest.contrast_ratio > 10.0);
+        assert_eq!(lowest.wcag, WcagLevel::AAA);
+        assert!(set.cross_contrast("light", "brand").is_none());
+    }
+}
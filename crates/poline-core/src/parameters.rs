@@ -0,0 +1,174 @@
+//! Normalized parameters for driving a palette from OSC, MIDI or any other
+//! controller that sends values in 0..1.
+
+use crate::{
+    color_point::ColorPointCollection, types::Vector3, Poline, PolineErrors, PositionScale,
+};
+
+/// Number of position functions `easing` steps through
+const POSITION_SCALES: usize = PositionScale::ALL.len();
+
+/// The palette knobs as normalized 0..1 parameters, addressed by ids that
+/// fit OSC paths:
+///
+/// - `hue_shift`: rotation of every hue, 0 to 360 degrees
+/// - `easing`: the position function of all axes
+/// - `num_points`: points between anchors, 1 to `max_points`
+/// - `anchor/<index>/x`, `anchor/<index>/y`, `anchor/<index>/z`: anchor
+///   positions in the unit cube
+#[derive(Debug, Clone)]
+pub struct ParameterMap {
+    poline: Poline,
+    hue_shift: f32,
+    easing: usize,
+    max_points: usize,
+}
+
+impl ParameterMap {
+    /// Wraps `poline`, letting `num_points` range up to `max_points`
+    pub fn new(poline: Poline, max_points: usize) -> Self {
+        let (easing, _, _) = poline.position_functions();
+        Self {
+            poline,
+            hue_shift: 0.0,
+            easing: easing as usize,
+            max_points: max_points.max(1),
+        }
+    }
+
+    pub fn poline(&self) -> &Poline {
+        &self.poline
+    }
+
+    pub fn into_poline(self) -> Poline {
+        self.poline
+    }
+
+    /// Every parameter id, in a stable order
+    pub fn ids(&self) -> Vec<String> {
+        let mut ids = vec![
+            "hue_shift".to_string(),
+            "easing".to_string(),
+            "num_points".to_string(),
+        ];
+        for index in 0..self.poline.anchors().len() {
+            for axis in ["x", "y", "z"] {
+                ids.push(format!("anchor/{index}/{axis}"));
+            }
+        }
+        ids
+    }
+
+    /// The current normalized value of the parameter `id`
+    pub fn get(&self, id: &str) -> Option<f32> {
+        match id {
+            "hue_shift" => Some(self.hue_shift),
+            "easing" => Some(self.easing as f32 / (POSITION_SCALES - 1) as f32),
+            "num_points" => {
+                let points = self.poline.points_per_segment().saturating_sub(2);
+                Some(points.saturating_sub(1) as f32 / (self.max_points - 1).max(1) as f32)
+            }
+            _ => {
+                let (index, axis) = parse_anchor_id(id)?;
+                let Vector3(x, y, z) = self.poline.anchors().get(index)?.position();
+                Some([x, y, z][axis])
+            }
+        }
+    }
+
+    /// Sets the parameter `id` to `value`, clamped to 0..1
+    pub fn set(&mut self, id: &str, value: f32) -> Result<(), PolineErrors> {
+        let value = value.clamp(0.0, 1.0);
+        match id {
+            "hue_shift" => {
+                self.poline.shift_hue((value - self.hue_shift) * 360.0);
+                self.hue_shift = value;
+            }
+            "easing" => {
+                let easing = (value * (POSITION_SCALES - 1) as f32).round() as usize;
+                if easing != self.easing {
+                    self.poline.set_position_fn(easing);
+                    self.easing = easing;
+                }
+            }
+            "num_points" => {
+                let points = 1 + (value * (self.max_points - 1) as f32).round() as usize;
+                self.poline.set_num_points(points)?;
+            }
+            _ => {
+                let (index, axis) = parse_anchor_id(id).ok_or(PolineErrors::InvalidOption)?;
+                let anchor = self
+                    .poline
+                    .anchors()
+                    .get(index)
+                    .ok_or(PolineErrors::PointNotFound)?;
+                let Vector3(x, y, z) = anchor.position();
+                let mut position = [x, y, z];
+                position[axis] = value;
+                self.poline.update_anchor_point_at_index(
+                    index,
+                    ColorPointCollection {
+                        xyz: Some(Vector3(position[0], position[1], position[2])),
+                        color: None,
+                        inverted_lightness: self.poline.inverted_lightness,
+                        model: self.poline.color_model,
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits `anchor/<index>/<axis>` into the anchor index and axis index
+fn parse_anchor_id(id: &str) -> Option<(usize, usize)> {
+    let mut parts = id.trim_start_matches('/').split('/');
+    if parts.next()? != "anchor" {
+        return None;
+    }
+    let index = parts.next()?.parse().ok()?;
+    let axis = match parts.next()? {
+        "x" => 0,
+        "y" => 1,
+        "z" => 2,
+        _ => return None,
+    };
+    parts.next().is_none().then_some((index, axis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{positions::PositionScale, PolineOptions};
+
+    #[test]
+    fn parameters_drive_the_palette() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.9, 0.2), Vector3(200.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let mut parameters = ParameterMap::new(poline, 16);
+        assert_eq!(parameters.ids().len(), 3 + 2 * 3);
+
+        parameters.set("hue_shift", 0.25).unwrap();
+        assert!((parameters.poline().anchors()[0].color.0 - 110.0).abs() < 1e-3);
+        parameters.set("hue_shift", 0.0).unwrap();
+        assert!((parameters.poline().anchors()[0].color.0 - 20.0).abs() < 1e-3);
+
+        parameters.set("easing", 0.0).unwrap();
+        assert_eq!(
+            parameters.poline().position_functions().0,
+            PositionScale::Linear
+        );
+
+        parameters.set("num_points", 1.0).unwrap();
+        assert_eq!(parameters.poline().points_per_segment(), 18);
+        assert_eq!(parameters.get("num_points"), Some(1.0));
+
+        parameters.set("/anchor/1/z", 0.3).unwrap();
+        assert_eq!(parameters.get("anchor/1/z"), Some(0.3));
+        assert!(parameters.set("anchor/5/x", 0.3).is_err());
+        assert!(parameters.set("tempo", 0.3).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! A minimal PNG writer for previews. Pixel data goes into stored (not
+//! compressed) deflate blocks, which keeps the encoder small and is fine
+//! for gradient strips of a few kilobytes.
+
+use crate::{export::LutFormat, utils::crc32, Poline};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut zlib = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    zlib.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        zlib.push(u8::from(last));
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(chunk);
+    }
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+/// Encodes `width` x `height` RGBA8 pixels, row-major, as a PNG file
+pub(crate) fn encode_rgba8(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let stride = width as usize * 4;
+    debug_assert_eq!(pixels.len(), stride * height as usize);
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in pixels.chunks(stride.max(1)).take(height as usize) {
+        // No row filter
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+impl Poline {
+    /// The continuous gradient, left to right, as a `width` x `height` PNG
+    pub fn to_png(&self, width: u32, height: u32) -> Vec<u8> {
+        let row = self.to_lut_bytes(width as usize, LutFormat::Rgba8);
+        encode_rgba8(width, height, &row.repeat(height as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PolineOptions, Vector3};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(zlib_stored(&[]).len(), 2 + 5 + 4);
+    }
+
+    #[test]
+    fn writes_png_chunks() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let png = poline.to_png(4, 2);
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 4);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 2);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        // IDAT holds 2 rows of a filter byte and 4 RGBA pixels
+        assert_eq!(
+            u32::from_be_bytes(png[33..37].try_into().unwrap()),
+            2 + 5 + 2 * 17 + 4
+        );
+        // The first pixel of the first row is black and opaque
+        assert_eq!(png[41 + 7..41 + 12], [0, 0, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Poline;
+
+/// What `PolineOptions::num_points` counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PointCountMode {
+    /// Points between each pair of anchors, as in poline.js. A palette
+    /// outputs `segments * (num_points + 1) + 1` colors, or
+    /// `segments * (num_points + 1)` as a closed loop.
+    #[default]
+    PerSegment,
+    /// Colors in the whole palette, anchors included. The points are spread
+    /// over the segments as evenly as possible, with at least the anchors
+    /// themselves when there are too few.
+    TotalOutput,
+}
+
+impl Poline {
+    /// Points sampled on the segment at `idx`, including both of its anchors
+    pub(crate) fn segment_sample_count(&self, idx: usize) -> usize {
+        match self.point_count_mode {
+            PointCountMode::PerSegment => self.num_points,
+            PointCountMode::TotalOutput => {
+                let segments = self.anchor_pairs.len().max(1);
+                let requested = self.num_points - 2;
+                // Segments share their joints, and a closed loop does not
+                // repeat its first anchor
+                let intervals = if self.connect_last_and_first_anchor {
+                    requested
+                } else {
+                    requested.saturating_sub(1)
+                }
+                .max(segments);
+                let extra = usize::from(idx < intervals % segments);
+                intervals / segments + extra + 1
+            }
+        }
+    }
+
+    pub fn point_count_mode(&self) -> PointCountMode {
+        self.point_count_mode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::Vector3, PolineOptions};
+
+    fn poline(anchors: usize, num_points: usize, mode: PointCountMode, closed: bool) -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(
+                (0..anchors)
+                    .map(|i| Vector3(i as f32 * 50.0, 0.5, 0.5))
+                    .collect(),
+            ),
+            num_points,
+            point_count_mode: mode,
+            closed_loop: closed,
+            inverted_lightness: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn output_counts_are_predictable() {
+        use PointCountMode::*;
+        assert_eq!(poline(2, 4, PerSegment, false).output_points().len(), 6);
+        assert_eq!(poline(3, 4, PerSegment, false).output_points().len(), 11);
+        assert_eq!(poline(3, 4, PerSegment, true).output_points().len(), 15);
+        for anchors in 2..6 {
+            for n in 6..20 {
+                for closed in [false, true] {
+                    let palette = poline(anchors, n, TotalOutput, closed);
+                    assert_eq!(palette.output_points().len(), n, "{anchors} {n} {closed}");
+                }
+            }
+        }
+        assert_eq!(poline(4, 2, TotalOutput, false).output_points().len(), 4);
+    }
+}
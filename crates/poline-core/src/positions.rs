@@ -1,10 +1,13 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, fmt, str::FromStr};
 
 use serde::{Serialize, Deserialize};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::wasm_bindgen;
 
-#[wasm_bindgen]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+use crate::PolineErrors;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 /// Defines all possible scale function types for use in color generator
 pub enum PositionScale {
     Linear,
@@ -19,6 +22,43 @@ pub enum PositionScale {
 }
 
 impl PositionScale {
+    /// Every position function in id order, e.g. to fill a dropdown
+    pub const ALL: &'static [PositionScale] = &[
+        PositionScale::Linear,
+        PositionScale::Exponential,
+        PositionScale::Cubic,
+        PositionScale::Quadratic,
+        PositionScale::Quartic,
+        PositionScale::Sinusoidal,
+        PositionScale::Asinusoidal,
+        PositionScale::Arc,
+        PositionScale::SmoothStep,
+    ];
+
+    /// The stable number `set_position_fn` and `number_as_enum` take
+    pub const fn id(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_id(id: usize) -> Option<Self> {
+        Self::ALL.get(id).copied()
+    }
+
+    /// The kebab-case name, e.g. `smooth-step`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            PositionScale::Linear => "linear",
+            PositionScale::Exponential => "exponential",
+            PositionScale::Cubic => "cubic",
+            PositionScale::Quadratic => "quadratic",
+            PositionScale::Quartic => "quartic",
+            PositionScale::Sinusoidal => "sinusoidal",
+            PositionScale::Asinusoidal => "asinusoidal",
+            PositionScale::Arc => "arc",
+            PositionScale::SmoothStep => "smooth-step",
+        }
+    }
+
     /// Given a position on a x,y, or z calculate the position based the scale method
     pub fn position(self, t: f32, reverse: bool) -> f32 {
         use PositionScale::*;
@@ -78,7 +118,26 @@ impl PositionScale {
     }
 }
 
-#[wasm_bindgen]
+impl fmt::Display for PositionScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses the names [`PositionScale::as_str`] returns
+impl FromStr for PositionScale {
+    type Err = PolineErrors;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|scale| scale.as_str() == name)
+            .ok_or(PolineErrors::InvalidOption)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn position_from_scale(scale: PositionScale, t: f32, reverse: bool) -> f32 {
      use PositionScale::*;
         match scale {
@@ -134,4 +193,23 @@ pub fn position_from_scale(scale: PositionScale, t: f32, reverse: bool) -> f32 {
             }
             SmoothStep => t.powf(2.0 * (3.0 - 2.0 * t)),
         }
-    }
\ No newline at end of file
+    }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number_as_enum;
+
+    #[test]
+    fn ids_and_names_round_trip() {
+        for (id, &scale) in PositionScale::ALL.iter().enumerate() {
+            assert_eq!(scale.id(), id);
+            assert_eq!(number_as_enum(id), scale);
+            assert_eq!(PositionScale::from_id(id), Some(scale));
+            assert_eq!(scale.to_string().parse::<PositionScale>().unwrap(), scale);
+        }
+        assert_eq!(PositionScale::from_id(PositionScale::ALL.len()), None);
+        assert_eq!(PositionScale::SmoothStep.to_string(), "smooth-step");
+        assert!("Smooth step".parse::<PositionScale>().is_err());
+    }
+}
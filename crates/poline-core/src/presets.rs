@@ -0,0 +1,93 @@
+use crate::{
+    color_model::ColorModel,
+    color_space::{hsl_to_oklab, hsl_to_rgb, oklab_to_hsl},
+    positions::PositionScale,
+    types::Vector3,
+    Poline, PolineOptions, Provenance,
+};
+
+impl Poline {
+    /// A diverging palette running from `low` through a neutral `midpoint`
+    /// to `high`. Both ends are moved to the same OKLab lightness and the
+    /// curve is built in the OKLCH cylinder, so the two halves ramp towards
+    /// the midpoint at the same rate.
+    pub fn diverging(low: Vector3, high: Vector3, midpoint: Vector3) -> Poline {
+        let low_lab = hsl_to_oklab(low);
+        let high_lab = hsl_to_oklab(high);
+        let lightness = (low_lab.0 + high_lab.0) / 2.0;
+        let low = oklab_to_hsl(Vector3(lightness, low_lab.1, low_lab.2));
+        let high = oklab_to_hsl(Vector3(lightness, high_lab.1, high_lab.2));
+
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![low, midpoint, high]),
+            position_function: PositionScale::Sinusoidal,
+            color_model: ColorModel::OklchCylinder,
+            ..Default::default()
+        });
+        poline.set_provenance(Some(Provenance::preset("diverging")));
+        poline
+    }
+
+    /// A two color palette for duotone effects, from `shadow` to `highlight`
+    /// in `steps` colors. The curve runs linearly through the OKLCH
+    /// cylinder, so lightness rises evenly from one end to the other.
+    pub fn duotone(shadow: Vector3, highlight: Vector3, steps: usize) -> Poline {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![shadow, highlight]),
+            num_points: steps.saturating_sub(2),
+            position_function: PositionScale::Linear,
+            color_model: ColorModel::OklchCylinder,
+            ..Default::default()
+        });
+        poline.set_provenance(Some(Provenance::preset("duotone")));
+        poline
+    }
+
+    /// The sRGB color (channels in 0..1) a grayscale value `l` in 0..1 maps
+    /// to, 0 being the start of the palette and 1 its end. Used to run image
+    /// luminance through a palette.
+    pub fn apply_to_luminance(&self, l: f32) -> Vector3 {
+        hsl_to_rgb(self.curve_point(l).color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color_space::rgb_to_oklab;
+
+    #[test]
+    fn diverging_sides_mirror_lightness() {
+        let poline = Poline::diverging(
+            Vector3(240.0, 0.8, 0.3),
+            Vector3(20.0, 0.8, 0.6),
+            Vector3(0.0, 0.0, 0.95),
+        );
+        let lightness = |segment: &[Vector3]| -> Vec<f32> {
+            segment.iter().map(|&c| hsl_to_oklab(c).0).collect()
+        };
+        let low_side = lightness(poline.sampled_points().segment_colors(0));
+        let mut high_side = lightness(poline.sampled_points().segment_colors(1));
+        high_side.reverse();
+        for (low, high) in low_side.iter().zip(&high_side) {
+            assert!((low - high).abs() < 0.02, "{low_side:?} {high_side:?}");
+        }
+    }
+
+    #[test]
+    fn duotone_maps_luminance_between_the_tones() {
+        let shadow = Vector3(240.0, 0.6, 0.15);
+        let highlight = Vector3(40.0, 0.9, 0.85);
+        let duotone = Poline::duotone(shadow, highlight, 8);
+        assert_eq!(duotone.points_per_segment(), 8);
+
+        let rgb_close = |a: Vector3, b: Vector3| {
+            (a.0 - b.0).abs() < 1e-2 && (a.1 - b.1).abs() < 1e-2 && (a.2 - b.2).abs() < 1e-2
+        };
+        assert!(rgb_close(duotone.apply_to_luminance(0.0), hsl_to_rgb(shadow)));
+        assert!(rgb_close(duotone.apply_to_luminance(1.0), hsl_to_rgb(highlight)));
+
+        let lightness = |l: f32| rgb_to_oklab(duotone.apply_to_luminance(l)).0;
+        assert!(lightness(0.25) < lightness(0.5) && lightness(0.5) < lightness(0.75));
+    }
+}
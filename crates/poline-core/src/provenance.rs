@@ -0,0 +1,119 @@
+//! Where a palette came from. A [`Provenance`] is saved with the palette
+//! and written as comments into the text exports that allow them, so a
+//! palette generated in bulk can be traced back to its inputs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Poline;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    /// The seed random anchors were drawn with
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The preset or generator that made the palette, e.g. `diverging`
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// A hash of the image the palette was taken from
+    #[serde(default)]
+    pub source_image_hash: Option<String>,
+    /// Where an imported palette was read from
+    #[serde(default)]
+    pub import_url: Option<String>,
+}
+
+impl Provenance {
+    pub fn preset(preset: &str) -> Self {
+        Self {
+            preset: Some(preset.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// `key: value` for every field that is set, on one line each
+    pub fn lines(&self) -> Vec<String> {
+        let one_line = |value: &str| value.replace(['\r', '\n'], " ");
+        let mut lines = Vec::new();
+        if let Some(seed) = self.seed {
+            lines.push(format!("seed: {seed}"));
+        }
+        if let Some(preset) = &self.preset {
+            lines.push(format!("preset: {}", one_line(preset)));
+        }
+        if let Some(hash) = &self.source_image_hash {
+            lines.push(format!("source image: {}", one_line(hash)));
+        }
+        if let Some(url) = &self.import_url {
+            lines.push(format!("import url: {}", one_line(url)));
+        }
+        lines
+    }
+}
+
+impl Poline {
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+
+    /// Records where the palette came from, `None` or an empty provenance
+    /// clears it
+    pub fn set_provenance(&mut self, provenance: Option<Provenance>) {
+        self.provenance = provenance.filter(|provenance| !provenance.is_empty());
+    }
+
+    /// The provenance lines, each after `prefix` and ending in a newline
+    pub(crate) fn provenance_comment(&self, prefix: &str) -> String {
+        self.provenance
+            .iter()
+            .flat_map(Provenance::lines)
+            .map(|line| format!("{prefix}{line}\n"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_saved_and_exported() {
+        let mut poline = Poline::diverging(
+            crate::Vector3(240.0, 0.8, 0.3),
+            crate::Vector3(20.0, 0.8, 0.6),
+            crate::Vector3(0.0, 0.0, 0.95),
+        );
+        assert_eq!(poline.provenance(), Some(&Provenance::preset("diverging")));
+        poline.set_provenance(Some(Provenance {
+            seed: Some(7),
+            import_url: Some("https://example.com/a\nb".into()),
+            ..Default::default()
+        }));
+
+        let loaded = Poline::from_json(&poline.to_json()).unwrap();
+        assert_eq!(loaded.provenance(), poline.provenance());
+        assert!(poline
+            .to_inkscape_gpl("p")
+            .contains("#\n# seed: 7\n# import url: https://example.com/a b\n"));
+        assert!(poline.to_glsl().contains("\n// seed: 7\n"));
+        assert!(poline.to_cube_1d(2).contains("\n# seed: 7\n"));
+        assert!(poline.to_svg_gradient(&Default::default()).starts_with("<!-- seed: 7 -->\n"));
+
+        poline.set_provenance(Some(Provenance {
+            import_url: Some("https://x/a---b--".into()),
+            ..Default::default()
+        }));
+        let svg = poline.to_svg_gradient(&Default::default());
+        assert!(svg.starts_with("<!-- import url: https://x/a- - -b- - -->\n"));
+        let comment = &svg["<!--".len()..svg.find("-->").unwrap()];
+        assert!(!comment.contains("--"));
+
+        poline.set_provenance(Some(Provenance::default()));
+        assert_eq!(poline.provenance(), None);
+        assert!(!poline.to_inkscape_gpl("p").contains("# seed"));
+    }
+}
@@ -0,0 +1,219 @@
+//! Exporters by name.
+//!
+//! Every file format poline writes is an [`Exporter`] in the global
+//! [`registry`], so front-ends can list the formats and
+//! [`Poline::export`] picks one at runtime. Downstream crates add their
+//! own formats with [`ExporterRegistry::register`] instead of patching
+//! poline-core.
+//!
+//! ```
+//! # use poline_core::{registry, FnExporter, Poline, PolineErrors};
+//! # let poline = Poline::default();
+//! registry().register(FnExporter::new("txt", "txt", |poline| {
+//!     poline.to_plain_preview().into_bytes()
+//! }));
+//! let bytes = poline.export("txt")?;
+//! # assert_eq!(bytes, poline.to_plain_preview().into_bytes());
+//! # Ok::<_, PolineErrors>(())
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, OnceLock, PoisonError, RwLock},
+};
+
+use crate::{
+    export::COLORMAP_ENTRIES,
+    palette_export::{LimitPolicy, PixelPaletteOptions},
+    trace, Poline, PolineErrors, SvgGradientOptions,
+};
+
+/// A file format a palette can be written as
+pub trait Exporter: Send + Sync {
+    /// The name the exporter is registered under, e.g. `gpl`
+    fn name(&self) -> &str;
+
+    /// The file extension of the output, without the dot
+    fn extension(&self) -> &str;
+
+    fn export(&self, poline: &Poline) -> Vec<u8>;
+}
+
+/// An [`Exporter`] from a plain function
+#[derive(Debug, Clone, Copy)]
+pub struct FnExporter {
+    name: &'static str,
+    extension: &'static str,
+    export: fn(&Poline) -> Vec<u8>,
+}
+
+impl FnExporter {
+    pub const fn new(
+        name: &'static str,
+        extension: &'static str,
+        export: fn(&Poline) -> Vec<u8>,
+    ) -> Self {
+        Self {
+            name,
+            extension,
+            export,
+        }
+    }
+}
+
+impl Exporter for FnExporter {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn extension(&self) -> &str {
+        self.extension
+    }
+
+    fn export(&self, poline: &Poline) -> Vec<u8> {
+        (self.export)(poline)
+    }
+}
+
+/// The formats built into poline-core, with each format's default settings
+const BUILTINS: &[FnExporter] = &[
+    FnExporter::new("css", "css", |poline| poline.css_strings().join("\n").into_bytes()),
+    FnExporter::new("css-gradient", "css", |poline| poline.to_css_gradient(90.0).into_bytes()),
+    FnExporter::new("svg", "svg", |poline| {
+        poline.to_svg_gradient(&SvgGradientOptions::default()).into_bytes()
+    }),
+    FnExporter::new("cube", "cube", |poline| poline.to_cube_1d(COLORMAP_ENTRIES).into_bytes()),
+    FnExporter::new("glsl", "glsl", |poline| poline.to_glsl().into_bytes()),
+    FnExporter::new("wgsl", "wgsl", |poline| poline.to_wgsl().into_bytes()),
+    FnExporter::new("matplotlib", "json", |poline| {
+        poline.to_matplotlib_json("poline").into_bytes()
+    }),
+    FnExporter::new("vega", "json", |poline| poline.to_vega_scheme_json().into_bytes()),
+    FnExporter::new("hex", "hex", |poline| {
+        poline.to_hex_palette(PixelPaletteOptions::default()).into_bytes()
+    }),
+    FnExporter::new("paint-net", "txt", |poline| {
+        poline.to_paint_net_palette(PixelPaletteOptions::default()).into_bytes()
+    }),
+    FnExporter::new("gpl", "gpl", |poline| poline.to_inkscape_gpl("poline").into_bytes()),
+    FnExporter::new("kpl", "kpl", |poline| poline.to_krita_palette("poline")),
+    FnExporter::new("procreate", "swatches", |poline| {
+        let mut export = poline.to_procreate_swatches("poline", LimitPolicy::Truncate);
+        export.pages.remove(0)
+    }),
+    FnExporter::new("png", "png", |poline| poline.to_png(COLORMAP_ENTRIES as u32, 32)),
+    FnExporter::new("json", "json", |poline| poline.to_json().into_bytes()),
+    FnExporter::new("share", "txt", |poline| poline.to_share_string().into_bytes()),
+    #[cfg(feature = "postcard")]
+    FnExporter::new("postcard", "bin", Poline::to_postcard),
+    #[cfg(feature = "msgpack")]
+    FnExporter::new("msgpack", "msgpack", Poline::to_msgpack),
+];
+
+/// Exporters by name. Registering is thread safe, so the global
+/// [`registry`] can be extended at any time.
+#[derive(Default)]
+pub struct ExporterRegistry {
+    exporters: RwLock<BTreeMap<String, Arc<dyn Exporter>>>,
+}
+
+impl ExporterRegistry {
+    /// A registry without any exporters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the formats built into poline-core
+    pub fn with_builtins() -> Self {
+        let registry = Self::new();
+        for exporter in BUILTINS {
+            registry.register(*exporter);
+        }
+        registry
+    }
+
+    /// Adds `exporter` under its name, returning the exporter it replaces
+    pub fn register(&self, exporter: impl Exporter + 'static) -> Option<Arc<dyn Exporter>> {
+        let mut exporters = self.exporters.write().unwrap_or_else(PoisonError::into_inner);
+        exporters.insert(exporter.name().to_string(), Arc::new(exporter))
+    }
+
+    pub fn unregister(&self, name: &str) -> Option<Arc<dyn Exporter>> {
+        let mut exporters = self.exporters.write().unwrap_or_else(PoisonError::into_inner);
+        exporters.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Exporter>> {
+        let exporters = self.exporters.read().unwrap_or_else(PoisonError::into_inner);
+        exporters.get(name).cloned()
+    }
+
+    /// The registered names, sorted
+    pub fn names(&self) -> Vec<String> {
+        let exporters = self.exporters.read().unwrap_or_else(PoisonError::into_inner);
+        exporters.keys().cloned().collect()
+    }
+}
+
+/// The registry [`Poline::export`] looks formats up in, starting out with
+/// the built in formats
+pub fn registry() -> &'static ExporterRegistry {
+    static REGISTRY: OnceLock<ExporterRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ExporterRegistry::with_builtins)
+}
+
+impl Poline {
+    /// The palette written by the exporter registered as `format`
+    pub fn export(&self, format: &str) -> Result<Vec<u8>, PolineErrors> {
+        trace::span!("export", format);
+        let exporter = registry().get(format).ok_or(PolineErrors::UnknownFormat)?;
+        let bytes = exporter.export(self);
+        trace::count!(
+            monotonic_counter.poline_exports = 1_u64,
+            monotonic_counter.poline_export_bytes = bytes.len() as u64,
+        );
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_match_the_export_methods() {
+        let poline = Poline::default();
+        assert_eq!(poline.export("gpl").unwrap(), poline.to_inkscape_gpl("poline").into_bytes());
+        assert_eq!(poline.export("kpl").unwrap(), poline.to_krita_palette("poline"));
+        assert_eq!(registry().get("cube").unwrap().extension(), "cube");
+        assert!(matches!(poline.export("ase"), Err(PolineErrors::UnknownFormat)));
+        for exporter in BUILTINS {
+            assert!(!poline.export(exporter.name()).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn registers_custom_formats() {
+        struct Count;
+        impl Exporter for Count {
+            fn name(&self) -> &str {
+                "count"
+            }
+            fn extension(&self) -> &str {
+                "txt"
+            }
+            fn export(&self, poline: &Poline) -> Vec<u8> {
+                poline.colors_vec().len().to_string().into_bytes()
+            }
+        }
+
+        let registry = ExporterRegistry::new();
+        assert!(registry.register(Count).is_none());
+        assert!(registry.register(Count).is_some());
+        assert_eq!(registry.names(), ["count"]);
+        let export = registry.get("count").unwrap().export(&Poline::default());
+        assert_eq!(export, Poline::default().colors_vec().len().to_string().into_bytes());
+        assert!(registry.unregister("count").is_some());
+        assert!(registry.get("count").is_none());
+    }
+}
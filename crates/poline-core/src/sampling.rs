@@ -0,0 +1,719 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    color_point::{ColorPoint, ColorPointCollection},
+    color_space::delta_e,
+    interpolation::InterpolationSpace,
+    mix::{color_distance, MixSpace},
+    positions::PositionScale,
+    stops::chord_fraction,
+    trace,
+    types::Vector3,
+    utils::{vector_on_line, vectors_on_line},
+    Poline,
+};
+
+/// Samples per segment used when searching the curve for colors
+pub(crate) const SEARCH_SAMPLES_PER_SEGMENT: usize = 64;
+
+/// One segment of a palette, the curve between two neighbouring anchors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub index: usize,
+    pub start: ColorPoint,
+    pub end: ColorPoint,
+    /// The easing of the x, y and z axes
+    pub easing: (PositionScale, PositionScale, PositionScale),
+    /// Whether each axis uses the reversed (ease-out) form of its easing
+    pub invert: [bool; 3],
+    /// Whether this is a closed loop's segment from the last anchor back to
+    /// the first
+    pub closing: bool,
+    /// The sampled HSL colors, from `start` to `end`
+    pub colors: Vec<Vector3>,
+}
+
+impl Poline {
+    /// Segments alternate their easing direction; the per-axis reverse
+    /// flags flip that on top of the alternation.
+    pub(crate) fn segment_invert(&self, idx: usize) -> [bool; 3] {
+        let alternate = idx.is_multiple_of(2);
+        [
+            alternate ^ self.reverse_x,
+            alternate ^ self.reverse_y,
+            alternate ^ self.reverse_z,
+        ]
+    }
+
+    fn color_point_at(&self, xyz: Vector3) -> ColorPoint {
+        ColorPoint::new(ColorPointCollection {
+            xyz: Some(xyz),
+            color: None,
+            inverted_lightness: self.inverted_lightness,
+            model: self.color_model,
+        })
+    }
+
+    /// Whether the segment at `idx` is the closing segment of a loop with
+    /// seam smoothing on
+    fn is_smoothed_seam(&self, idx: usize) -> bool {
+        self.smooth_seam && self.connect_last_and_first_anchor && idx + 1 == self.anchor_pairs.len()
+    }
+
+    /// The closing segment as a Catmull-Rom curve (a cubic Hermite with
+    /// tangents pointing from the previous anchor to the next one) so it
+    /// leaves the last anchor and enters the first one heading the same way
+    /// as the neighbouring segments
+    fn seam_position(&self, t: f32) -> Vector3 {
+        let n = self.anchor_points.len();
+        let position = |i: usize| self.anchor_points[i % n].position();
+        let (before, start, end, after) =
+            (position(n - 2), position(n - 1), position(0), position(1));
+
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + t;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+        let axis = |before: f32, start: f32, end: f32, after: f32| {
+            let m0 = (end - before) / 2.0;
+            let m1 = (after - start) / 2.0;
+            (h00 * start + h10 * m0 + h01 * end + h11 * m1).clamp(0.0, 1.0)
+        };
+        Vector3(
+            axis(before.0, start.0, end.0, after.0),
+            axis(before.1, start.1, end.1, after.1),
+            axis(before.2, start.2, end.2, after.2),
+        )
+    }
+
+    /// Samples `count` points along the segment at `idx`, including both of
+    /// its anchors, with the palette's easing and coordinate mapping
+    pub(crate) fn sample_segment(&self, idx: usize, count: usize) -> Vec<ColorPoint> {
+        if self.is_smoothed_seam(idx) {
+            let last = count.saturating_sub(1).max(1) as f32;
+            return (0..count)
+                .map(|i| self.color_point_at(self.seam_position(i as f32 / last)))
+                .collect();
+        }
+        if self.interpolation == InterpolationSpace::Hsl {
+            return self.sample_segment_hsl(idx, count);
+        }
+        let (p1, p2) = self.anchor_pairs[idx];
+        let positions = vectors_on_line(
+            p1.position(),
+            p2.position(),
+            Some(count),
+            self.segment_invert(idx),
+            Some(self.position_function_x),
+            Some(self.position_function_y),
+            Some(self.position_function_z),
+        );
+        let colors = self
+            .color_model
+            .points_to_hsl(&positions, self.inverted_lightness);
+        positions
+            .into_iter()
+            .zip(colors)
+            .map(|(position, color)| {
+                ColorPoint::from_parts(position, color, self.inverted_lightness, self.color_model)
+            })
+            .collect()
+    }
+
+    /// The point at `t` in 0..1 along the segment at `idx`
+    pub(crate) fn segment_point(&self, idx: usize, t: f32) -> ColorPoint {
+        if self.is_smoothed_seam(idx) {
+            return self.color_point_at(self.seam_position(t));
+        }
+        if self.interpolation == InterpolationSpace::Hsl {
+            return self.segment_point_hsl(idx, t);
+        }
+        let (p1, p2) = self.anchor_pairs[idx];
+        self.color_point_at(vector_on_line(
+            t,
+            p1.position(),
+            p2.position(),
+            self.segment_invert(idx),
+            Some(self.position_function_x),
+            Some(self.position_function_y),
+            Some(self.position_function_z),
+        ))
+    }
+
+    /// The point `along` of the way from the start to the end of the
+    /// segment at `idx`, measured on the straight line between them as
+    /// [`Poline::stops`] places the sampled points. The easing is undone by
+    /// bisection, a segment whose ends share a position is walked evenly.
+    fn segment_point_along(&self, idx: usize, along: f32) -> ColorPoint {
+        if along <= 0.0 || along >= 1.0 {
+            return self.segment_point(idx, along.clamp(0.0, 1.0));
+        }
+        let start = self.segment_point(idx, 0.0).position();
+        let end = self.segment_point(idx, 1.0).position();
+        let along_at = |t| chord_fraction(start, end, self.segment_point(idx, t).position());
+        if along_at(0.5).is_none() {
+            return self.segment_point(idx, along);
+        }
+        let (mut low, mut high) = (0.0_f32, 1.0_f32);
+        for _ in 0..24 {
+            let mid = (low + high) / 2.0;
+            if along_at(mid).is_some_and(|reached| reached < along) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        self.segment_point(idx, (low + high) / 2.0)
+    }
+
+    /// The point at `t` in 0..1 over the whole curve, each segment taking
+    /// an equal share of `t`. Within a segment `t` is how far along the
+    /// line between its anchors the point is, the parameter of
+    /// [`Poline::stops`] and so of the colormap and the LUTs.
+    pub(crate) fn curve_point(&self, t: f32) -> ColorPoint {
+        let segments = self.anchor_pairs.len();
+        let position = t.clamp(0.0, 1.0) * segments as f32;
+        let index = (position.floor() as usize).min(segments - 1);
+        self.segment_point_along(index, position - index as f32)
+    }
+
+    /// The segment at `index`, or `None` when there is no such segment
+    pub fn segment(&self, index: usize) -> Option<Segment> {
+        let &(start, end) = self.anchor_pairs.get(index)?;
+        Some(Segment {
+            index,
+            start,
+            end,
+            easing: (
+                self.position_function_x,
+                self.position_function_y,
+                self.position_function_z,
+            ),
+            invert: self.segment_invert(index),
+            closing: self.connect_last_and_first_anchor && index + 1 == self.anchor_pairs.len(),
+            colors: self.sampled_points().segment_colors(index).to_vec(),
+        })
+    }
+
+    /// Samples every segment with `count` points, on the rayon thread pool
+    /// when the `rayon` feature is enabled
+    pub(crate) fn sample_segments(&self, count: usize) -> Vec<Vec<ColorPoint>> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            (0..self.anchor_pairs.len())
+                .into_par_iter()
+                .map(|idx| self.sample_segment(idx, count))
+                .collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            (0..self.anchor_pairs.len())
+                .map(|idx| self.sample_segment(idx, count))
+                .collect()
+        }
+    }
+
+    /// Samples every segment with its share of the palette's points
+    pub(crate) fn sample_all_segments(&self) -> Vec<Vec<ColorPoint>> {
+        let sample = |idx| self.sample_segment(idx, self.segment_sample_count(idx));
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            (0..self.anchor_pairs.len()).into_par_iter().map(sample).collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            (0..self.anchor_pairs.len()).map(sample).collect()
+        }
+    }
+
+    /// The indices of the segments whose samples change when the anchor at
+    /// `index` moves, or `None` when every segment has to be rebuilt
+    fn segments_touching(&self, index: usize) -> Option<Vec<usize>> {
+        // Lightness enforcement reshapes the whole palette at once, and with
+        // fewer than three anchors every segment touches the anchor anyway
+        if self.monotonic_lightness || self.anchor_points.len() < 3 {
+            return None;
+        }
+        let segments = self.anchor_pairs.len();
+        let anchors = self.anchor_points.len();
+        let mut dirty = Vec::with_capacity(3);
+        if index < segments {
+            dirty.push(index);
+        }
+        if index > 0 {
+            dirty.push(index - 1);
+        } else if self.connect_last_and_first_anchor {
+            dirty.push(segments - 1);
+        }
+        // The smoothed seam also follows the anchors on either side of it
+        let seam = segments - 1;
+        if self.is_smoothed_seam(seam)
+            && [anchors - 2, 0, 1].contains(&index)
+            && !dirty.contains(&seam)
+        {
+            dirty.push(seam);
+        }
+        Some(dirty)
+    }
+
+    /// Refreshes the pairs and samples of the segments next to the anchor
+    /// at `index` after it changed, leaving the other segments untouched
+    pub(crate) fn update_segments_touching(&mut self, index: usize) {
+        trace::span!("update_segments_touching", index);
+        let Some(dirty) = self.segments_touching(index) else {
+            self.update_anchor_pairs();
+            return;
+        };
+        let anchors = self.anchor_points.len();
+        for &segment in &dirty {
+            self.anchor_pairs[segment] = (
+                self.anchor_points[segment],
+                self.anchor_points[(segment + 1) % anchors],
+            );
+        }
+        self.resample_segments(&dirty);
+    }
+
+    /// Samples the segments at `segments` again and keeps the samples of
+    /// the others. Lightness enforcement reshapes the whole palette at once,
+    /// so with it on everything is sampled again on the next read.
+    pub(crate) fn resample_segments(&mut self, segments: &[usize]) {
+        if self.monotonic_lightness {
+            self.points.take();
+            return;
+        }
+        // Stale samples are rebuilt in full on the next read anyway
+        if self.needs_update() {
+            return;
+        }
+        trace::count!(monotonic_counter.poline_segments_sampled = segments.len() as u64);
+        for &segment in segments {
+            let points = self.sample_segment(segment, self.segment_sample_count(segment));
+            if let Some(sampled) = self.points.get_mut() {
+                sampled.replace_segment(segment, &points);
+            }
+        }
+    }
+
+    /// `n` colors from along the curve that are as different from each other
+    /// as possible (farthest point sampling on OKLab distance), ordered by
+    /// where they sit on the curve. Meant for labeling discrete series.
+    /// Fewer than `n` come back when the curve has no more distinct colors.
+    pub fn categorical(&self, n: usize) -> Vec<Vector3> {
+        // Segments share their joints and a closed loop ends on its start,
+        // those are only candidates once
+        let mut candidates: Vec<Vector3> = self
+            .sample_segments(SEARCH_SAMPLES_PER_SEGMENT)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(idx, points)| points.into_iter().skip(usize::from(idx > 0)))
+            .map(|point| point.color)
+            .collect();
+        if self.connect_last_and_first_anchor && candidates.len() > 1 {
+            candidates.pop();
+        }
+        if candidates.is_empty() || n == 0 {
+            return Vec::new();
+        }
+
+        let mut chosen = vec![0];
+        let mut nearest: Vec<f32> = candidates
+            .iter()
+            .map(|&c| delta_e(c, candidates[0]))
+            .collect();
+        while chosen.len() < n.min(candidates.len()) {
+            let (next, &distance) = nearest
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .unwrap();
+            // Every color left repeats one already chosen, up to rounding
+            if distance < 1e-4 {
+                break;
+            }
+            chosen.push(next);
+            for (distance, &candidate) in nearest.iter_mut().zip(&candidates) {
+                *distance = distance.min(delta_e(candidate, candidates[next]));
+            }
+        }
+
+        chosen.sort_unstable();
+        chosen.into_iter().map(|i| candidates[i]).collect()
+    }
+
+    /// Exactly `total` points spread evenly over the curve by
+    /// [`Poline::color_at`]'s parameter, whatever `num_points` is. Joints
+    /// between segments are only visited once and a closed loop stops one
+    /// step short of repeating its first anchor.
+    pub fn resample(&self, total: usize) -> Vec<ColorPoint> {
+        let steps = if self.connect_last_and_first_anchor {
+            total
+        } else {
+            total.saturating_sub(1)
+        }
+        .max(1);
+        (0..total)
+            .map(|i| self.curve_point(i as f32 / steps as f32))
+            .collect()
+    }
+
+    /// The colors of [`Poline::resample`]
+    pub fn colors_n(&self, total: usize) -> Vec<Vector3> {
+        self.resample(total)
+            .into_iter()
+            .map(|point| point.color)
+            .collect()
+    }
+
+    fn segment_lengths_in(&self, space: MixSpace) -> Vec<f32> {
+        self.sample_segments(SEARCH_SAMPLES_PER_SEGMENT)
+            .iter()
+            .map(|segment| {
+                segment
+                    .windows(2)
+                    .map(|pair| color_distance(pair[0].color, pair[1].color, space))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// The distance traveled through `space` along the whole curve
+    pub fn path_length(&self, space: MixSpace) -> f32 {
+        self.segment_lengths_in(space).iter().sum()
+    }
+
+    /// The perceptual (OKLab) distance traveled along each segment. A
+    /// segment much longer than its neighbours crams more change into the
+    /// same number of points and looks compressed.
+    pub fn segment_lengths(&self) -> Vec<f32> {
+        self.segment_lengths_in(MixSpace::Oklab)
+    }
+
+    /// Samples the curve by repeatedly splitting the span with the largest
+    /// color difference until every pair of neighbouring colors is closer
+    /// than `max_delta_e` (OKLab distance) or `max_points` is reached.
+    /// The anchors are always part of the result, so smooth stretches get
+    /// few points and fast changing ones get many.
+    pub fn adaptive_colors(&self, max_delta_e: f32, max_points: usize) -> Vec<Vector3> {
+        let segments = self.anchor_pairs.len();
+        if segments == 0 {
+            return Vec::new();
+        }
+
+        // (segment, t, color) in curve order, neighbouring segments share
+        // their joint so it is only stored once
+        let mut samples: Vec<(usize, f32, Vector3)> = Vec::new();
+        for idx in 0..segments {
+            if idx == 0 {
+                samples.push((idx, 0.0, self.segment_point(idx, 0.0).color));
+            }
+            samples.push((idx, 1.0, self.segment_point(idx, 1.0).color));
+        }
+        let span_start = |samples: &[(usize, f32, Vector3)], i: usize| {
+            let (segment, t, _) = samples[i];
+            if samples[i + 1].0 == segment {
+                (segment, t)
+            } else {
+                (samples[i + 1].0, 0.0)
+            }
+        };
+
+        while samples.len() < max_points {
+            let Some((i, distance)) = (0..samples.len() - 1)
+                .map(|i| (i, delta_e(samples[i].2, samples[i + 1].2)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                break;
+            };
+            if distance < max_delta_e {
+                break;
+            }
+            let (segment, start) = span_start(&samples, i);
+            let t = (start + samples[i + 1].1) / 2.0;
+            samples.insert(i + 1, (segment, t, self.segment_point(segment, t).color));
+        }
+
+        if self.connect_last_and_first_anchor {
+            samples.pop();
+        }
+        samples.into_iter().map(|(_, _, color)| color).collect()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    /// The HSL color at `t` in 0..1 along the whole palette, without
+    /// sampling a fixed number of points. Each segment, including the one
+    /// closing a loop, takes an equal share of `t`, and within it `t`
+    /// follows the easing the way [`Poline::stops`] does, so a color at a
+    /// stop's position is that stop's color. Lightness is not adjusted by
+    /// `monotonic_lightness`, which only reshapes the sampled points.
+    pub fn color_at(&self, t: f32) -> Vector3 {
+        self.curve_point(t).color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color_point::ColorPointCollection, color_space::delta_e, mix::MixSpace, types::Vector3,
+        HueInterpolation, InterpolationSpace, Poline, PolineOptions,
+    };
+
+    #[test]
+    fn categorical_colors_are_spread_out() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.9, 0.2), Vector3(180.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let colors = poline.categorical(3);
+        assert_eq!(colors.len(), 3);
+        // Start, end and the far away white center of the disc
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert!(delta_e(*a, *b) > 0.2, "{colors:?}");
+            }
+        }
+
+        // A flat palette has one color to give, however many are asked for
+        let gray = Vector3(0.0, 0.0, 0.5);
+        for closed_loop in [false, true] {
+            let flat = Poline::from(PolineOptions {
+                anchor_colors: Some(vec![gray, gray, gray]),
+                inverted_lightness: true,
+                closed_loop,
+                ..Default::default()
+            });
+            assert_eq!(flat.categorical(4), [gray], "{closed_loop}");
+        }
+    }
+
+    #[test]
+    fn adaptive_colors_meet_the_target_difference() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.9, 0.2), Vector3(180.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let colors = poline.adaptive_colors(0.05, 1000);
+        assert!(colors.len() > 2);
+        for pair in colors.windows(2) {
+            assert!(delta_e(pair[0], pair[1]) < 0.05);
+        }
+        assert_eq!(poline.adaptive_colors(0.0, 10).len(), 10);
+    }
+
+    #[test]
+    fn path_length_sums_segments() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.9, 0.2),
+                Vector3(180.0, 0.9, 0.8),
+                Vector3(90.0, 0.5, 0.5),
+            ]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let lengths = poline.segment_lengths();
+        assert_eq!(lengths.len(), 2);
+        let total = poline.path_length(MixSpace::Oklab);
+        assert!((total - lengths.iter().sum::<f32>()).abs() < 1e-5);
+        // The curve is never shorter than the straight line between anchors
+        let direct = delta_e(Vector3(0.0, 0.9, 0.2), Vector3(180.0, 0.9, 0.8));
+        assert!(lengths[0] >= direct * 0.99, "{lengths:?} {direct}");
+    }
+
+    #[test]
+    fn smoothed_seam_joins_the_anchors() {
+        let options = PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.9, 0.3),
+                Vector3(120.0, 0.9, 0.5),
+                Vector3(240.0, 0.9, 0.7),
+            ]),
+            inverted_lightness: true,
+            closed_loop: true,
+            ..Default::default()
+        };
+        let smooth = Poline::from(PolineOptions {
+            smooth_seam: true,
+            ..options.clone()
+        });
+        let plain = Poline::from(options);
+
+        assert_eq!(
+            plain.sampled_points().segment_positions(0),
+            smooth.sampled_points().segment_positions(0)
+        );
+        let seam = smooth.sampled_points().segment_positions(2);
+        let first = smooth.anchor_points[0].position();
+        let last = smooth.anchor_points[2].position();
+        assert_eq!(seam[0], last);
+        assert_eq!(seam[seam.len() - 1], first);
+        assert_ne!(
+            plain.sampled_points().segment_positions(2),
+            smooth.sampled_points().segment_positions(2)
+        );
+    }
+
+    #[test]
+    fn updating_an_anchor_matches_a_full_rebuild() {
+        for (closed_loop, smooth_seam) in [(false, false), (true, false), (true, true)] {
+            let mut poline = Poline::from(PolineOptions {
+                anchor_colors: Some(vec![
+                    Vector3(0.0, 0.9, 0.3),
+                    Vector3(90.0, 0.9, 0.5),
+                    Vector3(180.0, 0.9, 0.6),
+                    Vector3(270.0, 0.9, 0.7),
+                ]),
+                inverted_lightness: true,
+                closed_loop,
+                smooth_seam,
+                ..Default::default()
+            });
+            poline.recompute();
+            for index in 0..4 {
+                poline.update_anchor_point_at_index(
+                    index,
+                    ColorPointCollection {
+                        xyz: None,
+                        color: Some(Vector3(45.0 * index as f32, 0.5, 0.4)),
+                        inverted_lightness: true,
+                        model: Default::default(),
+                    },
+                )
+                .unwrap();
+                let incremental = poline.sampled_points().clone();
+                poline.update_anchor_pairs();
+                assert_eq!(&incremental, poline.sampled_points());
+            }
+        }
+    }
+
+    #[test]
+    fn edits_only_resample_their_segments() {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.9, 0.3),
+                Vector3(72.0, 0.9, 0.5),
+                Vector3(144.0, 0.9, 0.6),
+                Vector3(216.0, 0.9, 0.7),
+                Vector3(288.0, 0.9, 0.4),
+            ]),
+            inverted_lightness: true,
+            interpolation: InterpolationSpace::Hsl,
+            ..Default::default()
+        });
+        poline.recompute();
+
+        let before = poline.snapshot();
+        poline
+            .update_anchor_point_at_index(
+                2,
+                ColorPointCollection {
+                    xyz: None,
+                    color: Some(Vector3(160.0, 0.5, 0.2)),
+                    inverted_lightness: true,
+                    model: Default::default(),
+                },
+            )
+            .unwrap();
+        assert!(!poline.needs_update());
+        assert_eq!(poline.diff_since_snapshot(&before).changed_segments(), [1, 2]);
+
+        let before = poline.snapshot();
+        poline
+            .set_segment_hue_interpolation(3, Some(HueInterpolation::Longer))
+            .unwrap();
+        assert!(!poline.needs_update());
+        assert_eq!(poline.diff_since_snapshot(&before).changed_segments(), [3]);
+
+        let incremental = poline.sampled_points().clone();
+        poline.update_anchor_pairs();
+        assert_eq!(&incremental, poline.sampled_points());
+    }
+
+    #[test]
+    fn segments_describe_their_anchors() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.9, 0.3),
+                Vector3(120.0, 0.9, 0.5),
+                Vector3(240.0, 0.9, 0.7),
+            ]),
+            inverted_lightness: true,
+            closed_loop: true,
+            ..Default::default()
+        });
+        let segment = poline.segment(1).unwrap();
+        assert_eq!(segment.start, poline.anchor_points[1]);
+        assert_eq!(segment.end, poline.anchor_points[2]);
+        assert_eq!(segment.invert, [false; 3]);
+        assert!(!segment.closing);
+        assert_eq!(segment.colors.len(), poline.num_points);
+        assert!(poline.segment(2).unwrap().closing);
+        assert!(poline.segment(3).is_none());
+    }
+
+    #[test]
+    fn color_at_follows_the_curve() {
+        for closed_loop in [false, true] {
+            let poline = Poline::from(PolineOptions {
+                anchor_colors: Some(vec![
+                    Vector3(10.0, 0.8, 0.3),
+                    Vector3(140.0, 0.6, 0.7),
+                    Vector3(260.0, 0.4, 0.5),
+                ]),
+                closed_loop,
+                inverted_lightness: true,
+                ..Default::default()
+            });
+            // The continuous curve and the stops, which the colormap and
+            // the LUTs are built from, share one parameter
+            for (t, color) in poline.stops() {
+                assert!(delta_e(poline.color_at(t), color) < 1e-3, "{t}");
+            }
+            assert_eq!(poline.color_at(-1.0), poline.color_at(0.0));
+            assert_eq!(poline.color_at(2.0), poline.color_at(1.0));
+        }
+    }
+
+    #[test]
+    fn resampling_outputs_exactly_total_colors() {
+        let anchors = vec![
+            Vector3(10.0, 0.8, 0.3),
+            Vector3(140.0, 0.6, 0.7),
+            Vector3(260.0, 0.4, 0.5),
+        ];
+        let open = Poline::from(PolineOptions {
+            anchor_colors: Some(anchors.clone()),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        for total in [0, 1, 2, 7, 50] {
+            assert_eq!(open.colors_n(total).len(), total);
+        }
+        let colors = open.colors_n(5);
+        assert!(delta_e(colors[0], anchors[0]) < 1e-3);
+        assert!(delta_e(colors[2], anchors[1]) < 1e-3);
+        assert!(delta_e(colors[4], anchors[2]) < 1e-3);
+
+        let closed = Poline::from(PolineOptions {
+            anchor_colors: Some(anchors.clone()),
+            closed_loop: true,
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let colors = closed.colors_n(6);
+        assert_eq!(colors.len(), 6);
+        for (i, anchor) in anchors.iter().enumerate() {
+            assert!(delta_e(colors[i * 2], *anchor) < 1e-3);
+        }
+    }
+}
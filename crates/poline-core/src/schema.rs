@@ -0,0 +1,135 @@
+//! Versioning for saved palettes. Options and palettes carry the schema
+//! version they were written with, fields added later deserialize to
+//! defaults that reproduce the old output, and `migrate` upgrades older
+//! versions in place.
+
+use crate::{Poline, PolineErrors, PolineOptions};
+
+/// The schema version written by this version of poline. Version 0 is
+/// anything saved before the version field existed.
+pub const SCHEMA_VERSION: u32 = 4;
+
+impl PolineOptions {
+    /// Upgrades options saved by an older version of poline, failing on
+    /// options from a newer one
+    pub fn migrate(mut self) -> Result<Self, PolineErrors> {
+        if self.version > SCHEMA_VERSION {
+            return Err(PolineErrors::UnsupportedVersion);
+        }
+        // What each version predates:
+        // - 0: the per-axis reverse flags, color models and lightness options
+        // - 1: the anchor strategy and random ranges
+        // - 2: the point count mode, anchor duplicates, interpolation space
+        //   and hue interpolation
+        // - 3: the count limits and the seed
+        // Their serde defaults already match the old behavior, so only the
+        // version changes
+        self.version = SCHEMA_VERSION;
+        Ok(self)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("options always serialize")
+    }
+
+    /// Reads options saved by [`PolineOptions::to_json`] with any earlier
+    /// version of poline, failing when they are out of the
+    /// [`PolineOptions::validate`] limits
+    pub fn from_json(json: &str) -> Result<Self, PolineErrors> {
+        let options = serde_json::from_str::<Self>(json)
+            .map_err(|_| PolineErrors::InvalidOption)?
+            .migrate()?;
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+impl Poline {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("palettes always serialize")
+    }
+
+    /// Reads a palette saved by [`Poline::to_json`] with any earlier version
+    /// of poline. The sampled points are not read back but regenerated, so
+    /// changes to how they are stored never break old saves.
+    pub fn from_json(json: &str) -> Result<Self, PolineErrors> {
+        let mut poline: Poline =
+            serde_json::from_str(json).map_err(|_| PolineErrors::InvalidOption)?;
+        if poline.version > SCHEMA_VERSION {
+            return Err(PolineErrors::UnsupportedVersion);
+        }
+        if poline.anchor_points.len() < 2 {
+            return Err(PolineErrors::MissingArgument);
+        }
+        poline.check_counts(
+            poline.num_points.saturating_sub(2),
+            poline.anchor_points.len(),
+        )?;
+        poline.version = SCHEMA_VERSION;
+        poline.update_anchor_pairs();
+        Ok(poline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Vector3;
+
+    #[test]
+    fn loads_unversioned_options() {
+        let json = r#"{
+            "anchorColors": [[0.0, 0.5, 0.5], [180.0, 0.5, 0.5]],
+            "numPoints": 4,
+            "positionFunction": "Linear",
+            "positionFunctionX": null,
+            "positionFunctionY": null,
+            "positionFunctionZ": null,
+            "invertedLightness": false,
+            "closedLoop": false
+        }"#;
+        let options = PolineOptions::from_json(json).unwrap();
+        assert_eq!(options.version, SCHEMA_VERSION);
+        assert!(!options.smooth_seam);
+
+        let newer = json.replace("\"numPoints\"", "\"version\": 99, \"numPoints\"");
+        assert!(matches!(
+            PolineOptions::from_json(&newer),
+            Err(PolineErrors::UnsupportedVersion)
+        ));
+    }
+
+    #[test]
+    fn loads_unversioned_palettes() {
+        let point = |x: f32, y: f32, hue: f32, l: f32| {
+            format!(
+                r#"{{"x":{x},"y":{y},"z":0.9,"color":[{hue},0.9,{l}],"_inverted_lightness":false}}"#
+            )
+        };
+        let (first, last) = (point(1.0, 0.5, 0.0, 0.2), point(0.0, 0.49999997, 180.0, 0.8));
+        let json = format!(
+            r#"{{"needs_update":true,"anchor_points":[{first},{last}],"num_points":4,
+            "points":[[{first},{first}]],"position_function_x":"Sinusoidal",
+            "position_function_y":"Sinusoidal","position_function_z":"Sinusoidal",
+            "anchor_pairs":[[{first},{last}]],"connect_last_and_first_anchor":false,
+            "animation_frame":null,"inverted_lightness":false}}"#
+        );
+        let loaded = Poline::from_json(&json).unwrap();
+        assert_eq!(loaded.anchor_points[1].color, Vector3(180.0, 0.9, 0.8));
+        assert_eq!(loaded.color_model(), crate::ColorModel::Hsl);
+        assert_eq!(loaded.output_points().len(), 4);
+        assert_eq!(loaded.anchor_ids(), [0, 1]);
+    }
+
+    #[test]
+    fn palette_round_trips_through_json() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.9, 0.2), Vector3(180.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let loaded = Poline::from_json(&poline.to_json()).unwrap();
+        assert_eq!(loaded, poline);
+        assert_eq!(loaded.sampled_points(), poline.sampled_points());
+    }
+}
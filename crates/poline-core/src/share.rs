@@ -0,0 +1,307 @@
+//! Short URL-safe palette encoding for putting the whole palette state in a
+//! link. The bytes are a version, flag and easing header followed by the
+//! anchors quantized to 16 bits per channel and then the per segment hue
+//! interpolation overrides, encoded as unpadded base64url.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    types::Vector3,
+    utils::number_as_enum,
+    ColorModel, HueInterpolation, InterpolationSpace, PointCountMode, Poline, PolineErrors, PolineOptions,
+    PositionScale, SCHEMA_VERSION,
+};
+
+/// Version of the share string layout, independent of [`SCHEMA_VERSION`]
+/// since the layout only changes when the header does
+const SHARE_VERSION: u8 = 1;
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const POSITION_SCALES: u8 = PositionScale::ALL.len() as u8;
+const INTERPOLATION_SPACES: u8 = 2;
+const HEADER_LEN: usize = 10;
+/// A segment index as two bytes and its hue interpolation as one
+const OVERRIDE_LEN: usize = 3;
+
+fn base64url_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let sextets = [
+            b[0] >> 2,
+            (b[0] & 0x03) << 4 | b[1] >> 4,
+            (b[1] & 0x0f) << 2 | b[2] >> 6,
+            b[2] & 0x3f,
+        ];
+        for &sextet in &sextets[..chunk.len() + 1] {
+            encoded.push(ALPHABET[sextet as usize] as char);
+        }
+    }
+    encoded
+}
+
+fn base64url_decode(encoded: &str) -> Option<Vec<u8>> {
+    let sextets: Vec<u8> = encoded
+        .bytes()
+        .map(|c| ALPHABET.iter().position(|&a| a == c).map(|i| i as u8))
+        .collect::<Option<_>>()?;
+    if sextets.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(sextets.len() * 3 / 4);
+    for chunk in sextets.chunks(4) {
+        let s = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+            chunk.get(3).copied().unwrap_or(0),
+        ];
+        let decoded = [
+            s[0] << 2 | s[1] >> 4,
+            s[1] << 4 | s[2] >> 2,
+            s[2] << 6 | s[3],
+        ];
+        bytes.extend_from_slice(&decoded[..chunk.len() - 1]);
+    }
+    Some(bytes)
+}
+
+fn hue_interpolation(id: u8) -> Option<HueInterpolation> {
+    match id {
+        0 => Some(HueInterpolation::Shorter),
+        1 => Some(HueInterpolation::Longer),
+        2 => Some(HueInterpolation::Increasing),
+        3 => Some(HueInterpolation::Decreasing),
+        _ => None,
+    }
+}
+
+fn quantize(value: f32, max: f32) -> [u8; 2] {
+    (((value / max).clamp(0.0, 1.0) * 65535.0).round() as u16).to_le_bytes()
+}
+
+fn dequantize(bytes: [u8; 2], max: f32) -> f32 {
+    u16::from_le_bytes(bytes) as f32 / 65535.0 * max
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    /// The anchors and options as a short URL-safe string, for example to
+    /// keep in a link's `#hash`. Anchor channels are stored with 16 bits,
+    /// so hues come back within 0.01 degrees. Every per segment hue
+    /// interpolation override adds three bytes.
+    pub fn to_share_string(&self) -> String {
+        let options = self.options();
+        let flags = [
+            options.inverted_lightness,
+            options.closed_loop,
+            options.reverse_x,
+            options.reverse_y,
+            options.reverse_z,
+            options.monotonic_lightness,
+            options.smooth_seam,
+            options.point_count_mode == PointCountMode::TotalOutput,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0_u8, |flags, (bit, &set)| flags | (u8::from(set) << bit));
+        let anchors = options.anchor_colors.unwrap_or_default();
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + anchors.len() * 6);
+        bytes.push(SHARE_VERSION);
+        bytes.push(flags);
+        // The color model fits in the low nibble, so the interpolation space
+        // and hue interpolation share the high one and older strings still
+        // decode with the defaults
+        bytes.push(
+            options.color_model as u8
+                | (options.interpolation as u8) << 4
+                | (options.hue_interpolation as u8) << 6,
+        );
+        bytes.extend_from_slice(&(options.num_points.min(u16::MAX as usize) as u16).to_le_bytes());
+        bytes.push(self.position_function_x as u8);
+        bytes.push(self.position_function_y as u8);
+        bytes.push(self.position_function_z as u8);
+        bytes.extend_from_slice(&(anchors.len().min(u16::MAX as usize) as u16).to_le_bytes());
+        for Vector3(h, s, l) in anchors.into_iter().take(u16::MAX as usize) {
+            bytes.extend_from_slice(&quantize(h.rem_euclid(360.0), 360.0));
+            bytes.extend_from_slice(&quantize(s, 1.0));
+            bytes.extend_from_slice(&quantize(l, 1.0));
+        }
+        // Segment indices are below the anchor count, so they fit as well
+        for (segment, hue) in options.segment_hue_interpolations {
+            bytes.extend_from_slice(&(segment as u16).to_le_bytes());
+            bytes.push(hue as u8);
+        }
+        base64url_encode(&bytes)
+    }
+
+    /// Rebuilds a palette from [`Poline::to_share_string`]
+    pub fn from_share_string(share: &str) -> Result<Poline, PolineErrors> {
+        let bytes =
+            base64url_decode(share.trim_start_matches('#')).ok_or(PolineErrors::InvalidOption)?;
+        if bytes.len() < HEADER_LEN {
+            return Err(PolineErrors::InvalidOption);
+        }
+        if bytes[0] != SHARE_VERSION {
+            return Err(PolineErrors::UnsupportedVersion);
+        }
+        let flag = |bit: u8| bytes[1] & (1 << bit) != 0;
+        let (model, interpolation, hue) = (bytes[2] & 0x0F, bytes[2] >> 4 & 0x03, bytes[2] >> 6);
+        let color_model = ColorModel::from_id(model as usize).ok_or(PolineErrors::InvalidOption)?;
+        if interpolation >= INTERPOLATION_SPACES
+            || bytes[5..8].iter().any(|&f| f >= POSITION_SCALES)
+        {
+            return Err(PolineErrors::InvalidOption);
+        }
+        let anchor_count = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        if anchor_count < 2 {
+            return Err(PolineErrors::MissingArgument);
+        }
+        // Strings from before the overrides were encoded end with the anchors
+        let Some((anchor_bytes, override_bytes)) =
+            bytes[HEADER_LEN..].split_at_checked(anchor_count * 6)
+        else {
+            return Err(PolineErrors::InvalidOption);
+        };
+        if override_bytes.len() % OVERRIDE_LEN != 0 {
+            return Err(PolineErrors::InvalidOption);
+        }
+        let segment_hue_interpolations = override_bytes
+            .chunks(OVERRIDE_LEN)
+            .map(|c| {
+                let segment = u16::from_le_bytes([c[0], c[1]]) as usize;
+                hue_interpolation(c[2])
+                    .filter(|_| segment < anchor_count)
+                    .map(|hue| (segment, hue))
+            })
+            .collect::<Option<_>>()
+            .ok_or(PolineErrors::InvalidOption)?;
+        let anchor_colors = anchor_bytes
+            .chunks(6)
+            .map(|c| {
+                Vector3(
+                    dequantize([c[0], c[1]], 360.0),
+                    dequantize([c[2], c[3]], 1.0),
+                    dequantize([c[4], c[5]], 1.0),
+                )
+            })
+            .collect();
+        let position_function = number_as_enum(bytes[5] as usize);
+
+        let options = PolineOptions {
+            version: SCHEMA_VERSION,
+            anchor_colors: Some(anchor_colors),
+            anchor_strategy: Default::default(),
+            random_lightness_range: None,
+            random_saturation_range: None,
+            seed: None,
+            num_points: u16::from_le_bytes([bytes[3], bytes[4]]) as usize,
+            position_function,
+            position_function_x: Some(position_function),
+            position_function_y: Some(number_as_enum(bytes[6] as usize)),
+            position_function_z: Some(number_as_enum(bytes[7] as usize)),
+            inverted_lightness: flag(0),
+            closed_loop: flag(1),
+            reverse_x: flag(2),
+            reverse_y: flag(3),
+            reverse_z: flag(4),
+            monotonic_lightness: flag(5),
+            smooth_seam: flag(6),
+            // How the points are output, not part of the palette itself
+            include_anchor_duplicates: false,
+            point_count_mode: if flag(7) {
+                PointCountMode::TotalOutput
+            } else {
+                PointCountMode::PerSegment
+            },
+            color_model,
+            interpolation: if interpolation == 1 {
+                InterpolationSpace::Hsl
+            } else {
+                InterpolationSpace::Position
+            },
+            // Two bits, always one of the four
+            hue_interpolation: hue_interpolation(hue).unwrap_or_default(),
+            segment_hue_interpolations,
+            // Links come from anywhere, so they get the default limits
+            max_anchors: None,
+            max_total_points: None,
+            // How edits are rounded, not part of the palette itself
+            snapping: None,
+        };
+        Poline::try_from_options(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color_space::delta_e, positions::PositionScale};
+
+    #[test]
+    fn base64url_round_trip() {
+        for len in 0..8 {
+            let bytes: Vec<u8> = (0..len)
+                .map(|i: u8| i.wrapping_mul(37).wrapping_add(200))
+                .collect();
+            let encoded = base64url_encode(&bytes);
+            assert!(!encoded.contains(['+', '/', '=']));
+            assert_eq!(base64url_decode(&encoded).unwrap(), bytes);
+        }
+        assert_eq!(base64url_encode(b"poline"), "cG9saW5l");
+    }
+
+    #[test]
+    fn share_string_round_trip() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(12.5, 0.9, 0.2),
+                Vector3(180.0, 0.9, 0.8),
+                Vector3(300.0, 0.4, 0.5),
+            ]),
+            position_function_y: Some(PositionScale::Arc),
+            inverted_lightness: true,
+            closed_loop: true,
+            reverse_z: true,
+            interpolation: InterpolationSpace::Hsl,
+            hue_interpolation: HueInterpolation::Decreasing,
+            ..Default::default()
+        });
+        let mut poline = poline;
+        poline
+            .set_segment_hue_interpolation(1, Some(HueInterpolation::Longer))
+            .unwrap();
+        let share = poline.to_share_string();
+        assert!(share.len() < 50, "{share}");
+
+        let shared = Poline::from_share_string(&share).unwrap();
+        assert!(shared.connect_last_and_first_anchor);
+        assert_eq!(shared.position_function_y, PositionScale::Arc);
+        assert!(shared.reverse_z);
+        assert_eq!(shared.interpolation, InterpolationSpace::Hsl);
+        assert_eq!(shared.hue_interpolation, HueInterpolation::Decreasing);
+        assert_eq!(
+            shared.options().segment_hue_interpolations,
+            [(1, HueInterpolation::Longer)]
+        );
+        for (a, b) in shared.anchor_points.iter().zip(&poline.anchor_points) {
+            assert!(delta_e(a.color, b.color) < 1e-3);
+        }
+        assert!(Poline::from_share_string("not base64!").is_err());
+
+        // Without the overrides the string is what older versions wrote, and
+        // a dangling or unknown override is rejected
+        let mut bytes = base64url_decode(&share).unwrap();
+        let anchors_end = bytes.len() - OVERRIDE_LEN;
+        let older = Poline::from_share_string(&base64url_encode(&bytes[..anchors_end])).unwrap();
+        assert!(older.options().segment_hue_interpolations.is_empty());
+        bytes[anchors_end + 2] = 9;
+        assert!(Poline::from_share_string(&base64url_encode(&bytes)).is_err());
+        assert!(Poline::from_share_string(&base64url_encode(&bytes[..anchors_end + 1])).is_err());
+    }
+}
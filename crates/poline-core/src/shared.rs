@@ -0,0 +1,90 @@
+//! A palette shared between threads.
+//!
+//! [`SharedPoline`] is `Send + Sync` and cheap to clone, every clone refers
+//! to the same palette. Writers take a short exclusive lock, readers take
+//! [`SharedPoline::snapshot`]s: immutable `Arc<Poline>`s that a render or
+//! audio thread can sample for as long as it likes without holding a lock
+//! or seeing a half applied edit. An edit only copies the palette when a
+//! snapshot of the previous version is still alive.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::Poline;
+
+#[derive(Debug, Clone, Default)]
+pub struct SharedPoline {
+    current: Arc<RwLock<Arc<Poline>>>,
+}
+
+impl SharedPoline {
+    pub fn new(poline: Poline) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(poline))),
+        }
+    }
+
+    /// The palette as it is now, unaffected by later edits
+    pub fn snapshot(&self) -> Arc<Poline> {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(&current)
+    }
+
+    /// Runs `f` on the current palette under the read lock
+    pub fn read<R>(&self, f: impl FnOnce(&Poline) -> R) -> R {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        f(&current)
+    }
+
+    /// Edits the palette, other threads see the whole edit or none of it
+    ///
+    /// ```
+    /// # use poline_core::{Poline, SharedPoline};
+    /// let shared = SharedPoline::new(Poline::default());
+    /// let before = shared.snapshot();
+    /// shared.update(|poline| poline.shift_hue(10.0));
+    /// assert_ne!(*shared.snapshot(), *before);
+    /// ```
+    pub fn update<R>(&self, f: impl FnOnce(&mut Poline) -> R) -> R {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        f(Arc::make_mut(&mut current))
+    }
+
+    /// Replaces the whole palette
+    pub fn set(&self, poline: Poline) {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(poline);
+    }
+}
+
+impl From<Poline> for SharedPoline {
+    fn from(poline: Poline) -> Self {
+        Self::new(poline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::Vector3, PolineOptions};
+
+    #[test]
+    fn snapshots_are_isolated_from_edits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedPoline>();
+
+        let shared = SharedPoline::new(Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.9, 0.2), Vector3(200.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        }));
+        let before = shared.snapshot();
+
+        let writer = shared.clone();
+        std::thread::spawn(move || writer.update(|poline| poline.shift_hue(10.0)))
+            .join()
+            .unwrap();
+
+        assert_eq!(before.anchors()[0].color.0, 20.0);
+        assert_eq!(shared.read(|poline| poline.anchors()[0].color.0), 30.0);
+        assert_eq!(shared.snapshot().anchors()[1].color.0, 210.0);
+    }
+}
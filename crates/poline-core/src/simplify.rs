@@ -0,0 +1,169 @@
+use crate::{
+    color_space::hsl_to_oklab, sampling::SEARCH_SAMPLES_PER_SEGMENT, types::Vector3, Poline,
+};
+
+fn distance(a: Vector3, b: Vector3) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+/// Distance from `point` to the nearest point of the line segment `a` to `b`
+fn distance_to_segment(point: Vector3, a: Vector3, b: Vector3) -> f32 {
+    let ab = Vector3(b.0 - a.0, b.1 - a.1, b.2 - a.2);
+    let length = ab.0 * ab.0 + ab.1 * ab.1 + ab.2 * ab.2;
+    if length == 0.0 {
+        return distance(point, a);
+    }
+    let t = ((point.0 - a.0) * ab.0 + (point.1 - a.1) * ab.1 + (point.2 - a.2) * ab.2) / length;
+    let t = t.clamp(0.0, 1.0);
+    distance(point, Vector3(a.0 + t * ab.0, a.1 + t * ab.1, a.2 + t * ab.2))
+}
+
+/// Distance from `point` to the polyline through `curve`
+fn distance_to_curve(point: Vector3, curve: &[Vector3]) -> f32 {
+    curve
+        .windows(2)
+        .map(|pair| distance_to_segment(point, pair[0], pair[1]))
+        .fold(f32::INFINITY, f32::min)
+}
+
+impl Poline {
+    /// The curve in OKLab the palette would take from anchor `start`
+    /// straight to the anchor `span` anchors later, with the anchors in
+    /// between removed
+    fn shortcut_oklab(&self, start: usize, span: usize) -> Vec<Vector3> {
+        let end = (start + span) % self.anchor_points.len();
+        let mut options = self.options();
+        options.anchor_colors = Some(vec![
+            self.anchor_points[start].color,
+            self.anchor_points[end].color,
+        ]);
+        options.closed_loop = false;
+        options.segment_hue_interpolations = options
+            .segment_hue_interpolations
+            .iter()
+            .filter(|&&(segment, _)| segment == start)
+            .map(|&(_, hue)| (0, hue))
+            .collect();
+        Poline::from(options)
+            .sample_segments(SEARCH_SAMPLES_PER_SEGMENT * span)
+            .into_iter()
+            .flatten()
+            .map(|point| hsl_to_oklab(point.color))
+            .collect()
+    }
+
+    /// Ramer–Douglas–Peucker over the anchors `start + 1..start + span`,
+    /// wrapping past the last anchor: the one farthest from the shortcut
+    /// between `start` and `start + span` is kept when it is more than
+    /// `tolerance` away, and both sides of it are checked in turn
+    fn keep_anchors(&self, start: usize, span: usize, tolerance: f32, keep: &mut [bool]) {
+        if span < 2 {
+            return;
+        }
+        let n = self.anchor_points.len();
+        let shortcut = self.shortcut_oklab(start, span);
+        let farthest = (1..span)
+            .map(|offset| {
+                let anchor = hsl_to_oklab(self.anchor_points[(start + offset) % n].color);
+                (offset, distance_to_curve(anchor, &shortcut))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match farthest {
+            Some((offset, deviation)) if deviation > tolerance => {
+                keep[(start + offset) % n] = true;
+                self.keep_anchors(start, offset, tolerance, keep);
+                self.keep_anchors((start + offset) % n, span - offset, tolerance, keep);
+            }
+            _ => {}
+        }
+    }
+
+    /// Removes the anchors the palette can do without, by running
+    /// Ramer–Douglas–Peucker over them: an anchor stays when it is more than
+    /// `tolerance` (OKLab distance, about 0.02 is just noticeable) from the
+    /// curve the palette would take between the anchors kept around it. The
+    /// first anchor, the last one of an open palette and the one farthest
+    /// from the first in a closed loop are always kept. Returns the number
+    /// of anchors removed.
+    pub fn simplify_anchors(&mut self, tolerance: f32) -> usize {
+        let n = self.anchor_points.len();
+        if n <= 2 {
+            return 0;
+        }
+        let mut keep = vec![false; n];
+        keep[0] = true;
+        if self.connect_last_and_first_anchor {
+            let first = hsl_to_oklab(self.anchor_points[0].color);
+            let farthest = (1..n)
+                .max_by(|&a, &b| {
+                    let from_first =
+                        |i: usize| distance(first, hsl_to_oklab(self.anchor_points[i].color));
+                    from_first(a).total_cmp(&from_first(b))
+                })
+                .unwrap_or(1);
+            keep[farthest] = true;
+            self.keep_anchors(0, farthest, tolerance, &mut keep);
+            self.keep_anchors(farthest, n - farthest, tolerance, &mut keep);
+        } else {
+            keep[n - 1] = true;
+            self.keep_anchors(0, n - 1, tolerance, &mut keep);
+        }
+
+        let mut removed = 0;
+        for index in (0..n).rev().filter(|&index| !keep[index]) {
+            if self.remove_anchor_point_at_index(index).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{positions::PositionScale, PolineOptions};
+
+    #[test]
+    fn removes_anchors_on_the_line() {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.0, 0.1),
+                Vector3(0.0, 0.0, 0.3),
+                Vector3(0.0, 0.0, 0.5),
+                Vector3(0.0, 0.0, 0.9),
+            ]),
+            position_function: PositionScale::Linear,
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let mut strict = poline.clone();
+        assert_eq!(strict.simplify_anchors(0.0), 0);
+
+        assert_eq!(poline.simplify_anchors(0.01), 2);
+        let remaining: Vec<f32> = poline.anchors().iter().map(|point| point.color.2).collect();
+        assert_eq!(remaining, [0.1, 0.9]);
+    }
+
+    #[test]
+    fn keeps_anchors_off_the_line() {
+        let anchors = vec![
+            Vector3(0.0, 0.0, 0.1),
+            Vector3(0.0, 0.0, 0.3),
+            Vector3(0.0, 0.0, 0.5),
+            Vector3(0.0, 0.0, 0.9),
+            Vector3(0.0, 0.9, 0.5),
+        ];
+        for closed_loop in [false, true] {
+            let mut poline = Poline::from(PolineOptions {
+                anchor_colors: Some(anchors.clone()),
+                position_function: PositionScale::Linear,
+                inverted_lightness: true,
+                closed_loop,
+                ..Default::default()
+            });
+            assert_eq!(poline.simplify_anchors(0.02), 2, "{closed_loop}");
+            assert_eq!(poline.anchor_ids(), [0, 3, 4], "{closed_loop}");
+        }
+    }
+}
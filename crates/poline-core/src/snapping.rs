@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{color_point::ColorPoint, types::Vector3, Poline};
+
+/// Steps anchor colors are rounded to when they are edited, so UI driven
+/// edits produce clean and reproducible values. `None` leaves a channel as is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapping {
+    /// Degrees
+    pub hue: Option<f32>,
+    pub saturation: Option<f32>,
+    pub lightness: Option<f32>,
+}
+
+impl Snapping {
+    /// Hue to multiples of 15 degrees, saturation and lightness to 0.05
+    pub fn standard() -> Self {
+        Self {
+            hue: Some(15.0),
+            saturation: Some(0.05),
+            lightness: Some(0.05),
+        }
+    }
+
+    /// Rounds an HSL color to the configured steps
+    pub fn snap(&self, hsl: Vector3) -> Vector3 {
+        let round = |value: f32, step: Option<f32>| match step.filter(|step| *step > 0.0) {
+            Some(step) => (value / step).round() * step,
+            None => value,
+        };
+        let Vector3(h, s, l) = hsl;
+        Vector3(
+            round(h, self.hue).rem_euclid(360.0),
+            round(s, self.saturation).clamp(0.0, 1.0),
+            round(l, self.lightness).clamp(0.0, 1.0),
+        )
+    }
+}
+
+impl Poline {
+    /// Rounds anchors to `snapping` whenever they are added, updated or
+    /// dragged from now on. The setting is saved with the palette and kept
+    /// in [`Poline::options`], but not in share strings.
+    pub fn set_snapping(&mut self, snapping: Option<Snapping>) {
+        self.snapping = snapping;
+    }
+
+    pub fn snapping(&self) -> Option<Snapping> {
+        self.snapping
+    }
+
+    pub(crate) fn snap_anchor(&self, point: &mut ColorPoint) {
+        if let Some(snapping) = self.snapping {
+            point.set_hsl(snapping.snap(point.color));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color_point::ColorPointCollection, PolineOptions};
+
+    #[test]
+    fn snaps_edited_anchors() {
+        assert_eq!(
+            Snapping::standard().snap(Vector3(358.0, 0.62, 0.111)),
+            Vector3(0.0, 0.6, 0.1)
+        );
+
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.9, 0.2), Vector3(200.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        poline.set_snapping(Some(Snapping::standard()));
+        let updated = poline.update_anchor_point_at_index(
+            0,
+            ColorPointCollection {
+                xyz: None,
+                color: Some(Vector3(97.0, 0.43, 0.52)),
+                inverted_lightness: true,
+                model: Default::default(),
+            },
+        )
+        .unwrap();
+        let Vector3(h, s, l) = updated.color;
+        assert_eq!(h, 90.0);
+        assert!((s - 0.45).abs() < 1e-6 && (l - 0.5).abs() < 1e-6);
+        assert_eq!(poline.anchors()[0], updated);
+
+        // Saved and rebuilt with the palette
+        assert_eq!(poline.options().snapping, Some(Snapping::standard()));
+        let loaded = Poline::from_json(&poline.to_json()).unwrap();
+        assert_eq!(loaded.snapping(), Some(Snapping::standard()));
+        assert_eq!(Poline::from(poline.options()).snapping(), Some(Snapping::standard()));
+        let built = Poline::builder().snapping(Snapping::standard()).build().unwrap();
+        assert_eq!(built.snapping(), Some(Snapping::standard()));
+    }
+}
@@ -0,0 +1,118 @@
+//! Palettes baked into the binary. A build script generates the palette
+//! once with [`Poline::static_palette_source`], the application includes the
+//! result with [`poline_static!`] and pays no generation cost at runtime.
+//!
+//! ```ignore
+//! // build.rs
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("palettes.rs");
+//! std::fs::write(out, poline.static_palette_source("BRAND")).unwrap();
+//!
+//! // main.rs
+//! poline_core::poline_static!("palettes.rs");
+//! let [r, g, b] = BRAND.rgb[0];
+//! ```
+
+use crate::{color_space::rgb_to_hex, types::Vector3, Poline};
+
+/// Precomputed sRGB colors of a palette, usable in `const` and `static`
+/// items without allocating
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StaticPalette<const N: usize> {
+    /// sRGB colors with channels in 0..1
+    pub rgb: [[f32; 3]; N],
+    /// The same colors as CSS hex strings
+    pub hex: [&'static str; N],
+}
+
+impl<const N: usize> StaticPalette<N> {
+    pub const fn new(rgb: [[f32; 3]; N], hex: [&'static str; N]) -> Self {
+        Self { rgb, hex }
+    }
+
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// The color at `t` in 0..1, linearly interpolated between neighbouring
+    /// swatches
+    pub fn at(&self, t: f32) -> [f32; 3] {
+        let Some(last) = N.checked_sub(1) else {
+            return [0.0, 0.0, 0.0];
+        };
+        let position = t.clamp(0.0, 1.0) * last as f32;
+        let index = (position.floor() as usize).min(last.saturating_sub(1));
+        let next = (index + 1).min(last);
+        let f = position - index as f32;
+        let (a, b) = (self.rgb[index], self.rgb[next]);
+        [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * f)
+    }
+}
+
+/// Includes palettes a build script wrote to `OUT_DIR` with
+/// [`Poline::static_palette_source`]
+#[macro_export]
+macro_rules! poline_static {
+    ($file:literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $file));
+    };
+}
+
+impl Poline {
+    /// Rust source declaring `pub const {name}: StaticPalette<N>` with the
+    /// palette colors, for build scripts
+    pub fn static_palette_source(&self, name: &str) -> String {
+        let colors = self.colors_rgb();
+        let rgb: Vec<String> = colors
+            .iter()
+            .map(|Vector3(r, g, b)| format!("[{r:?}, {g:?}, {b:?}]"))
+            .collect();
+        let hex: Vec<String> = colors
+            .iter()
+            .map(|&color| format!("{:?}", rgb_to_hex(color)))
+            .collect();
+        format!(
+            "pub const {name}: poline_core::StaticPalette<{n}> = poline_core::StaticPalette::new(\n    [{rgb}],\n    [{hex}],\n);\n",
+            n = colors.len(),
+            rgb = rgb.join(", "),
+            hex = hex.join(", "),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolineOptions;
+
+    const GRAYS: StaticPalette<3> = StaticPalette::new(
+        [[0.0, 0.0, 0.0], [0.5, 0.5, 0.5], [1.0, 1.0, 1.0]],
+        ["#000000", "#808080", "#ffffff"],
+    );
+
+    #[test]
+    fn samples_baked_colors() {
+        assert_eq!(GRAYS.len(), 3);
+        assert_eq!(GRAYS.at(0.25), [0.25, 0.25, 0.25]);
+        assert_eq!(GRAYS.at(2.0), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn generates_const_source() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, 1.0)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let n = poline.colors_rgb().len();
+        let source = poline.static_palette_source("GRAYS");
+        assert!(source.starts_with(&format!(
+            "pub const GRAYS: poline_core::StaticPalette<{n}> ="
+        )));
+        assert!(source.contains("[0.0, 0.0, 0.0]"));
+        assert!(source.contains("\"#000000\""));
+    }
+}
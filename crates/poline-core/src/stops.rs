@@ -0,0 +1,158 @@
+use crate::{color_space::delta_e, types::Vector3, Poline};
+
+/// How far `point` has gone from `start` towards `end`, 0..1, measured
+/// along the straight line between them. `None` when they share a position.
+pub(crate) fn chord_fraction(start: Vector3, end: Vector3, point: Vector3) -> Option<f32> {
+    let (Vector3(x0, y0, z0), Vector3(x1, y1, z1)) = (start, end);
+    let (dx, dy, dz) = (x1 - x0, y1 - y0, z1 - z0);
+    let length_squared = dx * dx + dy * dy + dz * dz;
+    if length_squared < 1e-12 {
+        return None;
+    }
+    let Vector3(x, y, z) = point;
+    Some((((x - x0) * dx + (y - y0) * dy + (z - z0) * dz) / length_squared).clamp(0.0, 1.0))
+}
+
+/// How far the point at `index` of a sampled segment has gone from the
+/// segment's first anchor towards its last, 0..1. Eased samples bunch up
+/// the way their positions do, a segment whose anchors share a position
+/// falls back to even spacing.
+fn eased_along(positions: &[Vector3], index: usize) -> f32 {
+    let last = positions.len().saturating_sub(1);
+    let even = index as f32 / last.max(1) as f32;
+    let (Some(&start), Some(&end)) = (positions.first(), positions.last()) else {
+        return even;
+    };
+    chord_fraction(start, end, positions[index]).unwrap_or(even)
+}
+
+impl Poline {
+    /// The palette colors as gradient stops: each HSL color with where it
+    /// sits on the curve in 0..1, each segment taking an equal share as in
+    /// the continuous curve. Within a segment a stop sits as far along as
+    /// its position is between the two anchors, so stops follow the
+    /// easing instead of being spaced evenly.
+    pub fn stops(&self) -> Vec<(f32, Vector3)> {
+        let sampled = self.sampled_points();
+        let segments = sampled.segment_count().max(1) as f32;
+        let mut previous = 0.0_f32;
+        let mut stops: Vec<(f32, Vector3)> = self
+            .flattened_indices()
+            .into_iter()
+            .map(|(segment, idx)| {
+                let range = sampled.segment_range(segment);
+                let along = eased_along(&sampled.positions()[range.clone()], idx - range.start);
+                // Mixed per-axis easings can step back a little, stops may not
+                previous = previous.max((segment as f32 + along) / segments);
+                (previous, sampled.colors()[idx])
+            })
+            .collect();
+        stops.truncate(self.output_points().len());
+        stops
+    }
+
+    /// The palette colors placed by perceptual arc length, so the distance
+    /// between two stops matches their OKLab color difference. Closed loops
+    /// count the way back to the first color.
+    pub fn arc_length_stops(&self) -> Vec<(f32, Vector3)> {
+        let colors: Vec<Vector3> = self
+            .output_points()
+            .iter()
+            .map(|point| point.color)
+            .collect();
+        let mut travelled = Vec::with_capacity(colors.len());
+        let mut total = 0.0;
+        for (i, &color) in colors.iter().enumerate() {
+            if i > 0 {
+                total += delta_e(colors[i - 1], color);
+            }
+            travelled.push(total);
+        }
+        if self.connect_last_and_first_anchor {
+            if let (Some(&first), Some(&last)) = (colors.first(), colors.last()) {
+                total += delta_e(last, first);
+            }
+        }
+        if total == 0.0 {
+            let last = colors.len().saturating_sub(1).max(1) as f32;
+            return colors
+                .into_iter()
+                .enumerate()
+                .map(|(i, color)| (i as f32 / last, color))
+                .collect();
+        }
+        travelled
+            .into_iter()
+            .map(|distance| distance / total)
+            .zip(colors)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        point_count::PointCountMode, types::Vector3, Poline, PolineOptions, PositionScale,
+    };
+
+    fn options() -> PolineOptions {
+        PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.0, 0.0),
+                Vector3(0.0, 0.0, 0.2),
+                Vector3(0.0, 0.0, 1.0),
+            ]),
+            inverted_lightness: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stops_follow_the_curve_parameter() {
+        let linear = PolineOptions {
+            position_function: PositionScale::Linear,
+            ..options()
+        };
+        let even = Poline::from(linear.clone()).stops();
+        assert_eq!(even.len(), 11);
+        assert_eq!(even[0].0, 0.0);
+        assert!((even[2].0 - 0.2).abs() < 1e-6);
+        assert_eq!(even[5].0, 0.5);
+        assert_eq!(even[10].0, 1.0);
+
+        let uneven = Poline::from(PolineOptions {
+            num_points: 4,
+            point_count_mode: PointCountMode::TotalOutput,
+            ..linear
+        });
+        let positions: Vec<f32> = uneven.stops().iter().map(|stop| stop.0).collect();
+        assert_eq!(positions, [0.0, 0.25, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn stops_follow_the_easing() {
+        let stops = |position_function| {
+            let poline = Poline::from(PolineOptions {
+                position_function,
+                ..options()
+            });
+            poline.stops().iter().map(|stop| stop.0).collect::<Vec<f32>>()
+        };
+        let (linear, cubic) = (stops(PositionScale::Linear), stops(PositionScale::Cubic));
+        // The first segment eases out, at t = 0.4 it is already
+        // 1 - 0.6^3 = 0.784 of the way to the second anchor
+        assert!((linear[2] - 0.2).abs() < 1e-6);
+        assert!((cubic[2] - 0.392).abs() < 1e-4, "{}", cubic[2]);
+        assert!(cubic.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!((cubic[5], cubic[10]), (0.5, 1.0));
+    }
+
+    #[test]
+    fn arc_length_stops_follow_color_difference() {
+        let stops = Poline::from(options()).arc_length_stops();
+        assert_eq!(stops[0].0, 0.0);
+        assert!((stops[10].0 - 1.0).abs() < 1e-6);
+        // The dark segment covers far less lightness than the light one
+        assert!(stops[5].0 < 0.4, "{}", stops[5].0);
+    }
+}
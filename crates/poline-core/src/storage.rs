@@ -0,0 +1,139 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{color_point::ColorPoint, types::Vector3};
+
+/// Sampled points kept as flat structure-of-arrays buffers. The points of
+/// segment `i` are at `offsets[i]..offsets[i + 1]` in both buffers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampledPoints {
+    positions: Vec<Vector3>,
+    colors: Vec<Vector3>,
+    offsets: Vec<usize>,
+}
+
+impl Default for SampledPoints {
+    fn default() -> Self {
+        Self {
+            positions: Vec::new(),
+            colors: Vec::new(),
+            offsets: vec![0],
+        }
+    }
+}
+
+impl SampledPoints {
+    pub(crate) fn from_segments(segments: Vec<Vec<ColorPoint>>) -> Self {
+        let total = segments.iter().map(Vec::len).sum();
+        let mut sampled = Self {
+            positions: Vec::with_capacity(total),
+            colors: Vec::with_capacity(total),
+            offsets: Vec::with_capacity(segments.len() + 1),
+        };
+        sampled.offsets.push(0);
+        for segment in segments {
+            sampled.push_segment(&segment);
+        }
+        sampled
+    }
+
+    fn push_segment(&mut self, points: &[ColorPoint]) {
+        self.positions
+            .extend(points.iter().map(ColorPoint::position));
+        self.colors.extend(points.iter().map(|point| point.color));
+        self.offsets.push(self.positions.len());
+    }
+
+    /// Swaps the points of segment `index` for `points`, shifting the
+    /// following segments when the point count differs
+    pub(crate) fn replace_segment(&mut self, index: usize, points: &[ColorPoint]) {
+        let range = self.segment_range(index);
+        let old_len = range.len();
+        self.positions
+            .splice(range.clone(), points.iter().map(ColorPoint::position));
+        self.colors
+            .splice(range, points.iter().map(|point| point.color));
+        if points.len() != old_len {
+            for offset in &mut self.offsets[index + 1..] {
+                *offset = *offset + points.len() - old_len;
+            }
+        }
+    }
+
+    /// Number of points over all segments
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Where segment `index` lives in the flat buffers
+    pub fn segment_range(&self, index: usize) -> Range<usize> {
+        self.offsets[index]..self.offsets[index + 1]
+    }
+
+    /// Positions in the unit cube of every point, segment after segment
+    pub fn positions(&self) -> &[Vector3] {
+        &self.positions
+    }
+
+    /// HSL colors of every point, segment after segment
+    pub fn colors(&self) -> &[Vector3] {
+        &self.colors
+    }
+
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    pub fn segment_positions(&self, index: usize) -> &[Vector3] {
+        &self.positions[self.segment_range(index)]
+    }
+
+    pub fn segment_colors(&self, index: usize) -> &[Vector3] {
+        &self.colors[self.segment_range(index)]
+    }
+
+    pub(crate) fn positions_and_colors_mut(&mut self) -> (&mut [Vector3], &mut [Vector3]) {
+        (&mut self.positions, &mut self.colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color_model::ColorModel, color_point::ColorPointCollection};
+
+    fn points(lightness: &[f32]) -> Vec<ColorPoint> {
+        lightness
+            .iter()
+            .map(|&l| {
+                ColorPoint::new(ColorPointCollection {
+                    xyz: None,
+                    color: Some(Vector3(0.0, 0.0, l)),
+                    inverted_lightness: true,
+                    model: ColorModel::LightnessOnZ,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replacing_a_segment_shifts_the_rest() {
+        let mut sampled =
+            SampledPoints::from_segments(vec![points(&[0.0, 0.5]), points(&[0.5, 1.0])]);
+        assert_eq!(sampled.offsets(), [0, 2, 4]);
+
+        sampled.replace_segment(0, &points(&[0.0, 0.25, 0.5]));
+        assert_eq!(sampled.offsets(), [0, 3, 5]);
+        assert_eq!(sampled.segment_colors(1)[1], Vector3(0.0, 0.0, 1.0));
+        assert_eq!(sampled.len(), 5);
+    }
+}
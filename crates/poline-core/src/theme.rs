@@ -0,0 +1,200 @@
+//! Variants and roles for using a palette as a UI theme. The first and
+//! last anchors are taken as background and text.
+
+use crate::{
+    color_space::{hsl_to_oklab, oklab_to_hsl, oklab_to_oklch, oklch_to_oklab},
+    contrast::{contrast_ratio, WcagLevel},
+    types::Vector3,
+    Poline,
+};
+
+/// Chroma kept by the dark variant, saturated colors glare on dark
+/// backgrounds at the chroma that reads well on light ones
+const DARK_CHROMA: f32 = 0.85;
+
+/// Contrast against the background a primary or secondary color should
+/// reach, the WCAG minimum for UI components
+const ROLE_CONTRAST: f32 = 3.0;
+
+/// `moving` with its HSL lightness pushed towards white or black just far
+/// enough to reach `target` against `fixed`, or all the way when it can't
+fn push_lightness(fixed: Vector3, moving: Vector3, to_white: bool, target: f32) -> Vector3 {
+    let Vector3(h, s, start) = moving;
+    let at = |l: f32| Vector3(h, s, l);
+    let end = if to_white { 1.0 } else { 0.0 };
+    if contrast_ratio(fixed, at(end)) < target {
+        return at(end);
+    }
+    // The ratio may dip while `moving` passes `fixed` but meets the target
+    // only once on the way to the end
+    let (mut failing, mut passing) = (start, end);
+    for _ in 0..24 {
+        let mid = (failing + passing) / 2.0;
+        if contrast_ratio(fixed, at(mid)) >= target {
+            passing = mid;
+        } else {
+            failing = mid;
+        }
+    }
+    at(passing)
+}
+
+impl Poline {
+    /// The palette rebuilt from `colors` with inverted lightness, so the
+    /// anchors' lightness carries through rather than every anchor sitting
+    /// on the rim of the color wheel
+    pub fn with_anchor_colors(&self, colors: Vec<Vector3>) -> Poline {
+        let mut options = self.options();
+        options.anchor_colors = Some(colors);
+        options.inverted_lightness = true;
+        Poline::from(options)
+    }
+
+    fn anchor_colors(&self) -> Vec<Vector3> {
+        self.anchor_points.iter().map(|anchor| anchor.color).collect()
+    }
+
+    /// The palette for dark backgrounds: every anchor's OKLab lightness is
+    /// flipped while its OKLCH hue stays and its chroma is toned down, so
+    /// the colors keep their character instead of turning into a negative
+    /// as [`Poline::inverted`] does
+    pub fn dark_variant(&self) -> Poline {
+        let colors = self
+            .anchor_colors()
+            .into_iter()
+            .map(|hsl| {
+                let Vector3(l, c, h) = oklab_to_oklch(hsl_to_oklab(hsl));
+                oklab_to_hsl(oklch_to_oklab(Vector3(1.0 - l, c * DARK_CHROMA, h)))
+            })
+            .collect();
+        self.with_anchor_colors(colors)
+    }
+
+    /// The palette with its text and, when needed, background anchor
+    /// adjusted to meet `level`, or the best ratio it reached when even
+    /// that falls short
+    pub fn meet_contrast(&self, level: WcagLevel) -> Result<Poline, f32> {
+        let target = level.min_ratio();
+        let mut colors = self.anchor_colors();
+        let last = colors.len() - 1;
+        let (background, text) = (colors[0], colors[last]);
+        if contrast_ratio(background, text) >= target {
+            return Ok(self.clone());
+        }
+        let (white, black) = (Vector3(0.0, 0.0, 1.0), Vector3(0.0, 0.0, 0.0));
+        let to_white = contrast_ratio(background, white) >= contrast_ratio(background, black);
+        let text = push_lightness(background, text, to_white, target);
+        let background = push_lightness(text, background, !to_white, target);
+        let ratio = contrast_ratio(background, text);
+        if ratio < target {
+            return Err(ratio);
+        }
+        colors[0] = background;
+        colors[last] = text;
+        Ok(self.with_anchor_colors(colors))
+    }
+
+    /// Palette indices for the background, surface, primary, secondary and
+    /// text roles. Primary and secondary are the most saturated of the
+    /// middle colors that stand out from the background, falling back to
+    /// the ones with the most contrast.
+    pub fn assign_roles(&self) -> Vec<(&'static str, usize)> {
+        let colors = self.colors_vec();
+        let Some(&background) = colors.first() else {
+            return Vec::new();
+        };
+        let contrast = |i: usize| contrast_ratio(background, colors[i]);
+        let text = (1..colors.len())
+            .max_by(|&a, &b| contrast(a).total_cmp(&contrast(b)))
+            .unwrap_or(0);
+        let mut roles = vec![("background", 0)];
+        let mut middle: Vec<usize> = (1..colors.len()).filter(|&i| i != text).collect();
+        if let Some(&surface) =
+            middle.iter().min_by(|&&a, &&b| contrast(a).total_cmp(&contrast(b)))
+        {
+            roles.push(("surface", surface));
+            middle.retain(|&i| i != surface);
+        }
+        middle.sort_by(|&a, &b| {
+            let key = |i: usize| (contrast(i) >= ROLE_CONTRAST, colors[i].1, contrast(i));
+            let (a, b) = (key(a), key(b));
+            b.0.cmp(&a.0)
+                .then(b.1.total_cmp(&a.1))
+                .then(b.2.total_cmp(&a.2))
+        });
+        roles.extend(["primary", "secondary"].into_iter().zip(middle));
+        roles.push(("text", text));
+        roles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color_space::{hsl_to_oklab, oklab_to_oklch},
+        contrast::{contrast_ratio, WcagLevel},
+        Poline, PolineOptions, Vector3,
+    };
+
+    fn grays(high: f32) -> Poline {
+        Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(0.0, 0.0, high)]),
+            inverted_lightness: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn meets_the_contrast_level() {
+        for poline in [grays(0.3), grays(0.3).dark_variant()] {
+            let poline = poline.meet_contrast(WcagLevel::AAA).unwrap();
+            let anchors = poline.anchors();
+            let ratio = contrast_ratio(anchors[0].color, anchors[anchors.len() - 1].color);
+            assert!(ratio >= 7.0, "{ratio}");
+        }
+        // Enough contrast already, nothing moves
+        let enough = grays(1.0).meet_contrast(WcagLevel::AAA).unwrap();
+        assert_eq!(enough.anchors(), grays(1.0).anchors());
+
+        let flat = grays(0.0).meet_contrast(WcagLevel::AA);
+        assert!(flat.is_ok());
+    }
+
+    #[test]
+    fn dark_variant_flips_perceptual_lightness() {
+        let light = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(210.0, 0.6, 0.95), Vector3(210.0, 0.8, 0.2)]),
+            ..Default::default()
+        });
+        let dark = light.dark_variant();
+        for (light, dark) in light.anchors().iter().zip(dark.anchors()) {
+            let (light, dark) = (
+                oklab_to_oklch(hsl_to_oklab(light.color)),
+                oklab_to_oklch(hsl_to_oklab(dark.color)),
+            );
+            assert!((light.0 + dark.0 - 1.0).abs() < 0.02, "{light:?} {dark:?}");
+            assert!((light.2 - dark.2).abs() < 2.0, "{light:?} {dark:?}");
+        }
+    }
+
+    #[test]
+    fn assigns_roles_to_distinct_colors() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![
+                Vector3(0.0, 0.0, 1.0),
+                Vector3(220.0, 0.9, 0.45),
+                Vector3(0.0, 0.0, 0.0),
+            ]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let roles = poline.assign_roles();
+        let names: Vec<&str> = roles.iter().map(|(role, _)| *role).collect();
+        assert_eq!(names, ["background", "surface", "primary", "secondary", "text"]);
+        let mut indices: Vec<usize> = roles.iter().map(|(_, index)| *index).collect();
+        assert_eq!(roles[4].1, poline.colors_vec().len() - 1);
+        indices.sort();
+        indices.dedup();
+        assert_eq!(indices.len(), 5);
+    }
+}
@@ -0,0 +1,22 @@
+//! Instrumentation for the `tracing` feature. Palette work runs in debug
+//! spans, and counters are trace events with `monotonic_counter.` fields,
+//! which metrics layers such as tracing-opentelemetry's sum up. Without
+//! the feature both macros expand to nothing.
+
+/// Enters a debug span until the end of the enclosing block
+macro_rules! span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($args)*).entered();
+    };
+}
+
+/// Adds to the `monotonic_counter.` fields given
+macro_rules! count {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($args)*);
+    };
+}
+
+pub(crate) use {count, span};
@@ -0,0 +1,107 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    color_space::{hsl_to_oklab, oklab_to_hsl, oklab_to_oklch, oklch_to_oklab},
+    types::Vector3,
+    Poline,
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Poline {
+    /// A copy of the palette with every anchor's hue rotated by `angle`
+    /// degrees, the non-mutating form of `shift_hue`
+    pub fn rotated(&self, angle: f32) -> Poline {
+        let mut rotated = self.clone();
+        rotated.shift_hue(angle);
+        rotated
+    }
+
+    /// A copy of the palette with the opposite hues
+    pub fn complement(&self) -> Poline {
+        self.rotated(180.0)
+    }
+
+    /// A photographic negative of the palette: every anchor's OKLab
+    /// lightness is flipped and its OKLCH hue turned by 180 degrees. Unlike
+    /// the `inverted_lightness` option this changes the colors themselves.
+    pub fn inverted(&self) -> Poline {
+        let mut inverted = self.clone();
+        inverted.map_anchor_colors(|hsl| {
+            let Vector3(l, c, h) = oklab_to_oklch(hsl_to_oklab(hsl));
+            oklab_to_hsl(oklch_to_oklab(Vector3(
+                1.0 - l,
+                c,
+                (h + 180.0).rem_euclid(360.0),
+            )))
+        });
+        inverted
+    }
+
+    /// Multiplies every anchor's saturation by `factor`, clamped to 0..1,
+    /// so 0.8 makes the palette 20% more muted
+    pub fn scale_saturation(&mut self, factor: f32) {
+        self.map_anchor_colors(|Vector3(h, s, l)| Vector3(h, (s * factor).clamp(0.0, 1.0), l));
+    }
+
+    /// Multiplies every anchor's lightness by `factor`, clamped to 0..1
+    pub fn scale_lightness(&mut self, factor: f32) {
+        self.map_anchor_colors(|Vector3(h, s, l)| Vector3(h, s, (l * factor).clamp(0.0, 1.0)));
+    }
+}
+
+impl Poline {
+    pub(crate) fn map_anchor_colors(&mut self, f: impl Fn(Vector3) -> Vector3) {
+        self.anchor_points
+            .iter_mut()
+            .for_each(|point| point.set_hsl(f(point.color)));
+        self.update_anchor_pairs();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{color_space::hsl_to_oklab, types::Vector3, Poline, PolineOptions};
+
+    #[test]
+    fn rotation_leaves_the_original() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.9, 0.2), Vector3(300.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let complement = poline.complement();
+        assert_eq!(poline.anchors()[0].color.0, 20.0);
+        assert_eq!(complement.anchors()[0].color.0, 200.0);
+        assert_eq!(complement.anchors()[1].color.0, 120.0);
+        assert_eq!(poline.rotated(-40.0).anchors()[0].color.0, 340.0);
+    }
+
+    #[test]
+    fn scaling_is_multiplicative_and_clamped() {
+        let mut poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(20.0, 0.5, 0.2), Vector3(300.0, 0.9, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        poline.scale_saturation(0.8);
+        assert!((poline.anchors()[0].color.1 - 0.4).abs() < 1e-6);
+        poline.scale_lightness(2.0);
+        assert!((poline.anchors()[0].color.2 - 0.4).abs() < 1e-6);
+        assert_eq!(poline.anchors()[1].color.2, 1.0);
+    }
+
+    #[test]
+    fn inverting_swaps_dark_and_light() {
+        let poline = Poline::from(PolineOptions {
+            anchor_colors: Some(vec![Vector3(0.0, 0.0, 0.0), Vector3(210.0, 0.6, 0.8)]),
+            inverted_lightness: true,
+            ..Default::default()
+        });
+        let inverted = poline.inverted();
+        let lightness = |hsl: Vector3| hsl_to_oklab(hsl).0;
+        assert!((lightness(inverted.anchors()[0].color) - 1.0).abs() < 1e-3);
+        let light = lightness(poline.anchors()[1].color);
+        assert!((lightness(inverted.anchors()[1].color) - (1.0 - light)).abs() < 1e-2);
+    }
+}
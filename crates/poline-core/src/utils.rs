@@ -1,12 +1,17 @@
 use std::f32::consts::PI;
 
-use rand::random;
+use rand::{thread_rng, Rng};
 
 use crate::{
+    color_model::ColorModel,
+    color_space::{hsl_to_oklab, oklab_to_hsl, oklab_to_oklch, oklch_to_oklab, OKLCH_MAX_CHROMA},
     positions::{PositionScale, position_from_scale},
     types::{PartialVector3, Vector2, Vector3},
 };
 
+const DEGREES_PER_RADIAN: f32 = 180.0 / PI;
+const RADIANS_PER_DEGREE: f32 = PI / 180.0;
+
 pub fn optional_vector3(vector3: Vector3) -> PartialVector3 {
     let Vector3(x, y, z) = vector3;
     PartialVector3(Some(x), Some(y), Some(z))
@@ -27,6 +32,12 @@ pub fn number_as_enum(scale_num: usize) -> PositionScale {
     }
 }
 
+/// The color model numbered `model_num`, panicking on unknown numbers.
+/// Use [`ColorModel::from_id`] for numbers that may be out of range.
+pub fn number_as_color_model(model_num: usize) -> ColorModel {
+    ColorModel::from_id(model_num).expect("color model number out of range")
+}
+
 ///
 /// Converts the given (x, y, z) coordinate to an HSL color
 /// The (x, y) values are used to calculate the hue, while the z value is used as the saturation
@@ -43,17 +54,19 @@ pub fn point_to_hsl(xyz: Vector3, inverted_lightness: bool) -> Vector3 {
     let cx = 0.5_f32;
     let cy = 0.5_f32;
 
+    let (dx, dy) = (x - cx, y - cy);
+
     // Calculate the angle between the point (x, y) and the center (cx, cy)
-    let radians = (y - cy).atan2(x - cx);
+    let radians = dy.atan2(dx);
 
     // Convert the angle to degrees and shift it so that it goes from 0 to 360
-    let mut deg = radians * (180_f32 / PI);
+    let mut deg = radians * DEGREES_PER_RADIAN;
     deg = (360_f32 + deg) % 360_f32;
 
     // The saturation value is taken from the z coordinate
     let s = z;
 
-    let dist = ((y - cy).powf(2_f32) + (x - cx).powf(2_f32)).sqrt();
+    let dist = (dx * dx + dy * dy).sqrt();
     let l = dist / cx;
 
     let lightness = if inverted_lightness { 1_f32 - l } else { l };
@@ -78,7 +91,7 @@ pub fn hsl_to_point(hsl: Vector3, inverted_lightness: bool) -> Vector3 {
     let cx = 0.5;
     let cy = 0.5;
     // Calculate the angle in radians based on the hue value
-    let radians = h / (180.0 / PI);
+    let (sin, cos) = (h * RADIANS_PER_DEGREE).sin_cos();
 
     // Calculate the distance from the center based on the lightness value
     let dist = if inverted_lightness {
@@ -88,57 +101,168 @@ pub fn hsl_to_point(hsl: Vector3, inverted_lightness: bool) -> Vector3 {
     };
 
     // Calculate the x and y coordinates based on the distance and angle
-    let x = cx + dist * radians.cos();
-    let y = cy + dist * radians.sin();
+    let x = cx + dist * cos;
+    let y = cy + dist * sin;
     // The z coordinate is equal to the saturation value
     let z = s;
     // Return the (x, y, z) coordinate as an array [x, y, z]
     Vector3(x, y, z)
 }
 
+///
+/// Converts the given (x, y, z) coordinate to an HSL color with lightness on the z axis
+/// The (x, y) values are used to calculate the hue and their distance from the center
+/// (0.5, 0.5) is used as the saturation, while the z value is used as the lightness
+///
+/// point_to_hsl_lightness_z((1.0, 0.5, 0.5), false); // [0, 1, 0.5]
+/// point_to_hsl_lightness_z((0.5, 0.5, 1.0), false); // [0, 0, 1]
+///
+pub fn point_to_hsl_lightness_z(xyz: Vector3, inverted_lightness: bool) -> Vector3 {
+    let Vector3(x, y, z) = xyz;
+    let cx = 0.5_f32;
+    let cy = 0.5_f32;
+
+    let (dx, dy) = (x - cx, y - cy);
+
+    let radians = dy.atan2(dx);
+    let mut deg = radians * DEGREES_PER_RADIAN;
+    deg = (360_f32 + deg) % 360_f32;
+
+    let dist = (dx * dx + dy * dy).sqrt();
+    let s = dist / cx;
+
+    let lightness = if inverted_lightness { 1_f32 - z } else { z };
+    Vector3(deg, s, lightness)
+}
+
+///
+/// Converts the given HSL color to an (x, y, z) coordinate with lightness on the z axis
+/// The hue value is used to calculate the angle around the center (0.5, 0.5), the saturation
+/// value is used as the distance from it and the lightness value becomes the z coordinate
+///
+/// hsl_to_point_lightness_z([0, 1, 0.5], false); // [1, 0.5, 0.5]
+/// hsl_to_point_lightness_z([0, 0, 1], false); // [0.5, 0.5, 1]
+///
+pub fn hsl_to_point_lightness_z(hsl: Vector3, inverted_lightness: bool) -> Vector3 {
+    let Vector3(h, s, l) = hsl;
+    let cx = 0.5;
+    let cy = 0.5;
+    let (sin, cos) = (h * RADIANS_PER_DEGREE).sin_cos();
+    let dist = s * cx;
+
+    let x = cx + dist * cos;
+    let y = cy + dist * sin;
+    let z = if inverted_lightness { 1.0 - l } else { l };
+    Vector3(x, y, z)
+}
+
+///
+/// Converts the given (x, y, z) coordinate to an HSL color through the OKLCH cylinder
+/// The (x, y) values are used to calculate the OKLCH hue and their distance from the center
+/// (0.5, 0.5) is used as the chroma, while the z value is used as the OKLab lightness
+///
+pub fn point_to_hsl_oklch(xyz: Vector3, inverted_lightness: bool) -> Vector3 {
+    let Vector3(x, y, z) = xyz;
+    let cx = 0.5_f32;
+    let cy = 0.5_f32;
+
+    let (dx, dy) = (x - cx, y - cy);
+
+    let deg = (dy.atan2(dx) * DEGREES_PER_RADIAN).rem_euclid(360.0);
+    let dist = (dx * dx + dy * dy).sqrt();
+    let chroma = (dist / cx) * OKLCH_MAX_CHROMA;
+    let lightness = if inverted_lightness { 1_f32 - z } else { z };
+
+    oklab_to_hsl(oklch_to_oklab(Vector3(lightness, chroma, deg)))
+}
+
+///
+/// Converts the given HSL color to an (x, y, z) coordinate in the OKLCH cylinder
+/// The OKLCH hue is used as the angle around the center (0.5, 0.5), the chroma as the
+/// distance from it and the OKLab lightness becomes the z coordinate
+///
+pub fn hsl_to_point_oklch(hsl: Vector3, inverted_lightness: bool) -> Vector3 {
+    let Vector3(l, c, h) = oklab_to_oklch(hsl_to_oklab(hsl));
+    let cx = 0.5;
+    let cy = 0.5;
+    let (sin, cos) = (h * RADIANS_PER_DEGREE).sin_cos();
+    let dist = (c / OKLCH_MAX_CHROMA).min(1.0) * cx;
+
+    let x = cx + dist * cos;
+    let y = cy + dist * sin;
+    let z = if inverted_lightness { 1.0 - l } else { l };
+    Vector3(x, y, z)
+}
+
+/// A uniformly random number between `range.0` and `range.1`
+pub(crate) fn random_in(rng: &mut impl Rng, range: Vector2) -> f32 {
+    range.0 + rng.gen::<f32>() * (range.1 - range.0)
+}
+
 pub fn random_hsl_pair(
     start_hue: Option<f32>,
     saturations: Option<Vector2>,
     lightnesses: Option<Vector2>,
 ) -> Vec<Vector3> {
-    let start_hue = start_hue.unwrap_or(random::<f32>() * 360.0);
-    let saturations = saturations.unwrap_or(Vector2(random(), random()));
-    let lightnesses = lightnesses.unwrap_or(Vector2(
-        0.75 + random::<f32>() * 0.2,
-        0.3 + random::<f32>() * 0.2,
-    ));
+    random_hsl_pair_with_rng(&mut thread_rng(), start_hue, saturations, lightnesses)
+}
+
+/// [`random_hsl_pair`] drawn from `rng`, e.g. a seeded `StdRng` for the
+/// same pair on every run
+pub fn random_hsl_pair_with_rng(
+    rng: &mut impl Rng,
+    start_hue: Option<f32>,
+    saturations: Option<Vector2>,
+    lightnesses: Option<Vector2>,
+) -> Vec<Vector3> {
+    let start_hue = start_hue.unwrap_or_else(|| rng.gen::<f32>() * 360.0);
+    let saturations = saturations.unwrap_or_else(|| Vector2(rng.gen(), rng.gen()));
+    let lightnesses = lightnesses.unwrap_or_else(|| {
+        Vector2(0.75 + rng.gen::<f32>() * 0.2, 0.3 + rng.gen::<f32>() * 0.2)
+    });
     vec![
         Vector3(start_hue, saturations.0, lightnesses.0),
         Vector3(
-            (start_hue + 60.0 + random::<f32>() * 180.0) % 360.0,
+            (start_hue + 60.0 + rng.gen::<f32>() * 180.0) % 360.0,
             saturations.1,
             lightnesses.1,
         ),
     ]
 }
 
-#[allow(dead_code)]
 pub fn random_hsl_triple(
     start_hue: Option<f32>,
     saturations: Option<Vector3>,
     lightnesses: Option<Vector3>,
 ) -> Vec<Vector3> {
-    let start_hue = start_hue.unwrap_or(random::<f32>() * 360.0);
-    let saturations = saturations.unwrap_or(Vector3(random(), random(), random()));
-    let lightnesses = lightnesses.unwrap_or(Vector3(
-        0.75 + random::<f32>() * 0.2,
-        0.3 + random::<f32>() * 0.2,
-        0.75 + random::<f32>() * 0.2,
-    ));
+    random_hsl_triple_with_rng(&mut thread_rng(), start_hue, saturations, lightnesses)
+}
+
+/// [`random_hsl_triple`] drawn from `rng`
+pub fn random_hsl_triple_with_rng(
+    rng: &mut impl Rng,
+    start_hue: Option<f32>,
+    saturations: Option<Vector3>,
+    lightnesses: Option<Vector3>,
+) -> Vec<Vector3> {
+    let start_hue = start_hue.unwrap_or_else(|| rng.gen::<f32>() * 360.0);
+    let saturations = saturations.unwrap_or_else(|| Vector3(rng.gen(), rng.gen(), rng.gen()));
+    let lightnesses = lightnesses.unwrap_or_else(|| {
+        Vector3(
+            0.75 + rng.gen::<f32>() * 0.2,
+            0.3 + rng.gen::<f32>() * 0.2,
+            0.75 + rng.gen::<f32>() * 0.2,
+        )
+    });
     vec![
         Vector3(start_hue, saturations.0, lightnesses.0),
         Vector3(
-            (start_hue + 60.0 + random::<f32>() * 180.0) % 360.0,
+            (start_hue + 60.0 + rng.gen::<f32>() * 180.0) % 360.0,
             saturations.1,
             lightnesses.1,
         ),
         Vector3(
-            (start_hue + 60.0 + random::<f32>() * 180.0) % 360.0,
+            (start_hue + 60.0 + rng.gen::<f32>() * 180.0) % 360.0,
             saturations.2,
             lightnesses.2,
         ),
@@ -153,29 +277,32 @@ fn _invert(number: f32, invert: bool) -> f32 {
     }
 }
 
+/// Interpolates between `p1` and `p2` at `t`, easing each axis with its
+/// position function. `invert` selects the reversed (ease-out) form per axis.
 pub fn vector_on_line(
     t: f32,
     p1: Vector3,
     p2: Vector3,
-    invert: bool,
+    invert: [bool; 3],
     fx: Option<PositionScale>,
     fy: Option<PositionScale>,
     fz: Option<PositionScale>,
 ) -> Vector3 {
+    let [invert_x, invert_y, invert_z] = invert;
     let t_modified_x = if let Some(fx) = fx {
-        position_from_scale(fx,t, invert)
+        position_from_scale(fx, t, invert_x)
     } else {
-        _invert(t, invert)
+        _invert(t, invert_x)
     };
     let t_modified_y = if let Some(fy) = fy {
-        position_from_scale(fy,t, invert)
+        position_from_scale(fy, t, invert_y)
     } else {
-        _invert(t, invert)
+        _invert(t, invert_y)
     };
     let t_modified_z = if let Some(fz) = fz {
-        position_from_scale(fz, t,invert)
+        position_from_scale(fz, t, invert_z)
     } else {
-        _invert(t, invert)
+        _invert(t, invert_z)
     };
 
     let x = (1.0 - t_modified_x) * p1.0 + t_modified_x * p2.0;
@@ -185,11 +312,13 @@ pub fn vector_on_line(
     Vector3(x, y, z)
 }
 
+/// `num_points` points from `p1` to `p2`, both included, spaced evenly in
+/// `t` before easing as in [`vector_on_line`]. 4 points when `None`.
 pub fn vectors_on_line(
     p1: Vector3,
     p2: Vector3,
     num_points: Option<usize>,
-    invert: bool,
+    invert: [bool; 3],
     fx: Option<PositionScale>,
     fy: Option<PositionScale>,
     fz: Option<PositionScale>,
@@ -197,8 +326,11 @@ pub fn vectors_on_line(
     let num_points = num_points.unwrap_or(4);
     let mut points = Vec::new();
 
+    // A single point sits on `p1` rather than dividing by zero
+    let last = num_points.saturating_sub(1).max(1) as f32;
     for i in 0..num_points {
-        let point = vector_on_line((i / (num_points - 1)) as f32, p1, p2, invert, fx, fy, fz);
+        let t = i as f32 / last;
+        let point = vector_on_line(t, p1, p2, invert, fx, fy, fz);
         points.push(point);
     }
 
@@ -206,7 +338,9 @@ pub fn vectors_on_line(
 }
 
 ///
-/// Calculates the distance between two points
+/// Calculates the distance between two points, ignoring the axes either of
+/// them leaves out. With `hue_mode` the first axis is a hue in degrees, and
+/// its difference goes the short way around the wheel as a fraction of it.
 /// let p1 = (Some(0.0), Some(0.0), Some(0.0));
 /// let p2 = (Some(1.0), Some(1.0), Some(1.0));
 /// let dist = distance(p1, p2, false);
@@ -234,11 +368,27 @@ pub fn distance(p1: PartialVector3, p2: PartialVector3, hue_mode: bool) -> f32 {
     (a * a + b * b + c * c).sqrt()
 }
 
+/// The CRC-32 checksum used by zip and PNG
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         types::{PartialVector3, Vector3},
-        utils::{distance, hsl_to_point, point_to_hsl},
+        positions::PositionScale,
+        utils::{
+            distance, hsl_to_point, hsl_to_point_lightness_z, point_to_hsl,
+            point_to_hsl_lightness_z, vector_on_line, vectors_on_line,
+        },
     };
 
     #[test]
@@ -283,4 +433,41 @@ mod tests {
         let p2 = PartialVector3(Some(1.0), Some(1.0), Some(1.0));
         assert_eq!(distance(p1, p2, false), 1.732_050_8);
     }
+
+    #[test]
+    fn vectors_on_line_spaces_points_between_the_ends() {
+        let linear = Some(PositionScale::Linear);
+        let xs: Vec<f32> = vectors_on_line(
+            Vector3(0.0, 0.0, 0.0),
+            Vector3(1.0, 1.0, 1.0),
+            Some(5),
+            [false; 3],
+            linear,
+            linear,
+            linear,
+        )
+        .iter()
+        .map(|point| point.0)
+        .collect();
+        assert_eq!(xs, [0.0, 0.25, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn vector_on_line_reverses_per_axis() {
+        let p1 = Vector3(0.0, 0.0, 0.0);
+        let p2 = Vector3(1.0, 1.0, 1.0);
+        let scale = Some(PositionScale::Exponential);
+        let eased = vector_on_line(0.5, p1, p2, [false, true, false], scale, scale, scale);
+        assert_eq!(eased, Vector3(0.25, 0.75, 0.25));
+    }
+
+    #[test]
+    fn lightness_on_z_round_trip() {
+        let hsl = Vector3(0.0, 1.0, 0.25);
+        let point = hsl_to_point_lightness_z(hsl, false);
+        assert_eq!(point, Vector3(1.0, 0.5, 0.25));
+        assert_eq!(point_to_hsl_lightness_z(point, false), hsl);
+        assert_eq!(point_to_hsl_lightness_z(point, true), Vector3(0.0, 1.0, 0.75));
+    }
 }
+
@@ -0,0 +1,88 @@
+//! Renders palette previews over HTTP, for README badges and the like:
+//!
+//! ```text
+//! GET /palette.svg?anchors=ff8800,2255aa&easing=arc
+//! GET /palette.png?seed=42&width=600&height=60
+//! ```
+//!
+//! `GET /openapi.json` describes the routes for client generators.
+//!
+//! Listens on `POLINE_SERVER_ADDR`, 127.0.0.1:8080 by default.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+use poline_core::PolineErrors;
+
+mod openapi;
+mod query;
+mod routes;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ServerError {
+    #[error("bad query: {0}")]
+    BadQuery(&'static str),
+    #[error("{0}")]
+    Poline(#[from] PolineErrors),
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    }
+}
+
+fn serve(stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The headers are not needed, but have to be read before answering
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => routes::handle(method, target),
+        _ => routes::handle("", ""),
+    };
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Cache-Control: public, max-age=86400\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn main() -> io::Result<()> {
+    let addr = std::env::var("POLINE_SERVER_ADDR").unwrap_or_else(|_| "127.0.0.1:8080".into());
+    let listener = TcpListener::bind(&addr)?;
+    eprintln!("poline-server listening on http://{addr}");
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    if let Err(err) = serve(stream) {
+                        eprintln!("poline-server: {err}");
+                    }
+                });
+            }
+            Err(err) => eprintln!("poline-server: {err}"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! The OpenAPI 3 document served at `/openapi.json`, built from the same
+//! parameter list the query parser matches on so that clients generated from it stay in sync.
+
+use serde_json::{json, Value};
+
+use crate::query::Parameter;
+
+/// The preview routes as (path, media type, summary)
+pub const PREVIEWS: [(&str, &str, &str); 2] = [
+    (
+        "/palette.svg",
+        "image/svg+xml",
+        "The palette as an SVG gradient",
+    ),
+    ("/palette.png", "image/png", "The palette as a PNG gradient"),
+];
+
+fn parameters() -> Vec<Value> {
+    Parameter::ALL
+        .iter()
+        .map(|&parameter| {
+            let mut schema = json!({ "type": parameter.kind() });
+            if let Some((minimum, maximum)) = parameter.bounds() {
+                schema["minimum"] = json!(minimum);
+                schema["maximum"] = json!(maximum);
+            }
+            if parameter == Parameter::Easing {
+                schema["enum"] = json!(Parameter::easings());
+            }
+            json!({
+                "name": parameter.name(),
+                "in": "query",
+                "required": false,
+                "description": parameter.description(),
+                "schema": schema,
+            })
+        })
+        .collect()
+}
+
+pub fn document() -> Value {
+    let mut paths = serde_json::Map::new();
+    for (path, media_type, summary) in PREVIEWS {
+        paths.insert(
+            path.to_string(),
+            json!({
+                "get": {
+                    "summary": summary,
+                    "parameters": parameters(),
+                    "responses": {
+                        "200": {
+                            "description": summary,
+                            "content": { media_type: { "schema": { "type": "string", "format": "binary" } } },
+                        },
+                        "400": {
+                            "description": "Invalid query parameters",
+                            "content": { "text/plain": { "schema": { "type": "string" } } },
+                        },
+                    },
+                },
+            }),
+        );
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "poline-server",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Palette previews rendered by poline",
+        },
+        "paths": paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::PaletteQuery;
+
+    #[test]
+    fn documents_every_parameter() {
+        let document = document();
+        let parameters = document["paths"]["/palette.png"]["get"]["parameters"]
+            .as_array()
+            .unwrap();
+        let names: Vec<_> = parameters
+            .iter()
+            .map(|parameter| parameter["name"].as_str().unwrap())
+            .collect();
+        let accepted: Vec<_> = Parameter::ALL.iter().map(|parameter| parameter.name()).collect();
+        assert_eq!(names, accepted);
+
+        for parameter in parameters {
+            let name = parameter["name"].as_str().unwrap();
+            let schema = &parameter["schema"];
+            let examples: Vec<String> = match schema["type"].as_str().unwrap() {
+                "integer" => vec![schema["minimum"].as_u64().unwrap_or(1).to_string()],
+                "boolean" => vec!["true".into()],
+                _ if name == "easing" => schema["enum"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|easing| easing.as_str().unwrap().to_string())
+                    .collect(),
+                _ => vec!["ff8800,2255aa".into()],
+            };
+            for example in examples {
+                let query = format!("{name}={example}");
+                assert!(PaletteQuery::parse(&query).is_ok(), "{query}");
+            }
+        }
+        assert!(PaletteQuery::parse("undocumented=1").is_err());
+    }
+}
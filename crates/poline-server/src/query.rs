@@ -0,0 +1,252 @@
+//! Palette options from a URL query string, e.g.
+//! `?anchors=ff8800,2255aa&easing=arc&points=6`, or `?seed=42` for
+//! generated anchors.
+
+use poline_core::{parse_css_color, Poline, PolineErrors, PositionScale, Vector3};
+
+use crate::ServerError;
+
+/// The query parameters [`PaletteQuery::parse`] accepts, each documented
+/// in the OpenAPI schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    Anchors,
+    Easing,
+    Seed,
+    Points,
+    Closed,
+    Width,
+    Height,
+}
+
+impl Parameter {
+    pub const ALL: [Parameter; 7] = [
+        Parameter::Anchors,
+        Parameter::Easing,
+        Parameter::Seed,
+        Parameter::Points,
+        Parameter::Closed,
+        Parameter::Width,
+        Parameter::Height,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Parameter::Anchors => "anchors",
+            Parameter::Easing => "easing",
+            Parameter::Seed => "seed",
+            Parameter::Points => "points",
+            Parameter::Closed => "closed",
+            Parameter::Width => "width",
+            Parameter::Height => "height",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|parameter| parameter.name() == name)
+    }
+
+    /// The OpenAPI type of the value
+    pub const fn kind(self) -> &'static str {
+        match self {
+            Parameter::Anchors | Parameter::Easing => "string",
+            Parameter::Seed | Parameter::Points | Parameter::Width | Parameter::Height => {
+                "integer"
+            }
+            Parameter::Closed => "boolean",
+        }
+    }
+
+    /// The smallest and largest value of integer parameters, when bounded
+    pub const fn bounds(self) -> Option<(u64, u64)> {
+        match self {
+            Parameter::Points => Some((0, MAX_POINTS as u64)),
+            Parameter::Width | Parameter::Height => Some((1, MAX_SIZE as u64)),
+            _ => None,
+        }
+    }
+
+    /// The names `easing` takes, from [`PositionScale::ALL`]
+    pub fn easings() -> Vec<&'static str> {
+        PositionScale::ALL.iter().map(|scale| scale.as_str()).collect()
+    }
+
+    pub fn description(self) -> String {
+        let defaults = PaletteQuery::default();
+        match self {
+            Parameter::Anchors => {
+                "Comma separated anchor colors, CSS colors or bare hex like ff8800".to_string()
+            }
+            Parameter::Easing => {
+                format!("Position function of all axes: {}", Self::easings().join(", "))
+            }
+            Parameter::Seed => {
+                "Generates a light and a dark anchor from this seed when no anchors are given"
+                    .to_string()
+            }
+            Parameter::Points => format!(
+                "Points between each pair of anchors, {} by default",
+                defaults.points
+            ),
+            Parameter::Closed => "Connect the last anchor back to the first".to_string(),
+            Parameter::Width => {
+                format!("Image width in pixels, {} by default", defaults.width)
+            }
+            Parameter::Height => {
+                format!("Image height in pixels, {} by default", defaults.height)
+            }
+        }
+    }
+}
+
+/// Previews are never larger than this on either side
+pub const MAX_SIZE: u32 = 2048;
+
+/// The most points between two anchors a preview is sampled with, a wider
+/// preview would not show more of them
+pub const MAX_POINTS: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteQuery {
+    pub anchors: Vec<Vector3>,
+    pub easing: Option<usize>,
+    pub seed: Option<u64>,
+    pub points: usize,
+    pub closed: bool,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for PaletteQuery {
+    fn default() -> Self {
+        Self {
+            anchors: Vec::new(),
+            easing: None,
+            seed: None,
+            points: 4,
+            closed: false,
+            width: 400,
+            height: 40,
+        }
+    }
+}
+
+/// Decodes `%xx` escapes and `+` as a space
+fn percent_decode(value: &str) -> Result<String, ServerError> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [input.next(), input.next()];
+                let [Some(high), Some(low)] = hex else {
+                    return Err(ServerError::BadQuery("truncated escape"));
+                };
+                let digits = std::str::from_utf8(&[high, low])
+                    .ok()
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+                bytes.push(digits.ok_or(ServerError::BadQuery("invalid escape"))?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| ServerError::BadQuery("query is not UTF-8"))
+}
+
+fn parse_anchor(color: &str) -> Result<Vector3, PolineErrors> {
+    // `#` starts the fragment in URLs, so bare hex colors are accepted too
+    if color.len() == 6 && color.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        parse_css_color(&format!("#{color}"))
+    } else {
+        parse_css_color(color)
+    }
+}
+
+impl PaletteQuery {
+    pub fn parse(query: &str) -> Result<Self, ServerError> {
+        let mut parsed = PaletteQuery::default();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value)?;
+            let parameter =
+                Parameter::from_name(key).ok_or(ServerError::BadQuery("unknown parameter"))?;
+            match parameter {
+                Parameter::Anchors => {
+                    parsed.anchors = value
+                        .split(',')
+                        .map(parse_anchor)
+                        .collect::<Result<_, _>>()?;
+                }
+                Parameter::Easing => {
+                    let easing = value.parse::<PositionScale>();
+                    parsed.easing =
+                        Some(easing.map_err(|_| ServerError::BadQuery("unknown easing"))?.id());
+                }
+                Parameter::Seed => parsed.seed = Some(number(&value)?),
+                Parameter::Points => {
+                    parsed.points = number(&value)?;
+                    if parsed.points > MAX_POINTS {
+                        return Err(ServerError::BadQuery("too many points"));
+                    }
+                }
+                Parameter::Closed => parsed.closed = matches!(value.as_str(), "" | "1" | "true"),
+                Parameter::Width => parsed.width = number::<u32>(&value)?.clamp(1, MAX_SIZE),
+                Parameter::Height => parsed.height = number::<u32>(&value)?.clamp(1, MAX_SIZE),
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub fn poline(&self) -> Result<Poline, ServerError> {
+        let mut builder = Poline::builder()
+            .num_points(self.points)
+            .closed_loop(self.closed)
+            .inverted_lightness(true);
+        builder = match (self.anchors.is_empty(), self.seed) {
+            (false, _) => builder.anchors(self.anchors.iter().copied()),
+            (true, Some(seed)) => builder.seed(seed),
+            (true, None) => builder,
+        };
+        let mut poline = builder.build()?;
+        if let Some(easing) = self.easing {
+            poline.set_position_fn(easing);
+        }
+        Ok(poline)
+    }
+}
+
+fn number<T: std::str::FromStr>(value: &str) -> Result<T, ServerError> {
+    value
+        .parse()
+        .map_err(|_| ServerError::BadQuery("expected a number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_palette_queries() {
+        let query =
+            PaletteQuery::parse("anchors=ff8800,%232255aa&easing=arc&closed&width=9000").unwrap();
+        assert_eq!(query.anchors.len(), 2);
+        assert_eq!(query.easing, Some(7));
+        assert!(query.closed);
+        assert_eq!(query.width, MAX_SIZE);
+        assert!(PaletteQuery::parse("easing=bouncy").is_err());
+        assert!(PaletteQuery::parse("anchors=nope").is_err());
+        assert!(PaletteQuery::parse("color=red").is_err());
+        let points = |points: usize| PaletteQuery::parse(&format!("points={points}"));
+        assert_eq!(points(MAX_POINTS).unwrap().points, MAX_POINTS);
+        assert!(points(MAX_POINTS + 1).is_err());
+        assert!(points(1 << 19).is_err());
+    }
+
+    #[test]
+    fn seeds_are_reproducible() {
+        let a = PaletteQuery::parse("seed=42").unwrap().poline().unwrap();
+        let b = PaletteQuery::parse("seed=42").unwrap().poline().unwrap();
+        assert_eq!(a.colors_vec(), b.colors_vec());
+    }
+}
@@ -0,0 +1,99 @@
+use poline_core::SvgGradientOptions;
+
+use crate::{openapi, query::PaletteQuery, ServerError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{message}\n").into_bytes(),
+        }
+    }
+}
+
+fn svg_preview(query: &PaletteQuery) -> Result<Vec<u8>, ServerError> {
+    let poline = query.poline()?;
+    let (width, height) = (query.width, query.height);
+    let defs = poline.to_svg_gradient(&SvgGradientOptions::default());
+    // Hex colors only, nothing to escape
+    let title = poline.to_plain_preview();
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n<title>{title}</title>\n{defs}\
+         <rect width=\"{width}\" height=\"{height}\" fill=\"url(#poline)\"/>\n</svg>\n"
+    )
+    .into_bytes())
+}
+
+/// Answers a `GET` of `target`, the path and query of the request line
+pub fn handle(method: &str, target: &str) -> Response {
+    if method != "GET" {
+        return Response::error(405, "only GET is supported");
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path == "/openapi.json" {
+        return Response::ok(
+            "application/json",
+            openapi::document().to_string().into_bytes(),
+        );
+    }
+    let query = match PaletteQuery::parse(query) {
+        Ok(query) => query,
+        Err(err) => return Response::error(400, &err.to_string()),
+    };
+    let rendered = match path {
+        "/palette.svg" => svg_preview(&query).map(|svg| Response::ok("image/svg+xml", svg)),
+        "/palette.png" => query
+            .poline()
+            .map(|poline| Response::ok("image/png", poline.to_png(query.width, query.height))),
+        _ => return Response::error(404, "not found"),
+    };
+    rendered.unwrap_or_else(|err| Response::error(400, &err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_previews() {
+        let png = handle(
+            "GET",
+            "/palette.png?anchors=000000,ffffff&width=16&height=2",
+        );
+        assert_eq!(png.status, 200);
+        assert_eq!(png.content_type, "image/png");
+        assert_eq!(&png.body[1..4], b"PNG");
+
+        let svg = handle("GET", "/palette.svg?seed=7");
+        assert_eq!(svg.status, 200);
+        let svg = String::from_utf8(svg.body).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("fill=\"url(#poline)\""));
+
+        assert_eq!(handle("GET", "/palette.svg?anchors=000000").status, 400);
+        assert_eq!(handle("GET", "/palette.png?points=1000000").status, 400);
+        assert_eq!(
+            handle("GET", "/openapi.json").content_type,
+            "application/json"
+        );
+        assert_eq!(handle("GET", "/").status, 404);
+        assert_eq!(handle("POST", "/palette.svg").status, 405);
+    }
+}
@@ -1,6 +1,16 @@
-use poline_core::{Poline, PolineOptions, position_from_scale, number_as_enum, PolineErrors, PositionScale, random_hsl_pair};
+use poline_core::{ColorModel, HueInterpolation, InterpolationSpace, LutFormat, PointCountMode, Poline, PolineOp, PolineOptions, PositionScale, Vector2};
 use serde::{Serialize, Deserialize};
-use wasm_bindgen::{prelude::wasm_bindgen, JsValue, throw_str};
+use wasm_bindgen::{prelude::wasm_bindgen, Clamped, JsValue, throw_str};
+use web_sys::ImageData;
+
+mod store;
+
+pub use store::PolineStore;
+
+/// `initThreadPool(navigator.hardwareConcurrency)` has to be awaited from JS
+/// before generating palettes when built with the `parallel` feature
+#[cfg(feature = "parallel")]
+pub use wasm_bindgen_rayon::init_thread_pool;
 
 
 
@@ -13,19 +23,85 @@ pub struct PolineJsOptions {
     position_function_x: Option<usize>,
     position_function_y: Option<usize>,
     position_function_z: Option<usize>,
+    #[serde(default)]
+    reverse_x: bool,
+    #[serde(default)]
+    reverse_y: bool,
+    #[serde(default)]
+    reverse_z: bool,
     inverted_lightness: bool,
     closed_loop: bool,
+    color_model: Option<usize>,
+    #[serde(default)]
+    monotonic_lightness: bool,
+    #[serde(default)]
+    smooth_seam: bool,
+    /// 0 counts points per segment, 1 the total output
+    #[serde(default)]
+    point_count_mode: usize,
+    /// 0 eases the x, y, z position, 1 the HSL channels
+    #[serde(default)]
+    interpolation: usize,
+    /// 0 shorter, 1 longer, 2 increasing or 3 decreasing, as CSS
+    /// `hue-interpolation-method`
+    #[serde(default)]
+    hue_interpolation: usize,
+    #[serde(default)]
+    random_lightness_range: Option<Vector2>,
+    #[serde(default)]
+    random_saturation_range: Option<Vector2>,
+    /// Generates the same anchors for the same seed
+    #[serde(default)]
+    seed: Option<u64>,
 }
 
 impl PolineJsOptions {
+    /// The core options, throwing on position function or color model
+    /// numbers out of range
     pub fn as_rs_options(self) -> PolineOptions {
-        let mut options = PolineOptions::default();
-        options.num_points = self.num_points;
-        options.position_function = number_as_enum(self.position_function);
-        options.position_function_x = self.position_function_x.map(number_as_enum);
-        options.position_function_y = self.position_function_y.map(number_as_enum);
-        options.position_function_z = self.position_function_z.map(number_as_enum);
-        options
+        let position_function = |id: usize| {
+            PositionScale::from_id(id)
+                .unwrap_or_else(|| throw_str(&format!("unknown position function {id}")))
+        };
+        let color_model = |id: usize| {
+            ColorModel::from_id(id).unwrap_or_else(|| throw_str(&format!("unknown color model {id}")))
+        };
+        PolineOptions {
+            num_points: self.num_points,
+            point_count_mode: if self.point_count_mode == 1 {
+                PointCountMode::TotalOutput
+            } else {
+                PointCountMode::PerSegment
+            },
+            position_function: position_function(self.position_function),
+            position_function_x: self.position_function_x.map(position_function),
+            position_function_y: self.position_function_y.map(position_function),
+            position_function_z: self.position_function_z.map(position_function),
+            reverse_x: self.reverse_x,
+            reverse_y: self.reverse_y,
+            reverse_z: self.reverse_z,
+            inverted_lightness: self.inverted_lightness,
+            closed_loop: self.closed_loop,
+            color_model: self.color_model.map(color_model).unwrap_or_default(),
+            monotonic_lightness: self.monotonic_lightness,
+            smooth_seam: self.smooth_seam,
+            interpolation: if self.interpolation == 1 {
+                InterpolationSpace::Hsl
+            } else {
+                InterpolationSpace::Position
+            },
+            hue_interpolation: match self.hue_interpolation {
+                1 => HueInterpolation::Longer,
+                2 => HueInterpolation::Increasing,
+                3 => HueInterpolation::Decreasing,
+                _ => HueInterpolation::Shorter,
+            },
+            anchor_colors: None,
+            random_lightness_range: self.random_lightness_range,
+            random_saturation_range: self.random_saturation_range,
+            seed: self.seed,
+            ..Default::default()
+        }
     }
 }
 
@@ -37,9 +113,94 @@ pub fn poline(options: JsValue) -> Poline {
             eprintln!("{err}");
             throw_str(&format!("{err}"))
         }
-        Ok(options) => {
-            Poline::from(options.as_rs_options())
-        }
+        Ok(options) => match Poline::try_from_options(options.as_rs_options()) {
+            Ok(poline) => poline,
+            Err(err) => throw_str(&format!("{err}")),
+        },
+    }
+}
+
+/// Position function names for pickers, indexed by their
+/// `positionFunction` number
+#[wasm_bindgen(js_name = positionFunctionNames)]
+pub fn position_function_names() -> JsValue {
+    let names: Vec<&str> = PositionScale::ALL.iter().map(|scale| scale.as_str()).collect();
+    serde_wasm_bindgen::to_value(&names).unwrap()
+}
+
+/// The HSL colors of the palette as `[h, s, l]` arrays
+#[wasm_bindgen(js_name = paletteColors)]
+pub fn palette_colors(poline: &Poline) -> JsValue {
+    serde_wasm_bindgen::to_value(&poline.colors_vec()).unwrap()
+}
+
+/// The palette colors as CSS `hsl()` strings
+#[wasm_bindgen(js_name = paletteColorsCss)]
+pub fn palette_colors_css(poline: &Poline) -> JsValue {
+    serde_wasm_bindgen::to_value(&poline.css_strings()).unwrap()
+}
+
+#[wasm_bindgen(js_name = anchorPoints)]
+pub fn anchor_points(poline: &Poline) -> JsValue {
+    serde_wasm_bindgen::to_value(poline.anchors()).unwrap()
+}
+
+#[wasm_bindgen(js_name = flattenedPoints)]
+pub fn flattened_points(poline: &Poline) -> JsValue {
+    serde_wasm_bindgen::to_value(&poline.flattened_points()).unwrap()
+}
+
+/// Applies an array of edits like `{ op: "shiftHue", degrees: 30 }` all at
+/// once and returns the new colors, throws and leaves the palette as it was
+/// when one of them fails
+#[wasm_bindgen(js_name = applyOps)]
+pub fn apply_ops(poline: &mut Poline, ops: JsValue) -> JsValue {
+    let ops = match serde_wasm_bindgen::from_value::<Vec<PolineOp>>(ops) {
+        Ok(ops) => ops,
+        Err(err) => throw_str(&format!("{err}")),
+    };
+    if let Err(err) = poline.apply_ops(&ops) {
+        throw_str(&format!("{err}"))
+    }
+    palette_colors(poline)
+}
+
+/// A `width` x `height` strip of the continuous gradient, left to right,
+/// ready for `ctx.putImageData`
+#[wasm_bindgen(js_name = gradientImageData)]
+pub fn gradient_image_data(poline: &Poline, width: u32, height: u32) -> Result<ImageData, JsValue> {
+    let row = poline.to_lut_bytes(width as usize, LutFormat::Rgba8);
+    let data = row.repeat(height as usize);
+    ImageData::new_with_u8_clamped_array_and_sh(Clamped(&data), width, height)
+}
+
+/// The palette as a short string for `localStorage` or a URL hash
+#[wasm_bindgen(js_name = serializeState)]
+pub fn serialize_state(poline: &Poline) -> String {
+    poline.to_share_string()
+}
+
+/// Restores a palette saved with `serializeState`
+#[wasm_bindgen(js_name = PolineFromState)]
+pub fn poline_from_state(state: &str) -> Poline {
+    match Poline::from_share_string(state) {
+        Err(err) => throw_str(&format!("{err}")),
+        Ok(poline) => poline,
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_keep_lightness_and_loop() {
+        let options: PolineJsOptions = serde_json::from_str(
+            r#"{"numPoints":4,"positionFunction":0,"invertedLightness":true,"closedLoop":true}"#,
+        )
+        .unwrap();
+        let options = options.as_rs_options();
+        assert!(options.inverted_lightness);
+        assert!(options.closed_loop);
+    }
+}
@@ -0,0 +1,147 @@
+use js_sys::Function;
+use poline_core::{ColorPointCollection, Poline, PolineErrors, PolineOp, Relation};
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::{palette_colors, palette_colors_css};
+
+/// A `Poline` that tells subscribers about fresh colors after every change,
+/// shaped to back React or Svelte stores
+#[wasm_bindgen]
+pub struct PolineStore {
+    poline: Poline,
+    subscribers: Vec<(u32, Function)>,
+    derived_subscribers: Vec<(u32, Relation, Function)>,
+    next_id: u32,
+}
+
+#[wasm_bindgen]
+impl PolineStore {
+    #[wasm_bindgen(constructor)]
+    pub fn new(poline: Poline) -> PolineStore {
+        PolineStore {
+            poline,
+            subscribers: Vec::new(),
+            derived_subscribers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Calls `callback` with the current colors right away and again after
+    /// every change, returns the id to `unsubscribe` with
+    pub fn subscribe(&mut self, callback: Function) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        // Errors thrown by subscribers are theirs to handle
+        let _ = callback.call1(&JsValue::NULL, &palette_colors(&self.poline));
+        self.subscribers.push((id, callback));
+        id
+    }
+
+    /// Like `subscribe`, but `callback` gets the colors of the palette
+    /// derived by `relation`, so a linked palette regenerates whenever this
+    /// one changes
+    pub fn subscribe_derived(&mut self, relation: Relation, callback: Function) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let _ = callback.call1(&JsValue::NULL, &palette_colors(&self.poline.derive(&relation)));
+        self.derived_subscribers.push((id, relation, callback));
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: u32) {
+        self.subscribers.retain(|(subscriber, _)| *subscriber != id);
+        self.derived_subscribers
+            .retain(|(subscriber, _, _)| *subscriber != id);
+    }
+
+    fn notify(&self) {
+        let colors = palette_colors(&self.poline);
+        for (_, callback) in &self.subscribers {
+            let _ = callback.call1(&JsValue::NULL, &colors);
+        }
+        for (_, relation, callback) in &self.derived_subscribers {
+            let _ = callback.call1(&JsValue::NULL, &palette_colors(&self.poline.derive(relation)));
+        }
+    }
+
+    /// A copy of the current palette
+    pub fn poline(&self) -> Poline {
+        self.poline.clone()
+    }
+
+    /// Replaces the whole palette
+    pub fn set(&mut self, poline: Poline) {
+        self.poline = poline;
+        self.notify();
+    }
+
+    pub fn colors(&self) -> JsValue {
+        palette_colors(&self.poline)
+    }
+
+    pub fn colors_css(&self) -> JsValue {
+        palette_colors_css(&self.poline)
+    }
+
+    pub fn add_anchor_point(
+        &mut self,
+        initial: ColorPointCollection,
+        insert_at_index: Option<usize>,
+    ) -> Result<u32, PolineErrors> {
+        let id = self.poline.add_anchor_point(initial, insert_at_index)?;
+        self.notify();
+        Ok(id)
+    }
+
+    pub fn remove_anchor_point_at_index(&mut self, index: usize) -> Result<(), PolineErrors> {
+        self.poline.remove_anchor_point_at_index(index)?;
+        self.notify();
+        Ok(())
+    }
+
+    pub fn update_anchor_point_at_index(
+        &mut self,
+        index: usize,
+        initial: ColorPointCollection,
+    ) -> Result<(), PolineErrors> {
+        self.poline.update_anchor_point_at_index(index, initial)?;
+        self.notify();
+        Ok(())
+    }
+
+    /// Applies a batch of edits, see `applyOps`, and notifies subscribers
+    /// once. Returns the new colors.
+    #[wasm_bindgen(js_name = applyOps)]
+    pub fn apply_ops(&mut self, ops: JsValue) -> Result<JsValue, PolineErrors> {
+        let ops: Vec<PolineOp> =
+            serde_wasm_bindgen::from_value(ops).map_err(|_| PolineErrors::InvalidOption)?;
+        self.poline.apply_ops(&ops)?;
+        self.notify();
+        Ok(palette_colors(&self.poline))
+    }
+
+    pub fn shift_hue(&mut self, shift: f32) {
+        self.poline.shift_hue(shift);
+        self.notify();
+    }
+
+    pub fn set_position_fn(&mut self, scale_num: usize) {
+        self.poline.set_position_fn(scale_num);
+        self.notify();
+    }
+
+    pub fn set_reverse(&mut self, reverse_x: bool, reverse_y: bool, reverse_z: bool) {
+        self.poline.set_reverse(reverse_x, reverse_y, reverse_z);
+        self.notify();
+    }
+
+    pub fn set_monotonic_lightness(&mut self, monotonic_lightness: bool) {
+        self.poline.set_monotonic_lightness(monotonic_lightness);
+        self.notify();
+    }
+
+    pub fn set_smooth_seam(&mut self, smooth_seam: bool) {
+        self.poline.set_smooth_seam(smooth_seam);
+        self.notify();
+    }
+}